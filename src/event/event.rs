@@ -130,11 +130,16 @@ impl Event {
     /// | [OS selector] | Flag(s) checked |
     /// |---------------|-----------------|
     /// | [epoll]       | `EPOLLPRI`      |
-    /// | [kqueue]      | *Not supported* |
+    /// | [kqueue]      | `EVFILT_EXCEPT` |
+    ///
+    /// On kqueue `EVFILT_EXCEPT` is only supported on DragonFly BSD, iOS,
+    /// macOS and OpenBSD. Events are only triggered when registering with
+    /// [`Interest::PRIORITY`].
     ///
     /// [OS selector]: ../struct.Poll.html#implementation-notes
     /// [epoll]: http://man7.org/linux/man-pages/man7/epoll.7.html
     /// [kqueue]: https://www.freebsd.org/cgi/man.cgi?query=kqueue&sektion=2
+    /// [`Interest::PRIORITY`]: ../struct.Interest.html#associatedconstant.PRIORITY
     #[inline]
     pub fn is_priority(&self) -> bool {
         sys::event::is_priority(&self.inner)
@@ -32,6 +32,24 @@ const WRITABLE: u8 = 0b0_010;
 const AIO: u8 = 0b0_100;
 #[cfg_attr(not(target_os = "freebsd"), allow(dead_code))]
 const LIO: u8 = 0b1_000;
+#[cfg_attr(
+    not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "solaris"
+    )),
+    allow(dead_code)
+)]
+const PRIORITY: u8 = 0b1_000_000;
+// Registration modes, these don't represent readiness.
+const LEVEL: u8 = 0b10_000;
+const ONESHOT: u8 = 0b100_000;
+#[cfg_attr(not(any(target_os = "android", target_os = "linux")), allow(dead_code))]
+const EXCLUSIVE: u8 = 0b10_000_000;
 
 impl Interest {
     /// Returns a `Interest` set representing readable interests.
@@ -53,6 +71,95 @@ impl Interest {
     #[cfg(target_os = "freebsd")]
     pub const LIO: Interest = Interest(unsafe { NonZeroU8::new_unchecked(LIO) });
 
+    /// Returns a `Interest` set representing priority interests.
+    ///
+    /// Priority readiness is, for example, used for out-of-band (urgent) TCP
+    /// data. On platforms that use epoll this maps to `EPOLLPRI`, on platforms
+    /// that use kqueue it maps to `EVFILT_EXCEPT` with `NOTE_OOB`.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "solaris"
+    ))]
+    pub const PRIORITY: Interest = Interest(unsafe { NonZeroU8::new_unchecked(PRIORITY) });
+
+    /// Returns a `Interest` set representing level-triggered registration.
+    ///
+    /// By default all registrations are edge-triggered, meaning that an event
+    /// is only returned once the readiness of the event source changes. When
+    /// combined with readiness interests, e.g. `Interest::READABLE |
+    /// Interest::LEVEL`, the event source is registered level-triggered
+    /// instead: [`Poll::poll`] keeps returning events for it as long as it is
+    /// ready, so it doesn't need to be drained until it returns a
+    /// [`WouldBlock`] error.
+    ///
+    /// This is a registration mode, not a readiness interest, so it must be
+    /// combined with at least one readiness interest, otherwise registering
+    /// returns an `InvalidInput` error.
+    ///
+    /// [`Poll::poll`]: struct.Poll.html#method.poll
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    #[cfg(unix)]
+    pub const LEVEL: Interest = Interest(unsafe { NonZeroU8::new_unchecked(LEVEL) });
+
+    /// Returns a `Interest` set representing one-shot registration.
+    ///
+    /// When combined with readiness interests, e.g. `Interest::READABLE |
+    /// Interest::ONESHOT`, the event source is disarmed after a single event is
+    /// returned by [`Poll::poll`]. No further events are returned for it until
+    /// it is rearmed by calling [`Registry::reregister`]. This ensures only a
+    /// single thread handles the event source when multiple threads are
+    /// polling the same `Poll` instance.
+    ///
+    /// This is a registration mode, not a readiness interest, so it must be
+    /// combined with at least one readiness interest, otherwise registering
+    /// returns an `InvalidInput` error.
+    ///
+    /// [`Poll::poll`]: struct.Poll.html#method.poll
+    /// [`Registry::reregister`]: struct.Registry.html#method.reregister
+    #[cfg(unix)]
+    pub const ONESHOT: Interest = Interest(unsafe { NonZeroU8::new_unchecked(ONESHOT) });
+
+    /// Returns a `Interest` set representing exclusive wake up registration.
+    ///
+    /// This maps to `EPOLLEXCLUSIVE`. When the same file descriptor, e.g. a
+    /// listening socket, is registered with multiple `Poll` instances using
+    /// this mode only one (or a few) of the threads blocked in [`Poll::poll`]
+    /// are woken up when the file descriptor becomes ready, rather than all of
+    /// them. Note that the same [`event::Source`] can't be registered with
+    /// multiple `Registry`s, instead register the file descriptor using
+    /// [`SourceFd`].
+    ///
+    /// The kernel places restrictions on exclusive registrations, which Mio
+    /// checks before calling into the kernel:
+    ///
+    /// * It can't be combined with [`Interest::ONESHOT`] or
+    ///   [`Interest::PRIORITY`].
+    /// * It can only be used in [`Registry::register`], not in
+    ///   [`Registry::reregister`]. To change the registration the source must
+    ///   be deregistered and registered again.
+    ///
+    /// Exclusive registrations don't report read closed readiness
+    /// (`EPOLLRDHUP`) as the kernel doesn't allow it.
+    ///
+    /// This is a registration mode, not a readiness interest, so it must be
+    /// combined with at least one readiness interest, otherwise registering
+    /// returns an `InvalidInput` error.
+    ///
+    /// [`Poll::poll`]: struct.Poll.html#method.poll
+    /// [`event::Source`]: ./event/trait.Source.html
+    /// [`SourceFd`]: ./unix/struct.SourceFd.html
+    /// [`Interest::ONESHOT`]: struct.Interest.html#associatedconstant.ONESHOT
+    /// [`Interest::PRIORITY`]: struct.Interest.html#associatedconstant.PRIORITY
+    /// [`Registry::register`]: struct.Registry.html#method.register
+    /// [`Registry::reregister`]: struct.Registry.html#method.reregister
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub const EXCLUSIVE: Interest = Interest(unsafe { NonZeroU8::new_unchecked(EXCLUSIVE) });
+
     /// Add together two `Interest`.
     ///
     /// This does the same thing as the `BitOr` implementation, but is a
@@ -89,6 +196,46 @@ impl Interest {
     pub const fn is_lio(self) -> bool {
         (self.0.get() & LIO) != 0
     }
+
+    /// Returns true if `Interest` contains priority readiness.
+    pub const fn is_priority(self) -> bool {
+        (self.0.get() & PRIORITY) != 0
+    }
+
+    /// Returns true if `Interest` contains the level-triggered registration
+    /// mode.
+    pub const fn is_level(self) -> bool {
+        (self.0.get() & LEVEL) != 0
+    }
+
+    /// Returns true if `Interest` contains the one-shot registration mode.
+    pub const fn is_oneshot(self) -> bool {
+        (self.0.get() & ONESHOT) != 0
+    }
+
+    /// Returns true if `Interest` contains the exclusive wake up registration
+    /// mode.
+    pub const fn is_exclusive(self) -> bool {
+        (self.0.get() & EXCLUSIVE) != 0
+    }
+
+    /// Returns true if `Interest` contains any readiness, rather than only
+    /// registration modes.
+    pub(crate) const fn has_readiness(self) -> bool {
+        (self.0.get() & (READABLE | WRITABLE | AIO | LIO | PRIORITY)) != 0
+    }
+
+    /// Returns the raw bits, used in the log of `record::Recorder`.
+    #[cfg(feature = "record")]
+    pub(crate) const fn bits(self) -> u8 {
+        self.0.get()
+    }
+
+    /// Returns an `Interest` from its raw bits, if valid.
+    #[cfg(feature = "record")]
+    pub(crate) fn from_bits(bits: u8) -> Option<Interest> {
+        NonZeroU8::new(bits).map(Interest)
+    }
 }
 
 impl ops::BitOr for Interest {
@@ -149,6 +296,51 @@ impl fmt::Debug for Interest {
                 one = true
             }
         }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "openbsd",
+            target_os = "solaris"
+        ))]
+        {
+            if self.is_priority() {
+                if one {
+                    write!(fmt, " | ")?
+                }
+                write!(fmt, "PRIORITY")?;
+                one = true
+            }
+        }
+        #[cfg(unix)]
+        {
+            if self.is_level() {
+                if one {
+                    write!(fmt, " | ")?
+                }
+                write!(fmt, "LEVEL")?;
+                one = true
+            }
+            if self.is_oneshot() {
+                if one {
+                    write!(fmt, " | ")?
+                }
+                write!(fmt, "ONESHOT")?;
+                one = true
+            }
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if self.is_exclusive() {
+                if one {
+                    write!(fmt, " | ")?
+                }
+                write!(fmt, "EXCLUSIVE")?;
+                one = true
+            }
+        }
         debug_assert!(one, "printing empty interests");
         Ok(())
     }
@@ -3,12 +3,14 @@ use std::ops::{Deref, DerefMut};
 use std::os::unix::io::AsRawFd;
 #[cfg(windows)]
 use std::os::windows::io::AsRawSocket;
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{fmt, io};
 
-#[cfg(any(unix, debug_assertions))]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 use crate::poll;
+#[cfg(feature = "registration-checks")]
+use crate::poll::Registrations;
 use crate::sys::IoSourceState;
 use crate::{event, Interest, Registry, Token};
 
@@ -62,7 +64,7 @@ use crate::{event, Interest, Registry, Token};
 pub struct IoSource<T> {
     state: IoSourceState,
     inner: T,
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
     selector_id: SelectorId,
 }
 
@@ -72,7 +74,7 @@ impl<T> IoSource<T> {
         IoSource {
             state: IoSourceState::new(),
             inner: io,
-            #[cfg(debug_assertions)]
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
             selector_id: SelectorId::new(),
         }
     }
@@ -140,9 +142,13 @@ where
         token: Token,
         interests: Interest,
     ) -> io::Result<()> {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         self.selector_id.associate(registry)?;
-        poll::selector(registry).register(self.inner.as_raw_fd(), token, interests)
+        self.state
+            .register(registry, token, interests, self.inner.as_raw_fd())?;
+        #[cfg(feature = "registration-checks")]
+        self.selector_id.track(registry, token, interests);
+        Ok(())
     }
 
     fn reregister(
@@ -151,15 +157,22 @@ where
         token: Token,
         interests: Interest,
     ) -> io::Result<()> {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         self.selector_id.check_association(registry)?;
-        poll::selector(registry).reregister(self.inner.as_raw_fd(), token, interests)
+        self.state
+            .reregister(registry, token, interests, self.inner.as_raw_fd())?;
+        #[cfg(feature = "registration-checks")]
+        self.selector_id.track(registry, token, interests);
+        Ok(())
     }
 
     fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         self.selector_id.remove_association(registry)?;
-        poll::selector(registry).deregister(self.inner.as_raw_fd())
+        self.state.deregister(registry, self.inner.as_raw_fd())?;
+        #[cfg(feature = "registration-checks")]
+        self.selector_id.untrack();
+        Ok(())
     }
 }
 
@@ -174,10 +187,13 @@ where
         token: Token,
         interests: Interest,
     ) -> io::Result<()> {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         self.selector_id.associate(registry)?;
         self.state
-            .register(registry, token, interests, self.inner.as_raw_socket())
+            .register(registry, token, interests, self.inner.as_raw_socket())?;
+        #[cfg(feature = "registration-checks")]
+        self.selector_id.track(registry, token, interests);
+        Ok(())
     }
 
     fn reregister(
@@ -186,15 +202,21 @@ where
         token: Token,
         interests: Interest,
     ) -> io::Result<()> {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         self.selector_id.check_association(registry)?;
-        self.state.reregister(registry, token, interests)
+        self.state.reregister(registry, token, interests)?;
+        #[cfg(feature = "registration-checks")]
+        self.selector_id.track(registry, token, interests);
+        Ok(())
     }
 
     fn deregister(&mut self, _registry: &Registry) -> io::Result<()> {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         self.selector_id.remove_association(_registry)?;
-        self.state.deregister()
+        self.state.deregister()?;
+        #[cfg(feature = "registration-checks")]
+        self.selector_id.untrack();
+        Ok(())
     }
 }
 
@@ -208,22 +230,36 @@ where
 }
 
 /// Used to associate an `IoSource` with a `sys::Selector`.
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 #[derive(Debug)]
 struct SelectorId {
     id: AtomicUsize,
+    /// Unique key of the I/O source in `Registrations`.
+    #[cfg(feature = "registration-checks")]
+    key: usize,
+    /// Registrations of the `Registry` the I/O source is registered with.
+    #[cfg(feature = "registration-checks")]
+    registrations: Option<Registrations>,
 }
 
-#[cfg(debug_assertions)]
+/// Next value for `SelectorId::key`.
+#[cfg(feature = "registration-checks")]
+static NEXT_KEY: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 impl SelectorId {
     /// Value of `id` if `SelectorId` is not associated with any
     /// `sys::Selector`. Valid selector ids start at 1.
     const UNASSOCIATED: usize = 0;
 
     /// Create a new `SelectorId`.
-    const fn new() -> SelectorId {
+    fn new() -> SelectorId {
         SelectorId {
             id: AtomicUsize::new(Self::UNASSOCIATED),
+            #[cfg(feature = "registration-checks")]
+            key: NEXT_KEY.fetch_add(1, Ordering::Relaxed),
+            #[cfg(feature = "registration-checks")]
+            registrations: None,
         }
     }
 
@@ -231,9 +267,14 @@ impl SelectorId {
     /// already registered.
     fn associate(&self, registry: &Registry) -> io::Result<()> {
         let registry_id = poll::selector(&registry).id();
-        let previous_id = self.id.swap(registry_id, Ordering::AcqRel);
+        let result = self.id.compare_exchange(
+            Self::UNASSOCIATED,
+            registry_id,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
 
-        if previous_id == Self::UNASSOCIATED {
+        if result.is_ok() {
             Ok(())
         } else {
             Err(io::Error::new(
@@ -269,9 +310,14 @@ impl SelectorId {
     /// if it was not previously associated with `registry`.
     fn remove_association(&self, registry: &Registry) -> io::Result<()> {
         let registry_id = poll::selector(&registry).id();
-        let previous_id = self.id.swap(Self::UNASSOCIATED, Ordering::AcqRel);
+        let result = self.id.compare_exchange(
+            registry_id,
+            Self::UNASSOCIATED,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
 
-        if previous_id == registry_id {
+        if result.is_ok() {
             Ok(())
         } else {
             Err(io::Error::new(
@@ -282,11 +328,41 @@ impl SelectorId {
     }
 }
 
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 impl Clone for SelectorId {
     fn clone(&self) -> SelectorId {
         SelectorId {
             id: AtomicUsize::new(self.id.load(Ordering::Acquire)),
+            // The clone is a different I/O source.
+            #[cfg(feature = "registration-checks")]
+            key: NEXT_KEY.fetch_add(1, Ordering::Relaxed),
+            #[cfg(feature = "registration-checks")]
+            registrations: None,
         }
     }
 }
+
+#[cfg(feature = "registration-checks")]
+impl SelectorId {
+    /// Record the `token` and `interests` of the I/O source in the
+    /// registrations of `registry`.
+    fn track(&mut self, registry: &Registry, token: Token, interests: Interest) {
+        let registrations = poll::registrations(registry);
+        registrations.insert(self.key, token, interests);
+        self.registrations = Some(registrations.clone());
+    }
+
+    /// Remove the I/O source from the registrations it was recorded in.
+    fn untrack(&mut self) {
+        if let Some(registrations) = self.registrations.take() {
+            registrations.remove(self.key);
+        }
+    }
+}
+
+#[cfg(feature = "registration-checks")]
+impl Drop for SelectorId {
+    fn drop(&mut self) {
+        self.untrack();
+    }
+}
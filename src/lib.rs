@@ -69,25 +69,45 @@ mod waker;
 
 pub mod event;
 
-cfg_net! {
+cfg_io_source! {
     mod io_source;
+}
 
+cfg_net! {
     pub mod net;
 }
 
 #[doc(no_inline)]
 pub use event::Events;
 pub use interest::Interest;
+#[cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux")))]
+pub use poll::EpollRegistration;
 pub use poll::{Poll, Registry};
 pub use token::Token;
 pub use waker::Waker;
 
 #[cfg(all(unix, feature = "os-util"))]
 #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "os-util"))))]
-pub mod unix {
-    //! Unix only extensions.
-    pub use crate::sys::SourceFd;
-}
+pub mod unix;
+
+#[cfg(all(unix, feature = "mock"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "mock"))))]
+pub mod mock;
+
+#[cfg(all(unix, feature = "mock"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "mock"))))]
+pub mod mem;
+
+#[cfg(feature = "record")]
+#[cfg_attr(docsrs, doc(cfg(feature = "record")))]
+pub mod record;
+
+#[cfg(feature = "record")]
+mod readiness;
+
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+pub mod stats;
 
 // Enable with `cargo doc --features extra-docs`.
 #[cfg(feature = "extra-docs")]
@@ -109,7 +129,9 @@ pub mod features {
     #![cfg_attr(not(feature = "os-util"), doc = "## `os-util` (disabled)")]
     //!
     //! `os-util` enables additional OS specific facilities. Currently this
-    //! means the `unix` module (with `SourceFd`) becomes available.
+    //! means the `unix` module (with `SourceFd`, `pipe`, `pty`, `Signals` and
+    //! `Process`, and `Timer` and `Inotify` on Linux and Android) becomes
+    //! available.
     //!
     //! ## Network types
     //!
@@ -127,6 +149,81 @@ pub mod features {
     //!
     //! All types can be found in the `net` module.
     //!
+    #![cfg_attr(feature = "io-uring", doc = "## `io-uring` (enabled)")]
+    #![cfg_attr(not(feature = "io-uring"), doc = "## `io-uring` (disabled)")]
+    //!
+    //! On Linux `io-uring` replaces the epoll based selector with one based on
+    //! io_uring, this requires Linux 5.13 or later. Every registration becomes
+    //! a poll request on a single ring shared by `Poll` and its `Registry`s,
+    //! which are submitted in batches on the next call to `Poll::poll`. This
+    //! feature has no effect on other platforms.
+    //!
+    //! Note that a poll request keeps a reference to the file it polls. Types
+    //! provided by Mio remove their registration when dropped, but file
+    //! descriptors registered using `SourceFd` must be deregistered before
+    //! they're closed, otherwise the file isn't actually closed.
+    //!
+    #![cfg_attr(feature = "poll-fallback", doc = "## `poll-fallback` (enabled)")]
+    #![cfg_attr(not(feature = "poll-fallback"), doc = "## `poll-fallback` (disabled)")]
+    //!
+    //! `poll-fallback` replaces the platform's selector (epoll, io_uring or
+    //! kqueue) with one based on `poll(2)`. This is useful in sandboxes that
+    //! don't allow the use of epoll, or on platforms Mio doesn't otherwise
+    //! support. It is slower, especially with many registered sources.
+    //!
+    //! `poll(2)` only supports level-triggered notifications, edge-triggered
+    //! notifications are emulated by not reporting readiness again until an
+    //! I/O operation is performed on the source. This means spurious events
+    //! are more common. This works for all types provided by Mio, but sources
+    //! registered using `SourceFd` only get new events once they're
+    //! reregistered. Furthermore such file
+    //! descriptors must be deregistered before they're closed. Registering
+    //! with `Interest::EXCLUSIVE` is not supported.
+    //!
+    #![cfg_attr(feature = "mock", doc = "## `mock` (enabled)")]
+    #![cfg_attr(not(feature = "mock"), doc = "## `mock` (disabled)")]
+    //!
+    //! `mock` is meant for testing, it adds the `mock` module with event
+    //! sources whose readiness is set explicitly, which `Poll::poll` returns
+    //! before any events from the OS, the `mem` module with an in-memory
+    //! network built on them, and `record::Replay` to replay recorded events.
+    //! It implies `os-poll` and `record`. This feature is only supported on
+    //! Unix.
+    //!
+    #![cfg_attr(feature = "record", doc = "## `record` (enabled)")]
+    #![cfg_attr(not(feature = "record"), doc = "## `record` (disabled)")]
+    //!
+    //! `record` adds the `record` module, with `Recorder` to record the events
+    //! returned by `Poll` and the registrations made. Unlike `mock` this
+    //! doesn't change `Poll`, so it can be used in production. It implies
+    //! `os-poll`.
+    //!
+    #![cfg_attr(
+        feature = "registration-checks",
+        doc = "## `registration-checks` (enabled)"
+    )]
+    #![cfg_attr(
+        not(feature = "registration-checks"),
+        doc = "## `registration-checks` (disabled)"
+    )]
+    //!
+    //! In debug builds registering an event source provided by Mio with two
+    //! `Registry`s, registering it twice or deregistering it from a `Registry`
+    //! it isn't registered with returns an error. `registration-checks` keeps
+    //! these checks in release builds. It also adds
+    //! `Registry::registrations`, which returns the token and interests of the
+    //! registered event sources, e.g. to find sources that are never
+    //! deregistered.
+    //!
+    #![cfg_attr(feature = "stats", doc = "## `stats` (enabled)")]
+    #![cfg_attr(not(feature = "stats"), doc = "## `stats` (disabled)")]
+    //!
+    //! `stats` makes `Poll` keep statistics about its calls to `poll`, such as
+    //! the number of returned events and the time spent waiting for them, see
+    //! `Poll::stats`. It also adds `Poll::set_hook` to call a hook before and
+    //! after waiting for events. Without this feature `Poll` does neither. It
+    //! implies `os-poll`.
+    //!
     #![cfg_attr(feature = "extra-docs", doc = "## `extra-docs` (enabled)")]
     #![cfg_attr(not(feature = "extra-docs"), doc = "## `extra-docs` (disabled)")]
     //!
@@ -48,6 +48,35 @@ macro_rules! cfg_net {
     }
 }
 
+/// One of the features that need `IoSource` enabled, i.e. one of the network
+/// features, or `os-poll` and `os-util` for the `unix` event sources.
+#[cfg(unix)]
+macro_rules! cfg_io_source {
+    ($($item:item)*) => {
+        $(
+            #[cfg(any(
+                feature = "tcp",
+                feature = "udp",
+                feature = "uds",
+                all(feature = "os-poll", feature = "os-util")
+            ))]
+            $item
+        )*
+    }
+}
+
+/// One of the features that need `IoSource` enabled, i.e. one of the network
+/// features.
+#[cfg(windows)]
+macro_rules! cfg_io_source {
+    ($($item:item)*) => {
+        $(
+            #[cfg(any(feature = "tcp", feature = "udp"))]
+            $item
+        )*
+    }
+}
+
 /// Feature `tcp` enabled.
 macro_rules! cfg_tcp {
     ($($item:item)*) => {
@@ -105,3 +134,20 @@ macro_rules! cfg_any_os_util {
         )*
     }
 }
+
+/// Platforms that can report the length of a socket's send queue.
+macro_rules! cfg_send_queue_len {
+    ($($item:item)*) => {
+        $(
+            #[cfg(any(
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "netbsd"
+            ))]
+            $item
+        )*
+    }
+}
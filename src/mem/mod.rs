@@ -0,0 +1,153 @@
+//! In-memory network, for testing.
+//!
+//! This module provides types mirroring the API of the [`net`] module, which
+//! send data over a virtual [`Network`] rather than the OS. They're registered
+//! with a `Poll` instance like any other event source, making it possible to
+//! test protocol implementations without depending on kernel buffer sizes or
+//! timing. All sockets are on a single virtual host, any address can be bound.
+//!
+//! The `Network` can inject faults, see its documentation. Randomized faults,
+//! such as dropped datagrams, use a pseudo-random number generator with a
+//! fixed seed so tests are reproducible.
+//!
+//! These types are [mock event sources], which means their readiness is
+//! returned by `Poll::poll` before any events from the OS and the token
+//! [`WAKE_TOKEN`] is reserved.
+//!
+//! [`net`]: ../net/index.html
+//! [`Network`]: struct.Network.html
+//! [mock event sources]: ../mock/index.html
+//! [`WAKE_TOKEN`]: ../mock/constant.WAKE_TOKEN.html
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! use std::io::{Read, Write};
+//!
+//! use mio::mem::{Network, TcpListener, TcpStream};
+//! use mio::{Events, Interest, Poll, Token};
+//!
+//! let mut poll = Poll::new()?;
+//! let mut events = Events::with_capacity(8);
+//!
+//! let network = Network::new();
+//! // Every read returns at most 2 bytes.
+//! network.set_max_read(Some(2));
+//!
+//! let mut listener = TcpListener::bind(&network, "127.0.0.1:80".parse()?)?;
+//! poll.registry().register(&mut listener, Token(0), Interest::READABLE)?;
+//!
+//! let mut client = TcpStream::connect(&network, "127.0.0.1:80".parse()?)?;
+//! poll.poll(&mut events, None)?;
+//! let (mut server, _) = listener.accept()?;
+//!
+//! client.write_all(b"Hello")?;
+//! let mut buf = [0; 8];
+//! assert_eq!(server.read(&mut buf)?, 2);
+//! assert_eq!(&buf[..2], b"He");
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+use crate::sys::mock::{self, Mock};
+use crate::{poll, Interest, Registry, Token};
+
+mod network;
+pub use self::network::Network;
+
+mod tcp;
+pub use self::tcp::{TcpListener, TcpStream};
+
+mod udp;
+pub use self::udp::UdpSocket;
+
+/// Registration of a virtual socket, kept in the state shared with its peers
+/// so they can update its readiness.
+#[derive(Debug)]
+struct Registration {
+    id: usize,
+    /// This is `None` if the socket is not registered.
+    mock: Option<Mock>,
+}
+
+impl Registration {
+    fn new() -> Registration {
+        Registration {
+            id: mock::next_id(),
+            mock: None,
+        }
+    }
+
+    /// Register with `registry`, reporting the current `readiness`.
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+        readiness: u8,
+    ) -> io::Result<()> {
+        if self.mock.is_some() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        let mock = poll::mock(registry);
+        mock.register(self.id, token, interests)?;
+        mock.update(self.id, readiness, readiness);
+        self.mock = Some(mock.clone());
+        Ok(())
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+        readiness: u8,
+    ) -> io::Result<()> {
+        let mock = poll::mock(registry);
+        mock.reregister(self.id, token, interests)?;
+        mock.update(self.id, readiness, readiness);
+        Ok(())
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        poll::mock(registry).deregister(self.id)?;
+        self.mock = None;
+        Ok(())
+    }
+
+    /// Update the current `readiness`, `edge` is the readiness that changed.
+    fn update(&self, readiness: u8, edge: u8) {
+        if let Some(ref mock) = self.mock {
+            mock.update(self.id, readiness, edge);
+        }
+    }
+
+    /// Add `edge` readiness at `deadline`, or update the current `readiness`
+    /// if the deadline already passed.
+    fn update_at(&self, deadline: Instant, edge: u8, now: Instant, readiness: u8) {
+        match self.mock {
+            Some(ref mock) if deadline > now => mock.schedule(self.id, deadline, edge),
+            Some(_) => self.update(readiness, edge),
+            None => {}
+        }
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Some(mock) = self.mock.take() {
+            let _ = mock.deregister(self.id);
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // The state is always valid, even if a thread panicked.
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+
+use super::tcp::ListenerState;
+use super::udp::SocketState;
+
+/// Default size of the buffer of each direction of a connection.
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// First port used for ports picked by the network, e.g. when binding to port
+/// 0.
+const EPHEMERAL_PORT: u16 = 49152;
+
+/// Virtual network connecting the sockets in the [`mem`] module.
+///
+/// All sockets using (a clone of) the same `Network` can communicate. The
+/// `Network` also controls the faults injected into the communication:
+///
+/// * Latency: data, connections and datagrams are delivered after a delay,
+///   see [`set_latency`].
+/// * Partial reads and writes: reading and writing a [`TcpStream`] process at
+///   most a number of bytes at a time, see [`set_max_read`] and
+///   [`set_max_write`].
+/// * Full buffers: writing returns a [`WouldBlock`] error once the peer has a
+///   number of bytes unread, see [`set_buffer_size`].
+/// * Resets: see [`TcpStream::reset`].
+/// * Dropped and reordered datagrams: see [`set_datagram_loss`] and
+///   [`set_datagram_reorder`].
+///
+/// Faults can be changed at any time and affect all sockets, but only data
+/// sent afterwards.
+///
+/// [`mem`]: index.html
+/// [`set_latency`]: #method.set_latency
+/// [`TcpStream`]: struct.TcpStream.html
+/// [`set_max_read`]: #method.set_max_read
+/// [`set_max_write`]: #method.set_max_write
+/// [`WouldBlock`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.WouldBlock
+/// [`set_buffer_size`]: #method.set_buffer_size
+/// [`TcpStream::reset`]: struct.TcpStream.html#method.reset
+/// [`set_datagram_loss`]: #method.set_datagram_loss
+/// [`set_datagram_reorder`]: #method.set_datagram_reorder
+#[derive(Clone, Debug)]
+pub struct Network {
+    inner: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    faults: Faults,
+    /// State of the xorshift random number generator.
+    random: u64,
+    listeners: HashMap<SocketAddr, Weak<Mutex<ListenerState>>>,
+    /// Local addresses of connected TCP streams.
+    streams: HashSet<SocketAddr>,
+    udp_sockets: HashMap<SocketAddr, Weak<Mutex<SocketState>>>,
+    next_port: u16,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Faults {
+    pub(super) latency: Duration,
+    pub(super) max_read: Option<usize>,
+    pub(super) max_write: Option<usize>,
+    pub(super) buffer_size: usize,
+    pub(super) datagram_loss: f64,
+    pub(super) datagram_reorder: f64,
+}
+
+impl Network {
+    /// Create a new `Network` without any faults.
+    pub fn new() -> Network {
+        Network::with_seed(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Create a new `Network` using `seed` for randomized faults.
+    pub fn with_seed(seed: u64) -> Network {
+        let state = State {
+            faults: Faults {
+                latency: Duration::from_millis(0),
+                max_read: None,
+                max_write: None,
+                buffer_size: DEFAULT_BUFFER_SIZE,
+                datagram_loss: 0.0,
+                datagram_reorder: 0.0,
+            },
+            // Xorshift doesn't work with a zero state.
+            random: if seed == 0 { 1 } else { seed },
+            listeners: HashMap::new(),
+            streams: HashSet::new(),
+            udp_sockets: HashMap::new(),
+            next_port: EPHEMERAL_PORT,
+        };
+        Network {
+            inner: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Set the time it takes to deliver data, connections and datagrams.
+    ///
+    /// Defaults to zero, delivering everything immediately.
+    pub fn set_latency(&self, latency: Duration) {
+        self.lock().faults.latency = latency;
+    }
+
+    /// Set the maximum number of bytes returned by a single read of a
+    /// `TcpStream`, or `None` for no limit (the default).
+    pub fn set_max_read(&self, max: Option<usize>) {
+        self.lock().faults.max_read = max;
+    }
+
+    /// Set the maximum number of bytes accepted by a single write to a
+    /// `TcpStream`, or `None` for no limit (the default).
+    pub fn set_max_write(&self, max: Option<usize>) {
+        self.lock().faults.max_write = max;
+    }
+
+    /// Set the number of bytes a `TcpStream` can send before its peer reads
+    /// them, including data not yet delivered.
+    ///
+    /// Defaults to 64 KiB.
+    pub fn set_buffer_size(&self, size: usize) {
+        self.lock().faults.buffer_size = size;
+    }
+
+    /// Set the probability, between 0.0 and 1.0, of a datagram sent by a
+    /// `UdpSocket` being dropped.
+    ///
+    /// Defaults to 0.0.
+    pub fn set_datagram_loss(&self, probability: f64) {
+        self.lock().faults.datagram_loss = probability;
+    }
+
+    /// Set the probability, between 0.0 and 1.0, of a datagram sent by a
+    /// `UdpSocket` being delivered before datagrams sent earlier.
+    ///
+    /// Defaults to 0.0.
+    pub fn set_datagram_reorder(&self, probability: f64) {
+        self.lock().faults.datagram_reorder = probability;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The state is always valid, even if a thread panicked.
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub(super) fn faults(&self) -> Faults {
+        self.lock().faults.clone()
+    }
+
+    /// Returns true with `probability`.
+    pub(super) fn chance(&self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        // Use the upper 53 bits, the precision of `f64`.
+        let x = self.lock().random() >> 11;
+        (x as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    /// Returns a random number in `0..n`, `n` must not be zero.
+    pub(super) fn random_below(&self, n: usize) -> usize {
+        (self.lock().random() % n as u64) as usize
+    }
+
+    pub(super) fn bind_listener(
+        &self,
+        addr: SocketAddr,
+        listener: &Arc<Mutex<ListenerState>>,
+    ) -> io::Result<SocketAddr> {
+        let mut state = self.lock();
+        let addr = state.bind(addr, |state, addr| {
+            state.listeners.contains_key(addr) || state.streams.contains(addr)
+        })?;
+        state.listeners.insert(addr, Arc::downgrade(listener));
+        Ok(addr)
+    }
+
+    pub(super) fn unbind_listener(&self, addr: SocketAddr) {
+        self.lock().listeners.remove(&addr);
+    }
+
+    /// Look up the listener for `addr` and allocate a local address for a
+    /// stream connecting to it.
+    pub(super) fn connect(
+        &self,
+        addr: SocketAddr,
+    ) -> io::Result<(Arc<Mutex<ListenerState>>, SocketAddr)> {
+        let mut state = self.lock();
+        let listener = lookup(&state.listeners, addr).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "no listener bound to address",
+            )
+        })?;
+        let local_addr = state.bind(SocketAddr::new(addr.ip(), 0), |state, addr| {
+            state.listeners.contains_key(addr) || state.streams.contains(addr)
+        })?;
+        state.streams.insert(local_addr);
+        Ok((listener, local_addr))
+    }
+
+    pub(super) fn unbind_stream(&self, addr: SocketAddr) {
+        self.lock().streams.remove(&addr);
+    }
+
+    pub(super) fn bind_udp(
+        &self,
+        addr: SocketAddr,
+        socket: &Arc<Mutex<SocketState>>,
+    ) -> io::Result<SocketAddr> {
+        let mut state = self.lock();
+        let addr = state.bind(addr, |state, addr| state.udp_sockets.contains_key(addr))?;
+        state.udp_sockets.insert(addr, Arc::downgrade(socket));
+        Ok(addr)
+    }
+
+    pub(super) fn unbind_udp(&self, addr: SocketAddr) {
+        self.lock().udp_sockets.remove(&addr);
+    }
+
+    pub(super) fn lookup_udp(&self, addr: SocketAddr) -> Option<Arc<Mutex<SocketState>>> {
+        lookup(&self.lock().udp_sockets, addr)
+    }
+}
+
+impl Default for Network {
+    fn default() -> Network {
+        Network::new()
+    }
+}
+
+impl State {
+    /// Returns the next pseudo-random number.
+    fn random(&mut self) -> u64 {
+        let mut x = self.random;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.random = x;
+        x
+    }
+
+    /// Returns the address to bind to, picking a port if the port of `addr`
+    /// is zero.
+    fn bind<F>(&mut self, addr: SocketAddr, in_use: F) -> io::Result<SocketAddr>
+    where
+        F: Fn(&State, &SocketAddr) -> bool,
+    {
+        if addr.port() != 0 {
+            return if in_use(self, &addr) {
+                Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "address already in use",
+                ))
+            } else {
+                Ok(addr)
+            };
+        }
+
+        for _ in EPHEMERAL_PORT..=u16::max_value() {
+            let port = self.next_port;
+            self.next_port = port.checked_add(1).unwrap_or(EPHEMERAL_PORT);
+            let addr = SocketAddr::new(addr.ip(), port);
+            if !in_use(self, &addr) {
+                return Ok(addr);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            "no ports available",
+        ))
+    }
+}
+
+/// Look up the socket bound to `addr`, or to the unspecified address with the
+/// same port.
+fn lookup<T>(sockets: &HashMap<SocketAddr, Weak<T>>, addr: SocketAddr) -> Option<Arc<T>> {
+    let unspecified = match addr.ip() {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    sockets
+        .get(&addr)
+        .or_else(|| sockets.get(&SocketAddr::new(unspecified, addr.port())))
+        .and_then(Weak::upgrade)
+}
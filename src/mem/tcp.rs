@@ -0,0 +1,568 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{cmp, fmt};
+
+use super::{lock, Network, Registration};
+use crate::sys::mock::readiness::*;
+use crate::{event, Interest, Registry, Token};
+
+/// A TCP socket server in a virtual [`Network`], listening for connections.
+///
+/// This mirrors the API of [`net::TcpListener`].
+///
+/// [`Network`]: struct.Network.html
+/// [`net::TcpListener`]: ../net/struct.TcpListener.html
+pub struct TcpListener {
+    network: Network,
+    state: Arc<Mutex<ListenerState>>,
+    local_addr: SocketAddr,
+}
+
+#[derive(Debug)]
+pub(super) struct ListenerState {
+    /// Connections not yet accepted, with the time they're delivered.
+    backlog: VecDeque<(Instant, TcpStream)>,
+    registration: Registration,
+}
+
+impl ListenerState {
+    fn readiness(&self, now: Instant) -> u8 {
+        match self.backlog.front() {
+            Some((deadline, _)) if *deadline <= now => READABLE,
+            _ => 0,
+        }
+    }
+}
+
+impl TcpListener {
+    /// Creates a new `TcpListener` bound to `addr` in `network`.
+    ///
+    /// If the port of `addr` is zero the network picks a port, use
+    /// [`local_addr`] to retrieve it.
+    ///
+    /// [`local_addr`]: #method.local_addr
+    pub fn bind(network: &Network, addr: SocketAddr) -> io::Result<TcpListener> {
+        let state = Arc::new(Mutex::new(ListenerState {
+            backlog: VecDeque::new(),
+            registration: Registration::new(),
+        }));
+        let local_addr = network.bind_listener(addr, &state)?;
+        Ok(TcpListener {
+            network: network.clone(),
+            state,
+            local_addr,
+        })
+    }
+
+    /// Accepts a new `TcpStream`.
+    ///
+    /// If an accepted stream is returned, the remote address of the peer is
+    /// returned along with it.
+    pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let mut state = lock(&self.state);
+        let now = Instant::now();
+        let res = if state.readiness(now) != 0 {
+            let (_, stream) = state.backlog.pop_front().unwrap();
+            let peer_addr = stream.peer_addr;
+            Ok((stream, peer_addr))
+        } else {
+            Err(io::ErrorKind::WouldBlock.into())
+        };
+        let readiness = state.readiness(now);
+        state.registration.update(readiness, 0);
+        res
+    }
+
+    /// Returns the local socket address of this listener.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket, this is always
+    /// `None`.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        Ok(None)
+    }
+}
+
+impl event::Source for TcpListener {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        let mut state = lock(&self.state);
+        let readiness = state.readiness(Instant::now());
+        state
+            .registration
+            .register(registry, token, interests, readiness)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        let mut state = lock(&self.state);
+        let readiness = state.readiness(Instant::now());
+        state
+            .registration
+            .reregister(registry, token, interests, readiness)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        lock(&self.state).registration.deregister(registry)
+    }
+}
+
+impl Drop for TcpListener {
+    fn drop(&mut self) {
+        self.network.unbind_listener(self.local_addr);
+        // Close the connections not yet accepted.
+        let backlog = {
+            let mut state = lock(&self.state);
+            state.backlog.drain(..).collect::<Vec<_>>()
+        };
+        drop(backlog);
+    }
+}
+
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpListener")
+            .field("local_addr", &self.local_addr)
+            .finish()
+    }
+}
+
+/// A TCP stream between a local and a remote socket in a virtual [`Network`].
+///
+/// This mirrors the API of [`net::TcpStream`]. Dropping the stream closes the
+/// connection, after which the peer reads any remaining data followed by
+/// end-of-file, writing to the peer returns a [`BrokenPipe`] error.
+///
+/// [`Network`]: struct.Network.html
+/// [`net::TcpStream`]: ../net/struct.TcpStream.html
+/// [`BrokenPipe`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.BrokenPipe
+pub struct TcpStream {
+    network: Network,
+    connection: Arc<Mutex<Connection>>,
+    /// Side of the `connection`, either 0 or 1.
+    side: usize,
+    local_addr: SocketAddr,
+    peer_addr: SocketAddr,
+    /// True if `local_addr` should be unbound when dropped.
+    bound: bool,
+}
+
+#[derive(Debug)]
+struct Connection {
+    /// Data sent by each side.
+    pipes: [Pipe; 2],
+    registrations: [Registration; 2],
+    /// Each side shut down reading.
+    read_shutdown: [bool; 2],
+    /// Error returned by `take_error` of each side.
+    errors: [Option<io::ErrorKind>; 2],
+    /// The connection was reset.
+    reset: bool,
+}
+
+/// One direction of a `Connection`.
+#[derive(Debug, Default)]
+struct Pipe {
+    /// Data sent, with the time it's delivered.
+    segments: VecDeque<(Instant, Vec<u8>)>,
+    /// Number of bytes in `segments`.
+    len: usize,
+    /// Time the end-of-file is delivered, once the writing side shut down.
+    closed: Option<Instant>,
+    /// The receiving side is dropped.
+    dropped: bool,
+}
+
+impl Pipe {
+    /// Time the next data (or end-of-file) sent will be delivered.
+    fn deadline(&self, now: Instant, latency: Duration) -> Instant {
+        let deadline = now + latency;
+        // Data must be delivered in order.
+        match self.segments.back() {
+            Some((last, _)) if *last > deadline => *last,
+            _ => deadline,
+        }
+    }
+
+    fn is_readable(&self, now: Instant) -> bool {
+        match self.segments.front() {
+            Some((deadline, _)) => *deadline <= now,
+            None => false,
+        }
+    }
+
+    fn is_closed(&self, now: Instant) -> bool {
+        match self.closed {
+            Some(deadline) => deadline <= now,
+            None => false,
+        }
+    }
+
+    /// Read data delivered before `now` into `buf`.
+    fn read(&mut self, buf: &mut [u8], now: Instant, consume: bool) -> usize {
+        let mut n = 0;
+        for (deadline, data) in self.segments.iter() {
+            if *deadline > now || n == buf.len() {
+                break;
+            }
+            let len = cmp::min(data.len(), buf.len() - n);
+            buf[n..n + len].copy_from_slice(&data[..len]);
+            n += len;
+        }
+        if consume {
+            self.consume(n);
+        }
+        n
+    }
+
+    fn consume(&mut self, mut n: usize) {
+        self.len -= n;
+        while n > 0 {
+            let data = &mut self.segments.front_mut().unwrap().1;
+            if data.len() <= n {
+                n -= data.len();
+                self.segments.pop_front();
+            } else {
+                data.drain(..n);
+                n = 0;
+            }
+        }
+    }
+}
+
+impl Connection {
+    fn new() -> Connection {
+        Connection {
+            pipes: [Pipe::default(), Pipe::default()],
+            registrations: [Registration::new(), Registration::new()],
+            read_shutdown: [false, false],
+            errors: [None, None],
+            reset: false,
+        }
+    }
+
+    fn readiness(&self, side: usize, now: Instant, buffer_size: usize) -> u8 {
+        if self.reset {
+            return READABLE | WRITABLE | ERROR | READ_CLOSED | WRITE_CLOSED;
+        }
+        let (send, recv) = (&self.pipes[side], &self.pipes[1 - side]);
+        let mut readiness = 0;
+        if recv.is_readable(now) {
+            readiness |= READABLE;
+        }
+        if recv.is_closed(now) || self.read_shutdown[side] {
+            readiness |= READABLE | READ_CLOSED;
+        }
+        if send.dropped {
+            readiness |= WRITABLE | WRITE_CLOSED;
+        } else if send.len < buffer_size {
+            readiness |= WRITABLE;
+        }
+        readiness
+    }
+
+    /// Update the level-triggered readiness of `side`.
+    fn update(&self, side: usize, now: Instant, buffer_size: usize) {
+        let readiness = self.readiness(side, now, buffer_size);
+        self.registrations[side].update(readiness, 0);
+    }
+
+    /// Add `edge` readiness to `side`, at `deadline`.
+    fn update_at(
+        &self,
+        side: usize,
+        deadline: Instant,
+        edge: u8,
+        now: Instant,
+        buffer_size: usize,
+    ) {
+        let readiness = self.readiness(side, now, buffer_size);
+        self.registrations[side].update_at(deadline, edge, now, readiness);
+    }
+}
+
+impl TcpStream {
+    /// Create a new TCP stream and issue a non-blocking connect to the
+    /// specified address.
+    ///
+    /// Unlike [`net::TcpStream::connect`] this returns a
+    /// [`ConnectionRefused`] error if no [`TcpListener`] is bound to `addr`.
+    /// Otherwise the stream is connected, its peer is returned by
+    /// [`TcpListener::accept`] once the connection is delivered.
+    ///
+    /// [`net::TcpStream::connect`]: ../net/struct.TcpStream.html#method.connect
+    /// [`ConnectionRefused`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.ConnectionRefused
+    /// [`TcpListener`]: struct.TcpListener.html
+    /// [`TcpListener::accept`]: struct.TcpListener.html#method.accept
+    pub fn connect(network: &Network, addr: SocketAddr) -> io::Result<TcpStream> {
+        let (listener, local_addr) = network.connect(addr)?;
+        let connection = Arc::new(Mutex::new(Connection::new()));
+        let stream = TcpStream {
+            network: network.clone(),
+            connection: connection.clone(),
+            side: 0,
+            local_addr,
+            peer_addr: addr,
+            bound: true,
+        };
+        let peer = TcpStream {
+            network: network.clone(),
+            connection,
+            side: 1,
+            local_addr: addr,
+            peer_addr: local_addr,
+            bound: false,
+        };
+
+        let now = Instant::now();
+        let deadline = now + network.faults().latency;
+        let mut listener = lock(&listener);
+        listener.backlog.push_back((deadline, peer));
+        let readiness = listener.readiness(now);
+        listener
+            .registration
+            .update_at(deadline, READABLE, now, readiness);
+        Ok(stream)
+    }
+
+    /// Returns the socket address of the remote peer of this TCP connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.peer_addr)
+    }
+
+    /// Returns the socket address of the local half of this TCP connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    ///
+    /// Shutting down the write half delivers end-of-file to the peer, after
+    /// any data already written.
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        let faults = self.network.faults();
+        let mut connection = lock(&self.connection);
+        let now = Instant::now();
+        let side = self.side;
+        if how != Shutdown::Write {
+            connection.read_shutdown[side] = true;
+        }
+        if how != Shutdown::Read && connection.pipes[side].closed.is_none() {
+            let deadline = connection.pipes[side].deadline(now, faults.latency);
+            connection.pipes[side].closed = Some(deadline);
+            connection.update_at(
+                1 - side,
+                deadline,
+                READABLE | READ_CLOSED,
+                now,
+                faults.buffer_size,
+            );
+        }
+        connection.update(side, now, faults.buffer_size);
+        Ok(())
+    }
+
+    /// Reset the connection, simulating the peer aborting it.
+    ///
+    /// All data not yet read is discarded. Reading from and writing to either
+    /// side returns a [`ConnectionReset`] error and both sides receive an
+    /// event with error, read closed and write closed readiness.
+    ///
+    /// [`ConnectionReset`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.ConnectionReset
+    pub fn reset(&self) {
+        let mut connection = lock(&self.connection);
+        if connection.reset {
+            return;
+        }
+        connection.reset = true;
+        connection.errors = [Some(io::ErrorKind::ConnectionReset); 2];
+        for pipe in connection.pipes.iter_mut() {
+            pipe.segments.clear();
+            pipe.len = 0;
+        }
+        let readiness = connection.readiness(0, Instant::now(), 0);
+        for registration in connection.registrations.iter() {
+            registration.update(readiness, readiness);
+        }
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket.
+    ///
+    /// This returns a `ConnectionReset` error once after the connection is
+    /// reset.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        let mut connection = lock(&self.connection);
+        Ok(connection.errors[self.side].take().map(io::Error::from))
+    }
+
+    /// Receives data on the socket from the remote address to which it is
+    /// connected, without removing that data from the queue. On success,
+    /// returns the number of bytes peeked.
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv(buf, false)
+    }
+
+    fn recv(&self, buf: &mut [u8], consume: bool) -> io::Result<usize> {
+        let faults = self.network.faults();
+        let mut connection = lock(&self.connection);
+        let now = Instant::now();
+        let side = self.side;
+        if connection.reset {
+            return Err(io::ErrorKind::ConnectionReset.into());
+        } else if connection.read_shutdown[side] {
+            return Ok(0);
+        }
+
+        let max = faults.max_read.unwrap_or(buf.len());
+        let len = cmp::min(buf.len(), max);
+        let pipe = &mut connection.pipes[1 - side];
+        let was_full = pipe.len >= faults.buffer_size;
+        let n = pipe.read(&mut buf[..len], now, consume);
+        let res = if n != 0 || len == 0 || (pipe.is_closed(now) && pipe.segments.is_empty()) {
+            Ok(n)
+        } else {
+            Err(io::ErrorKind::WouldBlock.into())
+        };
+
+        connection.update(side, now, faults.buffer_size);
+        if was_full && n != 0 && consume {
+            // Peer can write again.
+            connection.update_at(1 - side, now, WRITABLE, now, faults.buffer_size);
+        }
+        res
+    }
+
+    fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        let faults = self.network.faults();
+        let mut connection = lock(&self.connection);
+        let now = Instant::now();
+        let side = self.side;
+        if connection.reset {
+            return Err(io::ErrorKind::ConnectionReset.into());
+        }
+        let pipe = &mut connection.pipes[side];
+        if pipe.closed.is_some() || pipe.dropped {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        } else if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let max = faults.max_write.unwrap_or(buf.len());
+        let available = faults.buffer_size.saturating_sub(pipe.len);
+        let n = cmp::min(cmp::min(buf.len(), max), available);
+        let res = if n != 0 {
+            let deadline = pipe.deadline(now, faults.latency);
+            pipe.segments.push_back((deadline, buf[..n].to_vec()));
+            pipe.len += n;
+            connection.update_at(1 - side, deadline, READABLE, now, faults.buffer_size);
+            Ok(n)
+        } else {
+            Err(io::ErrorKind::WouldBlock.into())
+        };
+        connection.update(side, now, faults.buffer_size);
+        res
+    }
+}
+
+impl Read for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv(buf, true)
+    }
+}
+
+impl<'a> Read for &'a TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv(buf, true)
+    }
+}
+
+impl Write for TcpStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> Write for &'a TcpStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl event::Source for TcpStream {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        let buffer_size = self.network.faults().buffer_size;
+        let mut connection = lock(&self.connection);
+        let readiness = connection.readiness(self.side, Instant::now(), buffer_size);
+        connection.registrations[self.side].register(registry, token, interests, readiness)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        let buffer_size = self.network.faults().buffer_size;
+        let mut connection = lock(&self.connection);
+        let readiness = connection.readiness(self.side, Instant::now(), buffer_size);
+        connection.registrations[self.side].reregister(registry, token, interests, readiness)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        lock(&self.connection).registrations[self.side].deregister(registry)
+    }
+}
+
+impl Drop for TcpStream {
+    fn drop(&mut self) {
+        let _ = self.shutdown(Shutdown::Both);
+        {
+            let faults = self.network.faults();
+            let mut connection = lock(&self.connection);
+            let side = self.side;
+            connection.pipes[1 - side].dropped = true;
+            let now = Instant::now();
+            connection.update_at(1 - side, now, WRITE_CLOSED, now, faults.buffer_size);
+            // Remove our registration.
+            connection.registrations[side] = Registration::new();
+        }
+        if self.bound {
+            self.network.unbind_stream(self.local_addr);
+        }
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpStream")
+            .field("local_addr", &self.local_addr)
+            .field("peer_addr", &self.peer_addr)
+            .finish()
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use std::{cmp, fmt};
+
+use super::{lock, Network, Registration};
+use crate::sys::mock::readiness::*;
+use crate::{event, Interest, Registry, Token};
+
+/// A UDP socket in a virtual [`Network`].
+///
+/// This mirrors the API of [`net::UdpSocket`]. Datagrams are subject to the
+/// latency, loss and reordering of the `Network`. Like with a real UDP socket
+/// sending a datagram to an address without a socket bound to it succeeds, the
+/// datagram is dropped.
+///
+/// [`Network`]: struct.Network.html
+/// [`net::UdpSocket`]: ../net/struct.UdpSocket.html
+pub struct UdpSocket {
+    network: Network,
+    state: Arc<Mutex<SocketState>>,
+    local_addr: SocketAddr,
+}
+
+#[derive(Debug)]
+pub(super) struct SocketState {
+    /// Datagrams received, with the time they're delivered and the source
+    /// address.
+    datagrams: VecDeque<(Instant, SocketAddr, Vec<u8>)>,
+    /// Address set using `connect`.
+    peer_addr: Option<SocketAddr>,
+    registration: Registration,
+}
+
+impl SocketState {
+    fn readiness(&self, now: Instant) -> u8 {
+        if self.next(now).is_some() {
+            READABLE | WRITABLE
+        } else {
+            WRITABLE
+        }
+    }
+
+    /// Returns the index of the next datagram delivered before `now`.
+    fn next(&self, now: Instant) -> Option<usize> {
+        self.datagrams
+            .iter()
+            .position(|(deadline, _, _)| *deadline <= now)
+    }
+}
+
+impl UdpSocket {
+    /// Creates a UDP socket bound to `addr` in `network`.
+    ///
+    /// If the port of `addr` is zero the network picks a port, use
+    /// [`local_addr`] to retrieve it.
+    ///
+    /// [`local_addr`]: #method.local_addr
+    pub fn bind(network: &Network, addr: SocketAddr) -> io::Result<UdpSocket> {
+        let state = Arc::new(Mutex::new(SocketState {
+            datagrams: VecDeque::new(),
+            peer_addr: None,
+            registration: Registration::new(),
+        }));
+        let local_addr = network.bind_udp(addr, &state)?;
+        Ok(UdpSocket {
+            network: network.clone(),
+            state,
+            local_addr,
+        })
+    }
+
+    /// Returns the socket address that this socket was created from.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    /// Returns the socket address of the remote peer this socket was connected
+    /// to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        lock(&self.state)
+            .peer_addr
+            .ok_or_else(|| io::ErrorKind::NotConnected.into())
+    }
+
+    /// Connects the UDP socket, setting the default destination for `send()`
+    /// and limiting packets that are read via `recv` from the address
+    /// specified in `addr`.
+    pub fn connect(&self, addr: SocketAddr) -> io::Result<()> {
+        let mut state = lock(&self.state);
+        state.peer_addr = Some(addr);
+        state.datagrams.retain(|(_, source, _)| *source == addr);
+        let readiness = state.readiness(Instant::now());
+        state.registration.update(readiness, 0);
+        Ok(())
+    }
+
+    /// Sends data on the socket to the given address. On success, returns the
+    /// number of bytes written.
+    pub fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        let faults = self.network.faults();
+        if self.network.chance(faults.datagram_loss) {
+            return Ok(buf.len());
+        }
+        let socket = match self.network.lookup_udp(target) {
+            Some(socket) => socket,
+            None => return Ok(buf.len()),
+        };
+
+        let mut state = lock(&socket);
+        match state.peer_addr {
+            Some(peer_addr) if peer_addr != self.local_addr => return Ok(buf.len()),
+            _ => {}
+        }
+        let now = Instant::now();
+        let deadline = now + faults.latency;
+        let datagram = (deadline, self.local_addr, buf.to_vec());
+        if !state.datagrams.is_empty() && self.network.chance(faults.datagram_reorder) {
+            // Deliver it before a datagram that is still queued.
+            let index = self.network.random_below(state.datagrams.len());
+            let deadline = cmp::min(deadline, state.datagrams[index].0);
+            state
+                .datagrams
+                .insert(index, (deadline, datagram.1, datagram.2));
+        } else {
+            state.datagrams.push_back(datagram);
+        }
+        let readiness = state.readiness(now);
+        state
+            .registration
+            .update_at(deadline, READABLE, now, readiness);
+        Ok(buf.len())
+    }
+
+    /// Receives data from the socket. On success, returns the number of bytes
+    /// read and the address from whence the data came.
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.recv_datagram(buf, true)
+    }
+
+    /// Receives data from the socket, without removing it from the input
+    /// queue. On success, returns the number of bytes read and the address
+    /// from whence the data came.
+    pub fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.recv_datagram(buf, false)
+    }
+
+    /// Sends data on the socket to the address previously bound via
+    /// `connect()`. On success, returns the number of bytes written.
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        let peer_addr = self.peer_addr()?;
+        self.send_to(buf, peer_addr)
+    }
+
+    /// Receives data from the socket previously bound with `connect()`. On
+    /// success, returns the number of bytes read.
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv_datagram(buf, true).map(|(n, _)| n)
+    }
+
+    /// Receives data from the socket, without removing it from the input
+    /// queue. On success, returns the number of bytes read.
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv_datagram(buf, false).map(|(n, _)| n)
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket, this is always
+    /// `None`.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        Ok(None)
+    }
+
+    fn recv_datagram(&self, buf: &mut [u8], consume: bool) -> io::Result<(usize, SocketAddr)> {
+        let mut state = lock(&self.state);
+        let now = Instant::now();
+        let res = match state.next(now) {
+            Some(index) => {
+                let (_, source, ref data) = state.datagrams[index];
+                // Like a real UDP socket the rest of the datagram is
+                // discarded if it doesn't fit in `buf`.
+                let n = cmp::min(buf.len(), data.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                if consume {
+                    let _ = state.datagrams.remove(index);
+                }
+                Ok((n, source))
+            }
+            None => Err(io::ErrorKind::WouldBlock.into()),
+        };
+        let readiness = state.readiness(now);
+        state.registration.update(readiness, 0);
+        res
+    }
+}
+
+impl event::Source for UdpSocket {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        let mut state = lock(&self.state);
+        let readiness = state.readiness(Instant::now());
+        state
+            .registration
+            .register(registry, token, interests, readiness)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        let mut state = lock(&self.state);
+        let readiness = state.readiness(Instant::now());
+        state
+            .registration
+            .reregister(registry, token, interests, readiness)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        lock(&self.state).registration.deregister(registry)
+    }
+}
+
+impl Drop for UdpSocket {
+    fn drop(&mut self) {
+        self.network.unbind_udp(self.local_addr);
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UdpSocket")
+            .field("local_addr", &self.local_addr)
+            .finish()
+    }
+}
@@ -0,0 +1,175 @@
+//! Deterministic mock event sources, for testing.
+//!
+//! With the `mock` feature enabled [`Poll::poll`] first returns the readiness
+//! set using [`set_readiness`] for [`MockSource`]s, before returning events
+//! from the OS. This makes it possible to test an event loop, or the state
+//! machines driven by it, without relying on the timing of real I/O.
+//!
+//! Readiness is returned in the same way as it is for the OS selector:
+//!
+//! * It's filtered by the interests the `MockSource` is registered with,
+//!   [error], [read closed] and [write closed] readiness is always returned.
+//! * By default registrations are edge-triggered, setting readiness returns a
+//!   single event. Events for the same source that are not yet returned are
+//!   merged into a single event.
+//! * For level-triggered registrations, using [`Interest::LEVEL`], the last
+//!   set readiness is returned by every call to `Poll::poll`, until it's set
+//!   to [`Readiness::EMPTY`].
+//! * One-shot registrations, using [`Interest::ONESHOT`], return a single
+//!   event until they're reregistered.
+//!
+//! Events of edge-triggered registrations are returned in the order
+//! `set_readiness` was called, followed by the events of level-triggered
+//! registrations in the order the sources were registered.
+//!
+//! Event sources backed by the OS, e.g. a [`Waker`], can still be registered
+//! with the same `Poll` instance. When readiness of mock sources is returned,
+//! `Poll::poll` doesn't block waiting for their events. Setting readiness from
+//! another thread wakes up a thread blocked in `Poll::poll`, this uses a pipe
+//! registered with [`WAKE_TOKEN`], so that token can't be used by other event
+//! sources.
+//!
+//! [`Poll::poll`]: ../struct.Poll.html#method.poll
+//! [`set_readiness`]: fn.set_readiness.html
+//! [`MockSource`]: struct.MockSource.html
+//! [error]: struct.Readiness.html#associatedconstant.ERROR
+//! [read closed]: struct.Readiness.html#associatedconstant.READ_CLOSED
+//! [write closed]: struct.Readiness.html#associatedconstant.WRITE_CLOSED
+//! [`Interest::LEVEL`]: ../struct.Interest.html#associatedconstant.LEVEL
+//! [`Readiness::EMPTY`]: struct.Readiness.html#associatedconstant.EMPTY
+//! [`Interest::ONESHOT`]: ../struct.Interest.html#associatedconstant.ONESHOT
+//! [`Waker`]: ../struct.Waker.html
+//! [`WAKE_TOKEN`]: constant.WAKE_TOKEN.html
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! use mio::mock::{self, MockSource, Readiness};
+//! use mio::{Events, Interest, Poll, Token};
+//!
+//! let mut poll = Poll::new()?;
+//! let mut events = Events::with_capacity(8);
+//!
+//! let mut source1 = MockSource::new();
+//! let mut source2 = MockSource::new();
+//! poll.registry().register(&mut source1, Token(1), Interest::READABLE)?;
+//! poll.registry().register(&mut source2, Token(2), Interest::WRITABLE)?;
+//!
+//! mock::set_readiness(poll.registry(), Token(2), Readiness::WRITABLE)?;
+//! mock::set_readiness(poll.registry(), Token(1), Readiness::READABLE | Readiness::READ_CLOSED)?;
+//!
+//! poll.poll(&mut events, None)?;
+//! let mut iter = events.iter();
+//! let event = iter.next().unwrap();
+//! assert_eq!(event.token(), Token(2));
+//! assert!(event.is_writable());
+//! let event = iter.next().unwrap();
+//! assert_eq!(event.token(), Token(1));
+//! assert!(event.is_readable() && event.is_read_closed());
+//! assert!(iter.next().is_none());
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::{fmt, io};
+
+use crate::event;
+pub use crate::readiness::Readiness;
+use crate::sys::mock::{self, Mock};
+use crate::{poll, Interest, Registry, Token};
+
+/// Token reserved for waking up `Poll` when the readiness of mock sources is
+/// changed, see the [module documentation].
+///
+/// [module documentation]: index.html
+pub const WAKE_TOKEN: Token = mock::WAKE;
+
+/// Set the readiness of the [`MockSource`]s registered with `token`.
+///
+/// Returns an error if no `MockSource` is registered with `token`.
+///
+/// [`MockSource`]: struct.MockSource.html
+pub fn set_readiness(registry: &Registry, token: Token, readiness: Readiness) -> io::Result<()> {
+    poll::mock(registry).set_readiness(token, readiness.bits())
+}
+
+/// Event source without an OS resource, its readiness is set using
+/// [`set_readiness`].
+///
+/// See the [module documentation] for more.
+///
+/// [`set_readiness`]: fn.set_readiness.html
+/// [module documentation]: index.html
+pub struct MockSource {
+    id: usize,
+    /// Readiness of the `Registry` the source is registered with, if any.
+    registration: Option<Mock>,
+}
+
+impl MockSource {
+    /// Create a new `MockSource`.
+    pub fn new() -> MockSource {
+        MockSource {
+            id: mock::next_id(),
+            registration: None,
+        }
+    }
+}
+
+impl Default for MockSource {
+    fn default() -> MockSource {
+        MockSource::new()
+    }
+}
+
+impl event::Source for MockSource {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        if self.registration.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "mock event source already registered with a `Registry`",
+            ));
+        }
+        let mock = poll::mock(registry);
+        mock.register(self.id, token, interests)?;
+        self.registration = Some(mock.clone());
+        Ok(())
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        poll::mock(registry).reregister(self.id, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        poll::mock(registry).deregister(self.id)?;
+        self.registration = None;
+        Ok(())
+    }
+}
+
+impl Drop for MockSource {
+    fn drop(&mut self) {
+        // Like closing a file descriptor, this removes the registration.
+        if let Some(mock) = self.registration.take() {
+            let _ = mock.deregister(self.id);
+        }
+    }
+}
+
+impl fmt::Debug for MockSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockSource").field("id", &self.id).finish()
+    }
+}
@@ -9,7 +9,15 @@
 
 cfg_tcp! {
     mod tcp;
-    pub use self::tcp::{TcpListener, TcpStream};
+    pub use self::tcp::{TcpKeepalive, TcpListener, TcpSocket, TcpStream};
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub use self::tcp::{TcpInfo, TcpState};
 }
 
 cfg_udp! {
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+/// Information about a TCP connection, returned by [`TcpStream::tcp_info`].
+///
+/// The information is retrieved using `getsockopt(TCP_INFO)` on Android,
+/// FreeBSD and Linux and `getsockopt(TCP_CONNECTION_INFO)` on iOS and macOS.
+/// Not all information is available on all platforms (or kernel versions),
+/// those methods return an `Option`.
+///
+/// [`TcpStream::tcp_info`]: struct.TcpStream.html#method.tcp_info
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TcpInfo {
+    pub(crate) state: TcpState,
+    pub(crate) rtt: Duration,
+    pub(crate) rtt_var: Duration,
+    pub(crate) rto: Duration,
+    pub(crate) snd_mss: u32,
+    pub(crate) snd_cwnd: u32,
+    pub(crate) retransmits: Option<u32>,
+    pub(crate) unacked: Option<u32>,
+    pub(crate) lost: Option<u32>,
+    pub(crate) min_rtt: Option<Duration>,
+    pub(crate) delivery_rate: Option<u64>,
+    pub(crate) bytes_sent: Option<u64>,
+    pub(crate) bytes_received: Option<u64>,
+}
+
+impl TcpInfo {
+    /// Returns the state of the connection.
+    pub fn state(&self) -> TcpState {
+        self.state
+    }
+
+    /// Returns the smoothed round-trip time.
+    pub fn rtt(&self) -> Duration {
+        self.rtt
+    }
+
+    /// Returns the round-trip time variance.
+    pub fn rtt_var(&self) -> Duration {
+        self.rtt_var
+    }
+
+    /// Returns the retransmission timeout.
+    pub fn rto(&self) -> Duration {
+        self.rto
+    }
+
+    /// Returns the maximum segment size used for sending, in bytes.
+    pub fn snd_mss(&self) -> u32 {
+        self.snd_mss
+    }
+
+    /// Returns the congestion window, in bytes.
+    ///
+    /// On Android and Linux this is calculated from the congestion window in
+    /// segments multiplied by [`snd_mss`].
+    ///
+    /// [`snd_mss`]: #method.snd_mss
+    pub fn snd_cwnd(&self) -> u32 {
+        self.snd_cwnd
+    }
+
+    /// Returns the total number of retransmitted segments.
+    ///
+    /// Not available on iOS and macOS.
+    pub fn retransmits(&self) -> Option<u32> {
+        self.retransmits
+    }
+
+    /// Returns the number of segments sent but not yet acknowledged.
+    ///
+    /// Only available on Android and Linux.
+    pub fn unacked(&self) -> Option<u32> {
+        self.unacked
+    }
+
+    /// Returns the number of segments presumed lost.
+    ///
+    /// Only available on Android and Linux.
+    pub fn lost(&self) -> Option<u32> {
+        self.lost
+    }
+
+    /// Returns the minimum round-trip time observed.
+    ///
+    /// Only available on Android and Linux 4.6 or later.
+    pub fn min_rtt(&self) -> Option<Duration> {
+        self.min_rtt
+    }
+
+    /// Returns the most recent delivery rate estimate, in bytes per second.
+    ///
+    /// Only available on Android and Linux 4.9 or later.
+    pub fn delivery_rate(&self) -> Option<u64> {
+        self.delivery_rate
+    }
+
+    /// Returns the total number of bytes sent, including retransmissions.
+    ///
+    /// Only available on Android, Linux 4.19 or later, iOS and macOS.
+    pub fn bytes_sent(&self) -> Option<u64> {
+        self.bytes_sent
+    }
+
+    /// Returns the total number of bytes received.
+    ///
+    /// Only available on Android, Linux 4.1 or later, iOS and macOS.
+    pub fn bytes_received(&self) -> Option<u64> {
+        self.bytes_received
+    }
+}
+
+/// State of a TCP connection, see [`TcpInfo::state`].
+///
+/// [`TcpInfo::state`]: struct.TcpInfo.html#method.state
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TcpState {
+    /// Connection is closed.
+    Closed,
+    /// Listening for connections.
+    Listen,
+    /// Sent a connection request, waiting for the reply.
+    SynSent,
+    /// Received a connection request, waiting for the acknowledgement.
+    SynReceived,
+    /// Connection is established.
+    Established,
+    /// Received a shutdown from the peer, waiting for the local shutdown.
+    CloseWait,
+    /// Shut down locally, waiting for the acknowledgement or the peer's
+    /// shutdown.
+    FinWait1,
+    /// Both sides shut down simultaneously, waiting for the acknowledgement.
+    Closing,
+    /// Shut down locally after the peer, waiting for the acknowledgement.
+    LastAck,
+    /// Shutdown was acknowledged, waiting for the peer's shutdown.
+    FinWait2,
+    /// Waiting for delayed segments after the connection is closed.
+    TimeWait,
+}
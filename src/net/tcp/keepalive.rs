@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+/// Configuration of TCP keepalive.
+///
+/// Used with [`TcpStream::set_keepalive_params`] and
+/// [`TcpListener::set_keepalive_params`], options that are not set are left
+/// at the default of the OS.
+///
+/// [`TcpStream::set_keepalive_params`]: struct.TcpStream.html#method.set_keepalive_params
+/// [`TcpListener::set_keepalive_params`]: struct.TcpListener.html#method.set_keepalive_params
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # #[cfg(not(target_os = "openbsd"))] {
+/// use std::net::TcpListener;
+/// use std::time::Duration;
+///
+/// use mio::net::{TcpKeepalive, TcpStream};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let stream = TcpStream::connect(listener.local_addr()?)?;
+///
+/// let keepalive = TcpKeepalive::new()
+///     .with_time(Duration::from_secs(60))
+///     .with_interval(Duration::from_secs(10))
+///     .with_retries(5);
+/// stream.set_keepalive_params(keepalive)?;
+/// assert!(stream.keepalive()?);
+/// # }
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TcpKeepalive {
+    pub(crate) time: Option<Duration>,
+    pub(crate) interval: Option<Duration>,
+    pub(crate) retries: Option<u32>,
+}
+
+impl TcpKeepalive {
+    /// Returns a new, empty configuration.
+    pub fn new() -> TcpKeepalive {
+        TcpKeepalive::default()
+    }
+
+    /// Sets the amount of time the connection must be idle before the first
+    /// keepalive probe is sent, `TCP_KEEPIDLE` (`TCP_KEEPALIVE` on iOS and
+    /// macOS).
+    ///
+    /// The time is rounded down to whole seconds, but a time shorter than a
+    /// second is rounded up to one second.
+    #[cfg(not(target_os = "openbsd"))]
+    pub fn with_time(self, time: Duration) -> TcpKeepalive {
+        TcpKeepalive {
+            time: Some(time),
+            ..self
+        }
+    }
+
+    /// Sets the time between keepalive probes, `TCP_KEEPINTVL`.
+    ///
+    /// The interval is rounded down to whole seconds, but an interval shorter
+    /// than a second is rounded up to one second.
+    #[cfg(not(target_os = "openbsd"))]
+    pub fn with_interval(self, interval: Duration) -> TcpKeepalive {
+        TcpKeepalive {
+            interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Sets the number of unacknowledged probes after which the connection is
+    /// dropped, `TCP_KEEPCNT`.
+    #[cfg(not(target_os = "openbsd"))]
+    pub fn with_retries(self, retries: u32) -> TcpKeepalive {
+        TcpKeepalive {
+            retries: Some(retries),
+            ..self
+        }
+    }
+
+    /// Returns the idle time before the first keepalive probe, if set.
+    pub fn time(&self) -> Option<Duration> {
+        self.time
+    }
+
+    /// Returns the time between keepalive probes, if set.
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    /// Returns the number of probes before the connection is dropped, if set.
+    pub fn retries(&self) -> Option<u32> {
+        self.retries
+    }
+}
@@ -5,7 +5,7 @@ use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
 use std::{fmt, io};
 
-use super::TcpStream;
+use super::{sys_socket, TcpKeepalive, TcpSocket, TcpStream};
 use crate::io_source::IoSource;
 use crate::{event, sys, Interest, Registry, Token};
 
@@ -47,9 +47,20 @@ impl TcpListener {
     /// 1. Create a new TCP socket.
     /// 2. Set the `SO_REUSEADDR` option on the socket on Unix.
     /// 3. Bind the socket to the specified address.
-    /// 4. Calls `listen` on the socket to prepare it to receive new connections.
+    /// 4. Calls `listen` on the socket, with a backlog of 1024, to prepare it
+    ///    to receive new connections.
+    ///
+    /// Use a [`TcpSocket`] to configure the socket differently.
+    ///
+    /// [`TcpSocket`]: struct.TcpSocket.html
     pub fn bind(addr: SocketAddr) -> io::Result<TcpListener> {
-        sys::tcp::bind(addr).map(TcpListener::from_std)
+        let socket = TcpSocket::new_for_addr(addr)?;
+        // On Windows `SO_REUSEADDR` allows binding to an address that is
+        // actively in use, so it's only set on Unix.
+        #[cfg(unix)]
+        socket.set_reuseaddr(true)?;
+        socket.bind(addr)?;
+        socket.listen(1024)
     }
 
     /// Creates a new `TcpListener` from a standard `net::TcpListener`.
@@ -100,6 +111,49 @@ impl TcpListener {
         self.inner.ttl()
     }
 
+    /// Sets the value of the `SO_KEEPALIVE` option on this socket.
+    ///
+    /// Accepted streams inherit the keepalive settings of the listener, see
+    /// [`TcpStream::set_keepalive`] and [`set_keepalive_params`].
+    ///
+    /// [`TcpStream::set_keepalive`]: struct.TcpStream.html#method.set_keepalive
+    ///
+    /// [`set_keepalive_params`]: #method.set_keepalive_params
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        sys::tcp::set_keepalive(sys_socket(self), keepalive)
+    }
+
+    /// Gets the value of the `SO_KEEPALIVE` option on this socket.
+    pub fn keepalive(&self) -> io::Result<bool> {
+        sys::tcp::get_keepalive(sys_socket(self))
+    }
+
+    /// Enables keepalive, see [`set_keepalive`], and sets the options that
+    /// are set in `keepalive`.
+    ///
+    /// # Notes
+    ///
+    /// Not supported on OpenBSD, where the keepalive options can only be set
+    /// system wide. On Windows this requires Windows 10 version 1709 or later.
+    ///
+    /// [`set_keepalive`]: #method.set_keepalive
+    #[cfg(not(target_os = "openbsd"))]
+    pub fn set_keepalive_params(&self, keepalive: TcpKeepalive) -> io::Result<()> {
+        sys::tcp::set_keepalive_params(sys_socket(self), keepalive)
+    }
+
+    /// Gets the keepalive options of this socket, all options of the returned
+    /// `TcpKeepalive` are set.
+    ///
+    /// For more information about the options, see
+    /// [`set_keepalive_params`].
+    ///
+    /// [`set_keepalive_params`]: #method.set_keepalive_params
+    #[cfg(not(target_os = "openbsd"))]
+    pub fn keepalive_params(&self) -> io::Result<TcpKeepalive> {
+        sys::tcp::get_keepalive_params(sys_socket(self))
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
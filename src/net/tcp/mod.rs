@@ -1,5 +1,47 @@
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(windows)]
+use std::os::windows::io::AsRawSocket;
+
+use crate::sys;
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+mod info;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub use self::info::{TcpInfo, TcpState};
+
+mod keepalive;
+pub use self::keepalive::TcpKeepalive;
+
 mod listener;
 pub use self::listener::TcpListener;
 
+mod socket;
+pub use self::socket::TcpSocket;
+
 mod stream;
 pub use self::stream::TcpStream;
+
+/// Returns the socket used by the `sys::tcp` functions.
+#[cfg(unix)]
+fn sys_socket<S: AsRawFd>(socket: &S) -> sys::tcp::TcpSocket {
+    socket.as_raw_fd()
+}
+
+/// Returns the socket used by the `sys::tcp` functions.
+#[cfg(windows)]
+fn sys_socket<S: AsRawSocket>(socket: &S) -> sys::tcp::TcpSocket {
+    socket.as_raw_socket() as sys::tcp::TcpSocket
+}
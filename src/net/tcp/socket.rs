@@ -0,0 +1,272 @@
+use std::io;
+use std::mem;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
+
+use crate::net::{TcpListener, TcpStream};
+use crate::sys;
+
+/// A non-blocking TCP socket used to configure a stream or listener.
+///
+/// The `TcpSocket` type wraps the operating-system's socket handle. This type
+/// is used to configure the socket before establishing a connection or start
+/// listening for inbound connections, e.g. to set options that must be set
+/// before calling `bind`.
+///
+/// The socket will be closed when the value is dropped, unless it's consumed
+/// by [`connect`] or [`listen`].
+///
+/// [`connect`]: #method.connect
+/// [`listen`]: #method.listen
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::net::TcpSocket;
+///
+/// let addr = "127.0.0.1:0".parse()?;
+/// let socket = TcpSocket::new_for_addr(addr)?;
+/// socket.set_reuseaddr(true)?;
+/// socket.bind(addr)?;
+///
+/// let listener = socket.listen(128)?;
+/// let stream = TcpSocket::new_for_addr(addr)?.connect(listener.local_addr()?)?;
+/// #     drop(stream);
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TcpSocket {
+    sys: sys::tcp::TcpSocket,
+}
+
+impl TcpSocket {
+    /// Create a new IPv4 TCP socket.
+    ///
+    /// This calls `socket(2)`.
+    pub fn new_v4() -> io::Result<TcpSocket> {
+        sys::tcp::new_v4_socket().map(|sys| TcpSocket { sys })
+    }
+
+    /// Create a new IPv6 TCP socket.
+    ///
+    /// This calls `socket(2)`.
+    pub fn new_v6() -> io::Result<TcpSocket> {
+        sys::tcp::new_v6_socket().map(|sys| TcpSocket { sys })
+    }
+
+    /// Create a new TCP socket of the same family as `addr`, i.e. IPv4 or
+    /// IPv6.
+    pub fn new_for_addr(addr: SocketAddr) -> io::Result<TcpSocket> {
+        match addr {
+            SocketAddr::V4(..) => TcpSocket::new_v4(),
+            SocketAddr::V6(..) => TcpSocket::new_v6(),
+        }
+    }
+
+    /// Bind `addr` to the TCP socket.
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<()> {
+        sys::tcp::bind(self.sys, addr)
+    }
+
+    /// Connect the socket to `addr`.
+    ///
+    /// This consumes the socket and performs the connect operation. Once the
+    /// connection completes, the socket is now a non-blocking `TcpStream` and
+    /// can be used as such. As with [`TcpStream::connect`] the connection may
+    /// not be established yet, wait for a writable event to find out.
+    ///
+    /// [`TcpStream::connect`]: struct.TcpStream.html#method.connect
+    pub fn connect(self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let stream = sys::tcp::connect(self.sys, addr)?;
+
+        // Don't close the socket.
+        mem::forget(self);
+        Ok(TcpStream::from_std(stream))
+    }
+
+    /// Listen for inbound connections, converting the socket to a
+    /// `TcpListener`.
+    ///
+    /// `backlog` is the maximum number of pending connections, it's capped by
+    /// the OS.
+    pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        let listener = sys::tcp::listen(self.sys, backlog)?;
+
+        // Don't close the socket.
+        mem::forget(self);
+        Ok(TcpListener::from_std(listener))
+    }
+
+    /// Sets the value of `SO_REUSEADDR` on this socket.
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        sys::tcp::set_reuseaddr(self.sys, reuseaddr)
+    }
+
+    /// Get the value of `SO_REUSEADDR` set on this socket.
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        sys::tcp::get_reuseaddr(self.sys)
+    }
+
+    /// Sets the value of `SO_REUSEPORT` on this socket.
+    ///
+    /// Only supported on Unix platforms other than Solaris and illumos.
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        sys::tcp::set_reuseport(self.sys, reuseport)
+    }
+
+    /// Get the value of `SO_REUSEPORT` set on this socket.
+    ///
+    /// Only supported on Unix platforms other than Solaris and illumos.
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    pub fn reuseport(&self) -> io::Result<bool> {
+        sys::tcp::get_reuseport(self.sys)
+    }
+
+    /// Sets the value of `IPV6_V6ONLY` on this socket.
+    ///
+    /// If set an IPv6 socket only communicates using IPv6, rather than also
+    /// accepting IPv4-mapped addresses. Only valid for IPv6 sockets and must
+    /// be set before calling `bind`.
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        sys::tcp::set_only_v6(self.sys, only_v6)
+    }
+
+    /// Get the value of `IPV6_V6ONLY` set on this socket.
+    pub fn only_v6(&self) -> io::Result<bool> {
+        sys::tcp::get_only_v6(self.sys)
+    }
+
+    /// Sets the value of `SO_SNDBUF` on this socket.
+    ///
+    /// The OS may adjust the value, e.g. Linux doubles it, see
+    /// [`send_buffer_size`] for the actual value.
+    ///
+    /// [`send_buffer_size`]: #method.send_buffer_size
+    pub fn set_send_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::tcp::set_send_buffer_size(self.sys, size)
+    }
+
+    /// Get the value of `SO_SNDBUF` set on this socket.
+    pub fn send_buffer_size(&self) -> io::Result<u32> {
+        sys::tcp::get_send_buffer_size(self.sys)
+    }
+
+    /// Sets the value of `SO_RCVBUF` on this socket.
+    ///
+    /// The OS may adjust the value, e.g. Linux doubles it, see
+    /// [`recv_buffer_size`] for the actual value.
+    ///
+    /// [`recv_buffer_size`]: #method.recv_buffer_size
+    pub fn set_recv_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::tcp::set_recv_buffer_size(self.sys, size)
+    }
+
+    /// Get the value of `SO_RCVBUF` set on this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<u32> {
+        sys::tcp::get_recv_buffer_size(self.sys)
+    }
+
+    /// Sets the value of `IP_BIND_ADDRESS_NO_PORT` on this socket.
+    ///
+    /// If set binding an address with port 0 doesn't reserve an ephemeral
+    /// port, instead the port is picked when calling [`connect`]. This allows
+    /// more outgoing connections from the same local address.
+    ///
+    /// Only supported on Android and Linux.
+    ///
+    /// [`connect`]: #method.connect
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_bind_address_no_port(&self, no_port: bool) -> io::Result<()> {
+        sys::tcp::set_bind_address_no_port(self.sys, no_port)
+    }
+
+    /// Get the value of `IP_BIND_ADDRESS_NO_PORT` set on this socket.
+    ///
+    /// Only supported on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bind_address_no_port(&self) -> io::Result<bool> {
+        sys::tcp::get_bind_address_no_port(self.sys)
+    }
+
+    /// Returns the local address of this socket.
+    ///
+    /// Will return `Err` result in windows if called before calling `bind`.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        sys::tcp::get_localaddr(self.sys)
+    }
+}
+
+impl Drop for TcpSocket {
+    fn drop(&mut self) {
+        sys::tcp::close(self.sys);
+    }
+}
+
+#[cfg(unix)]
+impl IntoRawFd for TcpSocket {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.sys;
+        // Avoid closing the socket.
+        mem::forget(self);
+        fd
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for TcpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys
+    }
+}
+
+#[cfg(unix)]
+impl FromRawFd for TcpSocket {
+    /// Converts a `RawFd` to a `TcpSocket`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the socket is in
+    /// non-blocking mode.
+    unsafe fn from_raw_fd(fd: RawFd) -> TcpSocket {
+        TcpSocket { sys: fd }
+    }
+}
+
+#[cfg(windows)]
+impl IntoRawSocket for TcpSocket {
+    fn into_raw_socket(self) -> RawSocket {
+        let socket = self.sys as RawSocket;
+        // Avoid closing the socket.
+        mem::forget(self);
+        socket
+    }
+}
+
+#[cfg(windows)]
+impl AsRawSocket for TcpSocket {
+    fn as_raw_socket(&self) -> RawSocket {
+        self.sys as RawSocket
+    }
+}
+
+#[cfg(windows)]
+impl FromRawSocket for TcpSocket {
+    /// Converts a `RawSocket` to a `TcpSocket`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the socket is in
+    /// non-blocking mode.
+    unsafe fn from_raw_socket(socket: RawSocket) -> TcpSocket {
+        TcpSocket {
+            sys: socket as sys::tcp::TcpSocket,
+        }
+    }
+}
@@ -5,7 +5,17 @@ use std::net::{self, Shutdown, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 #[cfg(windows)]
 use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
-
+use std::time::Duration;
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use super::TcpInfo;
+use super::{sys_socket, TcpKeepalive, TcpSocket};
 use crate::io_source::IoSource;
 use crate::{event, sys, Interest, Registry, Token};
 
@@ -47,8 +57,17 @@ pub struct TcpStream {
 impl TcpStream {
     /// Create a new TCP stream and issue a non-blocking connect to the
     /// specified address.
+    ///
+    /// Use a [`TcpSocket`] to configure the socket before connecting.
+    ///
+    /// [`TcpSocket`]: struct.TcpSocket.html
     pub fn connect(addr: SocketAddr) -> io::Result<TcpStream> {
-        sys::tcp::connect(addr).map(TcpStream::from_std)
+        let socket = TcpSocket::new_for_addr(addr)?;
+        // Required for a future `connect_overlapped` operation to be executed
+        // successfully.
+        #[cfg(windows)]
+        socket.bind(inaddr_any(addr))?;
+        socket.connect(addr)
     }
 
     /// Creates a new `TcpStream` from a standard `net::TcpStream`.
@@ -149,6 +168,246 @@ impl TcpStream {
         self.inner.ttl()
     }
 
+    /// Sets the value of the `SO_KEEPALIVE` option on this socket.
+    ///
+    /// If set, keepalive probes are sent when the connection is idle, to
+    /// detect a peer that is no longer reachable. Also see
+    /// [`set_keepalive_params`].
+    ///
+    /// [`set_keepalive_params`]: #method.set_keepalive_params
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        sys::tcp::set_keepalive(sys_socket(self), keepalive)
+    }
+
+    /// Gets the value of the `SO_KEEPALIVE` option on this socket.
+    pub fn keepalive(&self) -> io::Result<bool> {
+        sys::tcp::get_keepalive(sys_socket(self))
+    }
+
+    /// Enables keepalive, see [`set_keepalive`], and sets the options that
+    /// are set in `keepalive`.
+    ///
+    /// # Notes
+    ///
+    /// Not supported on OpenBSD, where the keepalive options can only be set
+    /// system wide. On Windows this requires Windows 10 version 1709 or later.
+    ///
+    /// [`set_keepalive`]: #method.set_keepalive
+    #[cfg(not(target_os = "openbsd"))]
+    pub fn set_keepalive_params(&self, keepalive: TcpKeepalive) -> io::Result<()> {
+        sys::tcp::set_keepalive_params(sys_socket(self), keepalive)
+    }
+
+    /// Gets the keepalive options of this socket, all options of the returned
+    /// `TcpKeepalive` are set.
+    ///
+    /// For more information about the options, see
+    /// [`set_keepalive_params`].
+    ///
+    /// [`set_keepalive_params`]: #method.set_keepalive_params
+    #[cfg(not(target_os = "openbsd"))]
+    pub fn keepalive_params(&self) -> io::Result<TcpKeepalive> {
+        sys::tcp::get_keepalive_params(sys_socket(self))
+    }
+
+    /// Sets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// If set to `Some(duration)` dropping the stream waits up to `duration`
+    /// (in whole seconds, rounded down but at least one second) for unsent
+    /// data to be sent, blocking the thread. With `Some(Duration::from_secs(0))`
+    /// dropping the stream discards unsent data and resets the connection. If set to `None`, the default,
+    /// dropping the stream returns immediately and the OS tries to send the
+    /// remaining data in the background.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        sys::tcp::set_linger(sys_socket(self), linger)
+    }
+
+    /// Gets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// For more information about this option, see [`set_linger`].
+    ///
+    /// [`set_linger`]: #method.set_linger
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        sys::tcp::get_linger(sys_socket(self))
+    }
+
+    /// Sets the value of the `TCP_USER_TIMEOUT` option on this socket.
+    ///
+    /// This is the maximum amount of time (in milliseconds, rounded down but at
+    /// least one millisecond) transmitted data may remain unacknowledged before
+    /// the connection is closed, returning a `TimedOut` error. Zero means the
+    /// OS default is used.
+    ///
+    /// Only supported on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_user_timeout(&self, timeout: Duration) -> io::Result<()> {
+        sys::tcp::set_user_timeout(sys_socket(self), timeout)
+    }
+
+    /// Gets the value of the `TCP_USER_TIMEOUT` option on this socket.
+    ///
+    /// Only supported on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn user_timeout(&self) -> io::Result<Duration> {
+        sys::tcp::get_user_timeout(sys_socket(self))
+    }
+
+    /// Sets the value of the `TCP_NOTSENT_LOWAT` option on this socket.
+    ///
+    /// Limits the amount of written data that is not yet sent to `lowat`
+    /// bytes, the stream is only reported as writable once less unsent data is
+    /// queued. This keeps the send buffer small without limiting the amount of
+    /// data in flight.
+    ///
+    /// Only supported on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn set_notsent_lowat(&self, lowat: u32) -> io::Result<()> {
+        sys::tcp::set_notsent_lowat(sys_socket(self), lowat)
+    }
+
+    /// Gets the value of the `TCP_NOTSENT_LOWAT` option on this socket.
+    ///
+    /// Only supported on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        sys::tcp::get_notsent_lowat(sys_socket(self))
+    }
+
+    /// Sets the value of the `TCP_QUICKACK` option on this socket.
+    ///
+    /// If set, acknowledgements are sent immediately rather than delayed. The
+    /// OS may reset this option, e.g. after receiving data.
+    ///
+    /// Only supported on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_quickack(&self, quickack: bool) -> io::Result<()> {
+        sys::tcp::set_quickack(sys_socket(self), quickack)
+    }
+
+    /// Gets the value of the `TCP_QUICKACK` option on this socket.
+    ///
+    /// Only supported on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn quickack(&self) -> io::Result<bool> {
+        sys::tcp::get_quickack(sys_socket(self))
+    }
+
+    /// Sets the value of the `TCP_CORK` option on this socket.
+    ///
+    /// If set, partial segments are not sent until the option is unset again
+    /// (or after 200 milliseconds), allowing multiple writes to be combined
+    /// into full segments.
+    ///
+    /// Only supported on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_cork(&self, cork: bool) -> io::Result<()> {
+        sys::tcp::set_cork(sys_socket(self), cork)
+    }
+
+    /// Gets the value of the `TCP_CORK` option on this socket.
+    ///
+    /// Only supported on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn cork(&self) -> io::Result<bool> {
+        sys::tcp::get_cork(sys_socket(self))
+    }
+
+    /// Sets the value of the `SO_RCVLOWAT` option on this socket.
+    ///
+    /// The stream is only reported as readable once at least `lowat` bytes
+    /// can be read, or the connection is closed. The default is 1.
+    ///
+    /// Only supported on Unix.
+    #[cfg(unix)]
+    pub fn set_recv_lowat(&self, lowat: u32) -> io::Result<()> {
+        sys::tcp::set_recv_lowat(sys_socket(self), lowat)
+    }
+
+    /// Gets the value of the `SO_RCVLOWAT` option on this socket.
+    ///
+    /// Only supported on Unix.
+    #[cfg(unix)]
+    pub fn recv_lowat(&self) -> io::Result<u32> {
+        sys::tcp::get_recv_lowat(sys_socket(self))
+    }
+
+    /// Returns information about the connection, such as the round-trip time
+    /// and congestion window, see [`TcpInfo`].
+    ///
+    /// Only supported on Android, FreeBSD, iOS, Linux and macOS.
+    ///
+    /// [`TcpInfo`]: struct.TcpInfo.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # #[cfg(any(
+    /// #     target_os = "android",
+    /// #     target_os = "freebsd",
+    /// #     target_os = "ios",
+    /// #     target_os = "linux",
+    /// #     target_os = "macos"
+    /// # ))] {
+    /// use std::net::TcpListener;
+    ///
+    /// use mio::net::TcpStream;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0")?;
+    /// let stream = TcpStream::connect(listener.local_addr()?)?;
+    /// let (_peer, _) = listener.accept()?;
+    ///
+    /// let info = stream.tcp_info()?;
+    /// println!("connection {:?}, rtt: {:?}", info.state(), info.rtt());
+    /// # }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        sys::tcp::get_tcp_info(sys_socket(self))
+    }
+
+    /// Returns the number of bytes in the receive queue, i.e. the number of
+    /// bytes that can be read without blocking (`FIONREAD`).
+    pub fn recv_queue_len(&self) -> io::Result<usize> {
+        sys::tcp::get_recv_queue_len(sys_socket(self))
+    }
+
+    cfg_send_queue_len! {
+        /// Returns the number of bytes in the send queue, i.e. bytes written that
+        /// are not yet sent or not yet acknowledged by the peer (`SIOCOUTQ` on
+        /// Android and Linux, `FIONWRITE` on FreeBSD and NetBSD and `SO_NWRITE` on
+        /// iOS and macOS).
+        ///
+        /// Once this returns zero all written data was received by the peer, e.g.
+        /// after calling [`shutdown`] before dropping the stream.
+        ///
+        /// Only supported on Android, FreeBSD, iOS, Linux, macOS and NetBSD.
+        ///
+        /// [`shutdown`]: #method.shutdown
+        pub fn send_queue_len(&self) -> io::Result<usize> {
+            sys::tcp::get_send_queue_len(sys_socket(self))
+        }
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
@@ -165,7 +424,7 @@ impl TcpStream {
     /// Successive calls return the same data. This is accomplished by passing
     /// `MSG_PEEK` as a flag to the underlying recv system call.
     pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
-        self.inner.peek(buf)
+        self.inner.do_io(|inner| inner.peek(buf))
     }
 }
 
@@ -300,3 +559,14 @@ impl FromRawSocket for TcpStream {
         TcpStream::from_std(FromRawSocket::from_raw_socket(socket))
     }
 }
+
+/// Returns the unspecified address of the same family as `other`.
+#[cfg(windows)]
+fn inaddr_any(other: SocketAddr) -> SocketAddr {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    match other {
+        SocketAddr::V4(..) => SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0),
+        SocketAddr::V6(..) => SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0),
+    }
+}
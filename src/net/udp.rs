@@ -502,6 +502,28 @@ impl UdpSocket {
         self.inner.leave_multicast_v6(multiaddr, interface)
     }
 
+    /// Returns the number of bytes in the receive queue (`FIONREAD`).
+    ///
+    /// On Android and Linux this is the size of the next datagram, on other
+    /// platforms it's the total size of all queued datagrams.
+    pub fn recv_queue_len(&self) -> io::Result<usize> {
+        sys::udp::recv_queue_len(&self.inner)
+    }
+
+    cfg_send_queue_len! {
+        /// Returns the number of bytes in the send queue, i.e. bytes not yet sent
+        /// (`SIOCOUTQ` on Android and Linux, `FIONWRITE` on FreeBSD and NetBSD and
+        /// `SO_NWRITE` on iOS and macOS).
+        ///
+        /// On Android and Linux this is the memory used by the datagrams not yet
+        /// sent, including the kernel's overhead.
+        ///
+        /// Only supported on Android, FreeBSD, iOS, Linux, macOS and NetBSD.
+        pub fn send_queue_len(&self) -> io::Result<usize> {
+            sys::udp::send_queue_len(&self.inner)
+        }
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
@@ -69,6 +69,26 @@ impl UnixStream {
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         self.inner.shutdown(how)
     }
+
+    /// Returns the number of bytes in the receive queue (`FIONREAD`).
+    pub fn recv_queue_len(&self) -> io::Result<usize> {
+        sys::uds::stream::recv_queue_len(&self.inner)
+    }
+
+    cfg_send_queue_len! {
+        /// Returns the number of bytes in the send queue (`SIOCOUTQ` on Android
+        /// and Linux, `FIONWRITE` on FreeBSD and NetBSD and `SO_NWRITE` on iOS and
+        /// macOS).
+        ///
+        /// On Android and Linux this is the memory used by the bytes not yet read by
+        /// the peer, including the kernel's overhead. Other platforms queue the
+        /// bytes at the peer directly.
+        ///
+        /// Only supported on Android, FreeBSD, iOS, Linux, macOS and NetBSD.
+        pub fn send_queue_len(&self) -> io::Result<usize> {
+            sys::uds::stream::send_queue_len(&self.inner)
+        }
+    }
 }
 
 impl Read for UnixStream {
@@ -1,7 +1,17 @@
+#[cfg(feature = "stats")]
+use crate::stats;
 use crate::{event, sys, Events, Interest, Token};
 use log::trace;
+#[cfg(feature = "registration-checks")]
+use std::collections::HashMap;
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(feature = "stats")]
+use std::sync::atomic::AtomicUsize;
+#[cfg(any(feature = "registration-checks", feature = "stats"))]
+use std::sync::Arc;
+#[cfg(feature = "registration-checks")]
+use std::sync::{Mutex, MutexGuard};
 use std::time::Duration;
 use std::{fmt, io};
 
@@ -92,7 +102,12 @@ use std::{fmt, io};
 /// there is no guarantee that another readiness event will be delivered, even
 /// if further data is received for the event source.
 ///
+/// On Unix platforms event sources can instead be registered level-triggered
+/// using [`Interest::LEVEL`], in which case readiness events keep being
+/// delivered as long as the event source is ready and draining is not required.
+///
 /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+/// [`Interest::LEVEL`]: struct.Interest.html#associatedconstant.LEVEL
 ///
 /// ### Readiness operations
 ///
@@ -200,11 +215,51 @@ use std::{fmt, io};
 /// [`Poll::poll`]: struct.Poll.html#method.poll
 pub struct Poll {
     registry: Registry,
+    #[cfg(feature = "stats")]
+    stats: stats::Stats,
 }
 
 /// Registers I/O resources.
 pub struct Registry {
     selector: sys::Selector,
+    /// Readiness of the sources in the `mock` module.
+    #[cfg(all(unix, feature = "mock"))]
+    mock: sys::mock::Mock,
+    /// Registrations of I/O sources, shared with all clones.
+    #[cfg(feature = "registration-checks")]
+    registrations: Registrations,
+    /// Number of calls to `Waker::wake`, shared with all clones.
+    #[cfg(feature = "stats")]
+    wakeups: Arc<AtomicUsize>,
+}
+
+/// Token and interests of the registered I/O sources, by the unique key of the
+/// source (see `IoSource`).
+#[cfg(feature = "registration-checks")]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Registrations {
+    inner: Arc<Mutex<HashMap<usize, (Token, Interest)>>>,
+}
+
+cfg_io_source! {
+    #[cfg(feature = "registration-checks")]
+    impl Registrations {
+        pub(crate) fn insert(&self, key: usize, token: Token, interests: Interest) {
+            let _ = self.lock().insert(key, (token, interests));
+        }
+
+        pub(crate) fn remove(&self, key: usize) {
+            let _ = self.lock().remove(&key);
+        }
+    }
+}
+
+#[cfg(feature = "registration-checks")]
+impl Registrations {
+    fn lock(&self) -> MutexGuard<'_, HashMap<usize, (Token, Interest)>> {
+        // The map is always valid, even if a thread panicked.
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
 }
 
 impl Poll {
@@ -236,7 +291,8 @@ impl Poll {
     ///
     /// Note that the `timeout` will be rounded up to the system clock
     /// granularity (usually 1ms), and kernel scheduling delays mean that
-    /// the blocking interval may be overrun by a small amount.
+    /// the blocking interval may be overrun by a small amount. On Linux and
+    /// Android, and with kqueue, sub-millisecond timeouts are supported.
     ///
     /// See the [struct] level documentation for a higher level discussion of
     /// polling.
@@ -307,7 +363,54 @@ impl Poll {
     ///
     /// [struct]: #
     pub fn poll(&mut self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
-        self.registry.selector.select(events.sys(), timeout)
+        #[cfg(feature = "stats")]
+        self.stats.before_select(timeout);
+        let result = self.select(events, timeout);
+        #[cfg(feature = "stats")]
+        self.stats.after_select(&result, events);
+        result
+    }
+
+    fn select(&mut self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        #[cfg(not(all(unix, feature = "mock")))]
+        return self.registry.selector.select(events.sys(), timeout);
+
+        #[cfg(all(unix, feature = "mock"))]
+        self.registry
+            .mock
+            .select(&self.registry.selector, events.sys(), timeout)
+    }
+
+    /// Returns the statistics of this `Poll` instance.
+    ///
+    /// See the [`stats`] module for an example.
+    ///
+    /// [`stats`]: stats/index.html
+    #[cfg(feature = "stats")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+    pub fn stats(&self) -> stats::PollStats {
+        self.stats.stats()
+    }
+
+    /// Sets the hook called by [`poll`], replacing any previous hook.
+    ///
+    /// See the [`stats`] module for an example.
+    ///
+    /// [`poll`]: #method.poll
+    /// [`stats`]: stats/index.html
+    #[cfg(feature = "stats")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+    pub fn set_hook(&mut self, hook: Box<dyn stats::Hook>) {
+        self.stats.set_hook(hook)
+    }
+
+    /// Removes the hook set using [`set_hook`], returning it.
+    ///
+    /// [`set_hook`]: #method.set_hook
+    #[cfg(feature = "stats")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+    pub fn take_hook(&mut self) -> Option<Box<dyn stats::Hook>> {
+        self.stats.take_hook()
     }
 }
 
@@ -347,8 +450,21 @@ cfg_os_poll! {
         /// # }
         /// ```
         pub fn new() -> io::Result<Poll> {
-            sys::Selector::new().map(|selector| Poll {
-                registry: Registry { selector },
+            let selector = sys::Selector::new()?;
+            #[cfg(feature = "stats")]
+            let wakeups = Arc::new(AtomicUsize::new(0));
+            Ok(Poll {
+                #[cfg(feature = "stats")]
+                stats: stats::Stats::new(wakeups.clone()),
+                registry: Registry {
+                    #[cfg(all(unix, feature = "mock"))]
+                    mock: sys::mock::Mock::new(&selector)?,
+                    selector,
+                    #[cfg(feature = "registration-checks")]
+                    registrations: Registrations::default(),
+                    #[cfg(feature = "stats")]
+                    wakeups,
+                },
             })
         }
     }
@@ -388,7 +504,7 @@ impl Registry {
     /// calling [`reregister`].
     ///
     /// See documentation on [`Token`] for an example showing how to pick
-    /// [`Token`] values.
+    /// [`Token`] values, and for the reserved token that can't be used.
     ///
     /// `interest: Interest`: Specifies which operations `Poll` should monitor
     /// for readiness. `Poll` will only return readiness events for operations
@@ -475,6 +591,8 @@ impl Registry {
             token,
             interests
         );
+        check_token(token)?;
+        check_interests(interests)?;
         source.register(self, token, interests)
     }
 
@@ -492,6 +610,9 @@ impl Registry {
     /// The event source must have previously been registered with this instance
     /// of `Poll`, otherwise the behavior is undefined.
     ///
+    /// Event sources registered with [`Interest::ONESHOT`] are disarmed after
+    /// returning a single event, calling `reregister` arms them again.
+    ///
     /// See the [`register`] documentation for details about the function
     /// arguments and see the [`struct`] docs for a high level overview of
     /// polling.
@@ -533,6 +654,7 @@ impl Registry {
     /// [`register`]: struct.Registry.html#method.register
     /// [`readable`]: ./event/struct.Event.html#is_readable
     /// [`writable`]: ./event/struct.Event.html#is_writable
+    /// [`Interest::ONESHOT`]: struct.Interest.html#associatedconstant.ONESHOT
     pub fn reregister<S>(&self, source: &mut S, token: Token, interests: Interest) -> io::Result<()>
     where
         S: event::Source + ?Sized,
@@ -542,6 +664,8 @@ impl Registry {
             token,
             interests
         );
+        check_token(token)?;
+        check_interests(interests)?;
         source.reregister(self, token, interests)
     }
 
@@ -608,9 +732,170 @@ impl Registry {
     /// Event sources registered with this `Registry` will be registered with
     /// the original `Registry` and `Poll` instance.
     pub fn try_clone(&self) -> io::Result<Registry> {
-        self.selector
-            .try_clone()
-            .map(|selector| Registry { selector })
+        self.selector.try_clone().map(|selector| Registry {
+            selector,
+            #[cfg(all(unix, feature = "mock"))]
+            mock: self.mock.clone(),
+            #[cfg(feature = "registration-checks")]
+            registrations: self.registrations.clone(),
+            #[cfg(feature = "stats")]
+            wakeups: self.wakeups.clone(),
+        })
+    }
+
+    /// Returns the token and interests of all event sources registered with
+    /// this `Registry`, or any of its clones, sorted by token.
+    ///
+    /// This is useful for diagnostics, e.g. to find event sources that are
+    /// never deregistered. Only event sources provided by Mio are included,
+    /// file descriptors registered using [`SourceFd`] and [`Waker`]s are not.
+    /// Event sources that are dropped are removed.
+    ///
+    /// [`SourceFd`]: unix/struct.SourceFd.html
+    /// [`Waker`]: struct.Waker.html
+    #[cfg(feature = "registration-checks")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "registration-checks")))]
+    pub fn registrations(&self) -> Vec<(Token, Interest)> {
+        let mut registrations: Vec<(Token, Interest)> =
+            self.registrations.lock().values().cloned().collect();
+        #[cfg(all(unix, feature = "mock"))]
+        registrations.extend(self.mock.registrations());
+        registrations.sort_by_key(|&(token, _)| token);
+        registrations
+    }
+
+    /// Returns the registrations of the epoll instance, as the kernel sees
+    /// them.
+    ///
+    /// This reads `/proc/self/fdinfo` for the epoll file descriptor, it
+    /// includes all registrations, including those of [`Waker`]s and file
+    /// descriptors registered using [`SourceFd`]. Comparing this with what
+    /// the application registered can find leaked registrations, e.g. a file
+    /// descriptor that was closed without being deregistered while a
+    /// duplicate of it is still open, which keeps it registered. Note that
+    /// when using the `mock` feature the pipe to wake up the poll is included.
+    ///
+    /// Returns an error if `/proc` is not mounted or if the `io-uring` or
+    /// `poll-fallback` selector is used.
+    ///
+    /// [`Waker`]: struct.Waker.html
+    /// [`SourceFd`]: unix/struct.SourceFd.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # if cfg!(any(feature = "io-uring", feature = "poll-fallback")) {
+    /// #     return Ok(());
+    /// # }
+    /// use std::os::unix::io::AsRawFd;
+    /// use std::os::unix::net::UnixDatagram;
+    ///
+    /// use mio::unix::SourceFd;
+    /// use mio::{Interest, Poll, Token};
+    ///
+    /// let poll = Poll::new()?;
+    /// let socket = UnixDatagram::unbound()?;
+    /// let fd = socket.as_raw_fd();
+    /// poll.registry().register(&mut SourceFd(&fd), Token(10), Interest::READABLE)?;
+    ///
+    /// let registrations = poll.registry().inspect()?;
+    /// let registration = registrations.iter().find(|r| r.fd() == fd).unwrap();
+    /// assert_eq!(registration.token(), Token(10));
+    /// assert_eq!(registration.interests(), Some(Interest::READABLE));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux")))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux"))))
+    )]
+    pub fn inspect(&self) -> io::Result<Vec<EpollRegistration>> {
+        self.selector.inspect().map(|registrations| {
+            registrations
+                .into_iter()
+                .map(|(fd, events, data)| EpollRegistration {
+                    fd,
+                    events,
+                    token: Token(data as usize),
+                })
+                .collect()
+        })
+    }
+}
+
+/// A registration of an epoll instance, as seen by the kernel.
+///
+/// Returned by [`Registry::inspect`].
+///
+/// [`Registry::inspect`]: struct.Registry.html#method.inspect
+#[cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux")))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux"))))
+)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EpollRegistration {
+    fd: RawFd,
+    events: u32,
+    token: Token,
+}
+
+#[cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux")))]
+impl EpollRegistration {
+    /// Returns the registered file descriptor.
+    ///
+    /// This is the file descriptor number used when registering, the
+    /// registration remains as long as any duplicate of it is open.
+    pub fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Returns the event mask of the registration, e.g. `EPOLLIN`, see
+    /// `epoll_ctl(2)`.
+    pub fn events(&self) -> u32 {
+        self.events
+    }
+
+    /// Returns the token of the registration.
+    pub fn token(&self) -> Token {
+        self.token
+    }
+
+    /// Returns the interests of the registration, if the event mask can be
+    /// expressed as `Interest`.
+    pub fn interests(&self) -> Option<Interest> {
+        let events = self.events as libc::c_int;
+        let kinds = [
+            (libc::EPOLLIN, Interest::READABLE),
+            (libc::EPOLLOUT, Interest::WRITABLE),
+            (libc::EPOLLPRI, Interest::PRIORITY),
+        ];
+        let interests = kinds
+            .iter()
+            .filter(|(flag, _)| events & flag != 0)
+            .map(|&(_, interest)| interest)
+            .fold(
+                None,
+                |interests: Option<Interest>, interest| match interests {
+                    Some(interests) => Some(interests | interest),
+                    None => Some(interest),
+                },
+            )?;
+
+        let modifiers = [
+            (events & libc::EPOLLET == 0, Interest::LEVEL),
+            (events & libc::EPOLLONESHOT != 0, Interest::ONESHOT),
+            (events & libc::EPOLLEXCLUSIVE != 0, Interest::EXCLUSIVE),
+        ];
+        Some(
+            modifiers
+                .iter()
+                .filter(|(set, _)| *set)
+                .fold(interests, |interests, &(_, interest)| interests | interest),
+        )
     }
 }
 
@@ -620,11 +905,57 @@ impl fmt::Debug for Registry {
     }
 }
 
+/// Returns an `InvalidInput` error if `interests` only contains registration
+/// modes, such as `Interest::LEVEL`, without any readiness.
+fn check_interests(interests: Interest) -> io::Result<()> {
+    if interests.has_readiness() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "registration mode interests must be combined with a readiness interest",
+        ))
+    }
+}
+
+/// Returns an `InvalidInput` error if `token` is the reserved
+/// `Token(usize::MAX)`, which the epoll selector uses for its timer.
+pub(crate) fn check_token(token: Token) -> io::Result<()> {
+    if token.0 == usize::max_value() {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "`Token(usize::MAX)` is reserved for internal use",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 /// Get access to the `sys::Selector` from `Registry`.
 pub(crate) fn selector(registry: &Registry) -> &sys::Selector {
     &registry.selector
 }
 
+cfg_io_source! {
+    /// Get access to the registrations of I/O sources from `Registry`.
+    #[cfg(feature = "registration-checks")]
+    pub(crate) fn registrations(registry: &Registry) -> &Registrations {
+        &registry.registrations
+    }
+}
+
+/// Get access to the number of calls to `Waker::wake` from `Registry`.
+#[cfg(feature = "stats")]
+pub(crate) fn wakeups(registry: &Registry) -> &Arc<AtomicUsize> {
+    &registry.wakeups
+}
+
+/// Get access to the readiness of mock sources from `Registry`.
+#[cfg(all(unix, feature = "mock"))]
+pub(crate) fn mock(registry: &Registry) -> &sys::mock::Mock {
+    &registry.mock
+}
+
 cfg_os_poll! {
     #[cfg(unix)]
     #[test]
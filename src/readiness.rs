@@ -0,0 +1,173 @@
+//! The `Readiness` type shared by the `mock` and `record` modules.
+
+use std::{fmt, ops};
+
+use crate::event::Event;
+
+/// Readiness flags, used in `Readiness`, the events of the mock selector and
+/// the log of `record::Recorder`.
+pub(crate) mod flags {
+    // These must be unique.
+    pub const READABLE: u8 = 0b0000_0001;
+    pub const WRITABLE: u8 = 0b0000_0010;
+    pub const ERROR: u8 = 0b0000_0100;
+    pub const READ_CLOSED: u8 = 0b0000_1000;
+    pub const WRITE_CLOSED: u8 = 0b0001_0000;
+    pub const PRIORITY: u8 = 0b0010_0000;
+    // Only used by the mock selector.
+    #[cfg_attr(not(feature = "mock"), allow(dead_code))]
+    pub const AIO: u8 = 0b0100_0000;
+    #[cfg_attr(not(feature = "mock"), allow(dead_code))]
+    pub const LIO: u8 = 0b1000_0000;
+}
+
+use self::flags::*;
+
+/// Readiness of an event, set using [`mock::set_readiness`] or recorded by
+/// [`record::Recorder`].
+///
+/// [`mock::set_readiness`]: ../mock/fn.set_readiness.html
+/// [`record::Recorder`]: ../record/struct.Recorder.html
+#[derive(Copy, PartialEq, Eq, Clone)]
+pub struct Readiness(u8);
+
+impl Readiness {
+    /// Returns an empty `Readiness` set.
+    pub const EMPTY: Readiness = Readiness(0);
+
+    /// Returns a `Readiness` set representing readable readiness.
+    pub const READABLE: Readiness = Readiness(READABLE);
+
+    /// Returns a `Readiness` set representing writable readiness.
+    pub const WRITABLE: Readiness = Readiness(WRITABLE);
+
+    /// Returns a `Readiness` set representing error readiness.
+    pub const ERROR: Readiness = Readiness(ERROR);
+
+    /// Returns a `Readiness` set representing read closed readiness.
+    pub const READ_CLOSED: Readiness = Readiness(READ_CLOSED);
+
+    /// Returns a `Readiness` set representing write closed readiness.
+    pub const WRITE_CLOSED: Readiness = Readiness(WRITE_CLOSED);
+
+    /// Returns a `Readiness` set representing priority readiness.
+    pub const PRIORITY: Readiness = Readiness(PRIORITY);
+
+    /// Add together two `Readiness`.
+    ///
+    /// This does the same thing as the `BitOr` implementation, but is a
+    /// constant function.
+    pub const fn add(self, other: Readiness) -> Readiness {
+        Readiness(self.0 | other.0)
+    }
+
+    /// Returns true if the value is empty.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if the value includes readable readiness.
+    pub const fn is_readable(self) -> bool {
+        (self.0 & READABLE) != 0
+    }
+
+    /// Returns true if the value includes writable readiness.
+    pub const fn is_writable(self) -> bool {
+        (self.0 & WRITABLE) != 0
+    }
+
+    /// Returns true if the value includes error readiness.
+    pub const fn is_error(self) -> bool {
+        (self.0 & ERROR) != 0
+    }
+
+    /// Returns true if the value includes read closed readiness.
+    pub const fn is_read_closed(self) -> bool {
+        (self.0 & READ_CLOSED) != 0
+    }
+
+    /// Returns true if the value includes write closed readiness.
+    pub const fn is_write_closed(self) -> bool {
+        (self.0 & WRITE_CLOSED) != 0
+    }
+
+    /// Returns true if the value includes priority readiness.
+    pub const fn is_priority(self) -> bool {
+        (self.0 & PRIORITY) != 0
+    }
+
+    pub(crate) const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the `Readiness` from its raw bits, ignoring unknown bits.
+    pub(crate) const fn from_bits(bits: u8) -> Readiness {
+        Readiness(bits & Readiness::ALL.0)
+    }
+
+    const ALL: Readiness = Readiness::READABLE
+        .add(Readiness::WRITABLE)
+        .add(Readiness::ERROR)
+        .add(Readiness::READ_CLOSED)
+        .add(Readiness::WRITE_CLOSED)
+        .add(Readiness::PRIORITY);
+}
+
+impl<'a> From<&'a Event> for Readiness {
+    /// Returns the readiness of `event`.
+    fn from(event: &'a Event) -> Readiness {
+        let flags = [
+            (event.is_readable(), Readiness::READABLE),
+            (event.is_writable(), Readiness::WRITABLE),
+            (event.is_error(), Readiness::ERROR),
+            (event.is_read_closed(), Readiness::READ_CLOSED),
+            (event.is_write_closed(), Readiness::WRITE_CLOSED),
+            (event.is_priority(), Readiness::PRIORITY),
+        ];
+        flags
+            .iter()
+            .filter(|(set, _)| *set)
+            .fold(Readiness::EMPTY, |readiness, (_, flag)| readiness | *flag)
+    }
+}
+
+impl ops::BitOr for Readiness {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        self.add(other)
+    }
+}
+
+impl ops::BitOrAssign for Readiness {
+    #[inline]
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl fmt::Debug for Readiness {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (self.is_readable(), "READABLE"),
+            (self.is_writable(), "WRITABLE"),
+            (self.is_error(), "ERROR"),
+            (self.is_read_closed(), "READ_CLOSED"),
+            (self.is_write_closed(), "WRITE_CLOSED"),
+            (self.is_priority(), "PRIORITY"),
+        ];
+        let mut one = false;
+        for (_, name) in flags.iter().filter(|(set, _)| *set) {
+            if one {
+                write!(fmt, " | ")?
+            }
+            write!(fmt, "{}", name)?;
+            one = true
+        }
+        if !one {
+            write!(fmt, "EMPTY")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,598 @@
+//! Recording and replaying the events returned by `Poll`.
+//!
+//! A [`Recorder`] wraps a [`Poll`] instance and writes every call to
+//! [`Recorder::poll`], along with the returned events, and all registrations
+//! made through it to a compact binary log. A [`Replay`] reads such a log and
+//! returns the same events from [`Replay::poll`], in the same batches. This
+//! makes it possible to capture the exact readiness sequence an event loop
+//! observed and replay it deterministically, e.g. on a developer machine.
+//! Recording only requires the `record` feature, replaying requires the
+//! `mock` feature as well.
+//!
+//! Replay is strict: the calls made to `Replay` must match the recording, in
+//! order. If the event loop makes a different call, e.g. registers a source
+//! with another token, an [`InvalidData`] error is returned describing the
+//! difference. `Replay` doesn't register event sources with the OS, so the
+//! event loop needs to replace its I/O as well, e.g. with the types in the
+//! [`mem`] module.
+//!
+//! Events are recorded with their token and readiness, see
+//! [`Readiness`], any platform specific details are lost. Timestamps
+//! are recorded as the time elapsed since the `Recorder` was created, they're
+//! available using [`records`] but not used by `Replay`.
+//!
+//! [`Recorder`]: struct.Recorder.html
+//! [`Poll`]: ../struct.Poll.html
+//! [`Recorder::poll`]: struct.Recorder.html#method.poll
+//! [`Replay`]: struct.Replay.html
+//! [`Replay::poll`]: struct.Replay.html#method.poll
+//! [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+//! [`mem`]: ../mem/index.html
+//! [`Readiness`]: struct.Readiness.html
+//! [`records`]: fn.records.html
+//!
+//! # Log format
+//!
+//! The log starts with the bytes `mio-rec` followed by a version byte (1).
+//! Then follows a record per call, starting with its kind and the elapsed
+//! time in microseconds (u64). All integers are little-endian.
+//!
+//! * `Poll` (1): number of events (u32), followed by the token (u64) and
+//!   readiness (u8) of each event.
+//! * `Register` (2) and `Reregister` (3): token (u64) and interests (u8).
+//! * `Deregister` (4): token (u64).
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! use std::time::Duration;
+//!
+//! use mio::record::{self, Readiness, Record, Recorder};
+//! use mio::{Events, Poll, Token, Waker};
+//!
+//! const WAKER: Token = Token(0);
+//! let mut events = Events::with_capacity(8);
+//!
+//! // Record the events of a poll instance, in memory in this example.
+//! let mut recorder = Recorder::new(Poll::new()?, Vec::new())?;
+//! let waker = Waker::new(recorder.registry(), WAKER)?;
+//! waker.wake()?;
+//! recorder.poll(&mut events, Some(Duration::from_secs(1)))?;
+//! let (_, log) = recorder.into_inner();
+//!
+//! // Later, inspect the recorded events.
+//! match record::records(&log[..])?.next().unwrap()? {
+//!     Record::Poll { events, .. } => assert_eq!(events, [(WAKER, Readiness::READABLE)]),
+//!     record => panic!("unexpected record: {:?}", record),
+//! }
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+use std::{cmp, fmt};
+
+pub use crate::readiness::Readiness;
+
+use crate::event::Source;
+#[cfg(all(unix, feature = "mock"))]
+use crate::sys::mock;
+use crate::{Events, Interest, Poll, Registry, Token};
+
+/// Header of the log.
+const HEADER: &[u8; 8] = b"mio-rec\x01";
+
+const POLL: u8 = 1;
+const REGISTER: u8 = 2;
+const REREGISTER: u8 = 3;
+const DEREGISTER: u8 = 4;
+
+/// A record in the log, see the [module documentation].
+///
+/// [module documentation]: index.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Record {
+    /// A call to `Poll::poll` and the events it returned.
+    Poll {
+        /// Time elapsed since the recorder was created.
+        elapsed: Duration,
+        /// The token and readiness of each event.
+        events: Vec<(Token, Readiness)>,
+    },
+    /// An event source was registered.
+    Register {
+        /// Time elapsed since the recorder was created.
+        elapsed: Duration,
+        /// Token of the registration.
+        token: Token,
+        /// Interests of the registration.
+        interests: Interest,
+    },
+    /// An event source was reregistered.
+    Reregister {
+        /// Time elapsed since the recorder was created.
+        elapsed: Duration,
+        /// Token of the registration.
+        token: Token,
+        /// Interests of the registration.
+        interests: Interest,
+    },
+    /// An event source was deregistered.
+    Deregister {
+        /// Time elapsed since the recorder was created.
+        elapsed: Duration,
+        /// Token the event source was registered with.
+        token: Token,
+    },
+}
+
+#[cfg(all(unix, feature = "mock"))]
+impl Record {
+    /// Returns the same record, ignoring the elapsed time, for comparisons.
+    fn without_elapsed(&self) -> Record {
+        let mut record = self.clone();
+        match record {
+            Record::Poll {
+                ref mut elapsed, ..
+            }
+            | Record::Register {
+                ref mut elapsed, ..
+            }
+            | Record::Reregister {
+                ref mut elapsed, ..
+            }
+            | Record::Deregister {
+                ref mut elapsed, ..
+            } => *elapsed = Duration::from_millis(0),
+        }
+        record
+    }
+}
+
+/// Wrapper around [`Poll`] recording the returned events and registrations.
+///
+/// Only calls made through the `Recorder` are recorded, registering event
+/// sources using its [`registry`] directly is possible (e.g. for a [`Waker`])
+/// but those registrations are not recorded. See the [module documentation]
+/// for more.
+///
+/// Writes are not buffered, consider wrapping `writer` in a `BufWriter`.
+///
+/// [`Poll`]: ../struct.Poll.html
+/// [`registry`]: #method.registry
+/// [`Waker`]: ../struct.Waker.html
+/// [module documentation]: index.html
+pub struct Recorder<W: Write> {
+    poll: Poll,
+    writer: W,
+    start: Instant,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Create a new `Recorder`, writing the log to `writer`.
+    pub fn new(poll: Poll, mut writer: W) -> io::Result<Recorder<W>> {
+        writer.write_all(HEADER)?;
+        Ok(Recorder {
+            poll,
+            writer,
+            start: Instant::now(),
+            buf: Vec::new(),
+        })
+    }
+
+    /// Calls [`Poll::poll`] and records the returned events.
+    ///
+    /// Errors returned by `Poll::poll` are not recorded.
+    ///
+    /// [`Poll::poll`]: ../struct.Poll.html#method.poll
+    pub fn poll(&mut self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        self.poll.poll(events, timeout)?;
+        let record = Record::Poll {
+            elapsed: self.start.elapsed(),
+            events: events
+                .iter()
+                .map(|event| (event.token(), Readiness::from(event)))
+                .collect(),
+        };
+        self.write(&record)
+    }
+
+    /// Returns the registry of the wrapped `Poll`.
+    pub fn registry(&self) -> &Registry {
+        self.poll.registry()
+    }
+
+    /// Calls [`Registry::register`] and records the registration.
+    ///
+    /// [`Registry::register`]: ../struct.Registry.html#method.register
+    pub fn register<S>(
+        &mut self,
+        source: &mut S,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()>
+    where
+        S: Source + ?Sized,
+    {
+        self.poll.registry().register(source, token, interests)?;
+        let record = Record::Register {
+            elapsed: self.start.elapsed(),
+            token,
+            interests,
+        };
+        self.write(&record)
+    }
+
+    /// Calls [`Registry::reregister`] and records the registration.
+    ///
+    /// [`Registry::reregister`]: ../struct.Registry.html#method.reregister
+    pub fn reregister<S>(
+        &mut self,
+        source: &mut S,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()>
+    where
+        S: Source + ?Sized,
+    {
+        self.poll.registry().reregister(source, token, interests)?;
+        let record = Record::Reregister {
+            elapsed: self.start.elapsed(),
+            token,
+            interests,
+        };
+        self.write(&record)
+    }
+
+    /// Calls [`Registry::deregister`] and records the deregistration.
+    ///
+    /// Event sources don't know their token, so it must be passed as `token`
+    /// to be recorded.
+    ///
+    /// [`Registry::deregister`]: ../struct.Registry.html#method.deregister
+    pub fn deregister<S>(&mut self, source: &mut S, token: Token) -> io::Result<()>
+    where
+        S: Source + ?Sized,
+    {
+        self.poll.registry().deregister(source)?;
+        let record = Record::Deregister {
+            elapsed: self.start.elapsed(),
+            token,
+        };
+        self.write(&record)
+    }
+
+    /// Returns the wrapped `Poll` and the writer.
+    pub fn into_inner(self) -> (Poll, W) {
+        (self.poll, self.writer)
+    }
+
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        self.buf.clear();
+        encode(record, &mut self.buf);
+        self.writer.write_all(&self.buf)
+    }
+}
+
+impl<W: Write> fmt::Debug for Recorder<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("poll", &self.poll)
+            .finish()
+    }
+}
+
+/// Replays a log written by a [`Recorder`].
+///
+/// See the [module documentation] for more.
+///
+/// [`Recorder`]: struct.Recorder.html
+/// [module documentation]: index.html
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use std::time::Duration;
+///
+/// use mio::record::{Recorder, Replay};
+/// use mio::{Events, Poll, Token, Waker};
+///
+/// const WAKER: Token = Token(0);
+/// let mut events = Events::with_capacity(8);
+///
+/// let mut recorder = Recorder::new(Poll::new()?, Vec::new())?;
+/// let waker = Waker::new(recorder.registry(), WAKER)?;
+/// waker.wake()?;
+/// recorder.poll(&mut events, Some(Duration::from_secs(1)))?;
+/// let (_, log) = recorder.into_inner();
+///
+/// // Replay the same events.
+/// let mut replay = Replay::new(&log[..])?;
+/// replay.poll(&mut events, None)?;
+/// let event = events.iter().next().unwrap();
+/// assert_eq!(event.token(), WAKER);
+/// assert!(event.is_readable());
+/// #     Ok(())
+/// # }
+/// ```
+#[cfg(all(unix, feature = "mock"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "mock"))))]
+pub struct Replay<R: Read> {
+    records: Records<R>,
+}
+
+#[cfg(all(unix, feature = "mock"))]
+impl<R: Read> Replay<R> {
+    /// Create a new `Replay`, reading the log from `reader`.
+    pub fn new(reader: R) -> io::Result<Replay<R>> {
+        records(reader).map(|records| Replay { records })
+    }
+
+    /// Returns the events of the next recorded call to [`Recorder::poll`].
+    ///
+    /// `timeout` is ignored, this never blocks. Returns an [`UnexpectedEof`]
+    /// error once all records are replayed, and an [`InvalidInput`] error if
+    /// the recorded events don't fit in `events`, see [`Events::capacity`].
+    ///
+    /// [`Recorder::poll`]: struct.Recorder.html#method.poll
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    /// [`Events::capacity`]: ../event/struct.Events.html#method.capacity
+    pub fn poll(&mut self, events: &mut Events, _timeout: Option<Duration>) -> io::Result<()> {
+        match self.next()? {
+            Record::Poll {
+                events: ref recorded,
+                ..
+            } if recorded.len() > events.capacity() => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "replay recorded {} events, more than the capacity of `Events` ({})",
+                    recorded.len(),
+                    events.capacity()
+                ),
+            )),
+            Record::Poll {
+                events: recorded, ..
+            } => {
+                let sys_events = events.sys();
+                sys_events.clear();
+                sys_events.extend(
+                    recorded
+                        .into_iter()
+                        .map(|(token, readiness)| mock::Event::new(token, readiness.bits())),
+                );
+                Ok(())
+            }
+            record => Err(diverged(&record, "poll")),
+        }
+    }
+
+    /// Checks the registration against the next record, the source is not
+    /// registered.
+    pub fn register<S>(
+        &mut self,
+        _source: &mut S,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()>
+    where
+        S: Source + ?Sized,
+    {
+        self.expect(Record::Register {
+            elapsed: Duration::from_millis(0),
+            token,
+            interests,
+        })
+    }
+
+    /// Checks the registration against the next record, the source is not
+    /// reregistered.
+    pub fn reregister<S>(
+        &mut self,
+        _source: &mut S,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()>
+    where
+        S: Source + ?Sized,
+    {
+        self.expect(Record::Reregister {
+            elapsed: Duration::from_millis(0),
+            token,
+            interests,
+        })
+    }
+
+    /// Checks the deregistration against the next record, the source is not
+    /// deregistered.
+    pub fn deregister<S>(&mut self, _source: &mut S, token: Token) -> io::Result<()>
+    where
+        S: Source + ?Sized,
+    {
+        self.expect(Record::Deregister {
+            elapsed: Duration::from_millis(0),
+            token,
+        })
+    }
+
+    fn next(&mut self) -> io::Result<Record> {
+        match self.records.next() {
+            Some(record) => record,
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "all records are replayed",
+            )),
+        }
+    }
+
+    fn expect(&mut self, expected: Record) -> io::Result<()> {
+        let record = self.next()?;
+        if record.without_elapsed() == expected {
+            Ok(())
+        } else {
+            Err(diverged(&record, &format!("{:?}", expected)))
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "mock"))]
+impl<R: Read> fmt::Debug for Replay<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replay").finish()
+    }
+}
+
+#[cfg(all(unix, feature = "mock"))]
+fn diverged(record: &Record, call: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("replay diverged, recorded {:?}, got {}", record, call),
+    )
+}
+
+/// Returns an iterator over the records in a log written by a [`Recorder`].
+///
+/// [`Recorder`]: struct.Recorder.html
+pub fn records<R: Read>(mut reader: R) -> io::Result<Records<R>> {
+    let mut header = [0; 8];
+    reader.read_exact(&mut header)?;
+    if &header != HEADER {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a mio record log, or unsupported version",
+        ));
+    }
+    Ok(Records { reader })
+}
+
+/// Iterator over the records in a log, see [`records`].
+///
+/// [`records`]: fn.records.html
+#[derive(Debug)]
+pub struct Records<R> {
+    reader: R,
+}
+
+impl<R: Read> Iterator for Records<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        let mut kind = [0; 1];
+        loop {
+            match self.reader.read(&mut kind) {
+                Ok(0) => return None,
+                Ok(_) => break,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        Some(decode(kind[0], &mut self.reader))
+    }
+}
+
+fn encode(record: &Record, buf: &mut Vec<u8>) {
+    fn elapsed(buf: &mut Vec<u8>, elapsed: Duration) {
+        let micros = cmp::min(elapsed.as_micros(), u128::from(u64::max_value())) as u64;
+        buf.extend_from_slice(&micros.to_le_bytes());
+    }
+
+    match *record {
+        Record::Poll {
+            elapsed: e,
+            ref events,
+        } => {
+            buf.push(POLL);
+            elapsed(buf, e);
+            buf.extend_from_slice(&(events.len() as u32).to_le_bytes());
+            for (token, readiness) in events {
+                buf.extend_from_slice(&(token.0 as u64).to_le_bytes());
+                buf.push(readiness.bits());
+            }
+        }
+        Record::Register {
+            elapsed: e,
+            token,
+            interests,
+        }
+        | Record::Reregister {
+            elapsed: e,
+            token,
+            interests,
+        } => {
+            let kind = match *record {
+                Record::Register { .. } => REGISTER,
+                _ => REREGISTER,
+            };
+            buf.push(kind);
+            elapsed(buf, e);
+            buf.extend_from_slice(&(token.0 as u64).to_le_bytes());
+            buf.push(interests.bits());
+        }
+        Record::Deregister { elapsed: e, token } => {
+            buf.push(DEREGISTER);
+            elapsed(buf, e);
+            buf.extend_from_slice(&(token.0 as u64).to_le_bytes());
+        }
+    }
+}
+
+fn decode<R: Read>(kind: u8, reader: &mut R) -> io::Result<Record> {
+    fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+        let mut buf = [0; 8];
+        reader
+            .read_exact(&mut buf)
+            .map(|()| u64::from_le_bytes(buf))
+    }
+
+    fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+        let mut buf = [0; 1];
+        reader.read_exact(&mut buf).map(|()| buf[0])
+    }
+
+    fn read_interests<R: Read>(reader: &mut R) -> io::Result<Interest> {
+        read_u8(reader).and_then(|bits| {
+            Interest::from_bits(bits)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid interests"))
+        })
+    }
+
+    let elapsed = Duration::from_micros(read_u64(reader)?);
+    match kind {
+        POLL => {
+            let mut count = [0; 4];
+            reader.read_exact(&mut count)?;
+            let count = u32::from_le_bytes(count) as usize;
+            let mut events = Vec::with_capacity(cmp::min(count, 1024));
+            for _ in 0..count {
+                let token = Token(read_u64(reader)? as usize);
+                let readiness = Readiness::from_bits(read_u8(reader)?);
+                events.push((token, readiness));
+            }
+            Ok(Record::Poll { elapsed, events })
+        }
+        REGISTER => Ok(Record::Register {
+            elapsed,
+            token: Token(read_u64(reader)? as usize),
+            interests: read_interests(reader)?,
+        }),
+        REREGISTER => Ok(Record::Reregister {
+            elapsed,
+            token: Token(read_u64(reader)? as usize),
+            interests: read_interests(reader)?,
+        }),
+        DEREGISTER => Ok(Record::Deregister {
+            elapsed,
+            token: Token(read_u64(reader)? as usize),
+        }),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid record kind",
+        )),
+    }
+}
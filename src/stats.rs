@@ -0,0 +1,240 @@
+//! Statistics and instrumentation of `Poll`.
+//!
+//! With the `stats` feature enabled [`Poll`] keeps counters about its calls to
+//! [`Poll::poll`], returned by [`Poll::stats`]. Furthermore a [`Hook`] can be
+//! set using [`Poll::set_hook`], which is called before and after waiting for
+//! events, e.g. to export metrics or traces without wrapping every call site.
+//!
+//! [`Poll`]: ../struct.Poll.html
+//! [`Poll::poll`]: ../struct.Poll.html#method.poll
+//! [`Poll::stats`]: ../struct.Poll.html#method.stats
+//! [`Hook`]: trait.Hook.html
+//! [`Poll::set_hook`]: ../struct.Poll.html#method.set_hook
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! use std::io;
+//! use std::time::Duration;
+//!
+//! use mio::stats::Hook;
+//! use mio::{Events, Poll, Token, Waker};
+//!
+//! /// Logs all calls to `Poll::poll`.
+//! struct Logger;
+//!
+//! impl Hook for Logger {
+//!     fn before_select(&mut self, timeout: Option<Duration>) {
+//!         println!("polling with timeout {:?}", timeout);
+//!     }
+//!
+//!     fn after_select(&mut self, result: &io::Result<()>, events: &Events) {
+//!         match result {
+//!             Ok(()) => println!("got {} events", events.iter().count()),
+//!             Err(err) => println!("error polling: {}", err),
+//!         }
+//!     }
+//! }
+//!
+//! let mut poll = Poll::new()?;
+//! poll.set_hook(Box::new(Logger));
+//! let mut events = Events::with_capacity(8);
+//!
+//! let waker = Waker::new(poll.registry(), Token(0))?;
+//! waker.wake()?;
+//! poll.poll(&mut events, Some(Duration::from_secs(1)))?;
+//! poll.poll(&mut events, Some(Duration::from_millis(1)))?;
+//!
+//! let stats = poll.stats();
+//! assert_eq!(stats.polls(), 2);
+//! assert_eq!(stats.events(), 1);
+//! assert_eq!(stats.wakeups(), 1);
+//! assert_eq!(stats.timeouts(), 1);
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{cmp, fmt, io};
+
+use crate::Events;
+
+/// Number of buckets in [`PollStats::histogram`].
+///
+/// [`PollStats::histogram`]: struct.PollStats.html#method.histogram
+pub const HISTOGRAM_BUCKETS: usize = 11;
+
+/// Hook called by [`Poll::poll`], see [`Poll::set_hook`].
+///
+/// [`Poll::poll`]: ../struct.Poll.html#method.poll
+/// [`Poll::set_hook`]: ../struct.Poll.html#method.set_hook
+pub trait Hook: Send + Sync {
+    /// Called before waiting for events, with the `timeout` passed to
+    /// `Poll::poll`.
+    fn before_select(&mut self, timeout: Option<Duration>) {
+        let _ = timeout;
+    }
+
+    /// Called after waiting for events, with the `result` `Poll::poll` is
+    /// about to return and the received `events`.
+    fn after_select(&mut self, result: &io::Result<()>, events: &Events) {
+        let _ = (result, events);
+    }
+}
+
+/// Statistics of a [`Poll`] instance, returned by [`Poll::stats`].
+///
+/// All counters start at zero when the `Poll` instance is created.
+///
+/// [`Poll`]: ../struct.Poll.html
+/// [`Poll::stats`]: ../struct.Poll.html#method.stats
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PollStats {
+    polls: u64,
+    events: u64,
+    histogram: [u64; HISTOGRAM_BUCKETS],
+    blocked: Duration,
+    returned: Duration,
+    wakeups: u64,
+    timeouts: u64,
+}
+
+impl PollStats {
+    /// Returns the number of calls to `Poll::poll`.
+    pub fn polls(&self) -> u64 {
+        self.polls
+    }
+
+    /// Returns the total number of events returned by `Poll::poll`.
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    /// Returns the number of calls to `Poll::poll` by the number of events
+    /// they returned.
+    ///
+    /// The first bucket counts the calls that returned no events, bucket `n`
+    /// counts the calls that returned `2^(n-1)` up to `2^n - 1` events. The
+    /// last bucket counts all calls that returned 512 or more events. Calls
+    /// that returned an error are not counted.
+    pub fn histogram(&self) -> &[u64; HISTOGRAM_BUCKETS] {
+        &self.histogram
+    }
+
+    /// Returns the total time spent waiting for events in `Poll::poll`.
+    pub fn blocked(&self) -> Duration {
+        self.blocked
+    }
+
+    /// Returns the total time between `Poll::poll` returning and the next call
+    /// to it, i.e. the time spent processing events.
+    pub fn returned(&self) -> Duration {
+        self.returned
+    }
+
+    /// Returns the number of calls to [`Waker::wake`] of the `Waker`s
+    /// registered with the `Poll` instance.
+    ///
+    /// [`Waker::wake`]: ../struct.Waker.html#method.wake
+    pub fn wakeups(&self) -> u64 {
+        self.wakeups
+    }
+
+    /// Returns the number of calls to `Poll::poll` that returned without
+    /// events, most likely because the timeout elapsed.
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+}
+
+/// Statistics kept by `Poll`.
+pub(crate) struct Stats {
+    stats: PollStats,
+    /// Shared with the `Registry`, see `Waker`.
+    wakeups: Arc<AtomicUsize>,
+    /// Last time `select` was called or returned.
+    last: Option<Instant>,
+    hook: Option<Box<dyn Hook>>,
+}
+
+impl Stats {
+    pub(crate) fn new(wakeups: Arc<AtomicUsize>) -> Stats {
+        Stats {
+            stats: PollStats {
+                polls: 0,
+                events: 0,
+                histogram: [0; HISTOGRAM_BUCKETS],
+                blocked: Duration::from_secs(0),
+                returned: Duration::from_secs(0),
+                wakeups: 0,
+                timeouts: 0,
+            },
+            wakeups,
+            last: None,
+            hook: None,
+        }
+    }
+
+    pub(crate) fn before_select(&mut self, timeout: Option<Duration>) {
+        let now = Instant::now();
+        if let Some(last) = self.last {
+            self.stats.returned += now.saturating_duration_since(last);
+        }
+        self.last = Some(now);
+        if let Some(ref mut hook) = self.hook {
+            hook.before_select(timeout);
+        }
+    }
+
+    pub(crate) fn after_select(&mut self, result: &io::Result<()>, events: &Events) {
+        let now = Instant::now();
+        if let Some(last) = self.last {
+            self.stats.blocked += now.saturating_duration_since(last);
+        }
+        self.last = Some(now);
+        self.stats.polls += 1;
+        if result.is_ok() {
+            let n = events.iter().count();
+            self.stats.events += n as u64;
+            self.stats.histogram[bucket(n)] += 1;
+            if n == 0 {
+                self.stats.timeouts += 1;
+            }
+        }
+        if let Some(ref mut hook) = self.hook {
+            hook.after_select(result, events);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> PollStats {
+        let mut stats = self.stats.clone();
+        stats.wakeups = self.wakeups.load(Ordering::Relaxed) as u64;
+        stats
+    }
+
+    pub(crate) fn set_hook(&mut self, hook: Box<dyn Hook>) {
+        self.hook = Some(hook);
+    }
+
+    pub(crate) fn take_hook(&mut self) -> Option<Box<dyn Hook>> {
+        self.hook.take()
+    }
+}
+
+impl fmt::Debug for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stats")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// Returns the histogram bucket for a call returning `n` events.
+fn bucket(n: usize) -> usize {
+    let bits = (0usize.leading_zeros() - n.leading_zeros()) as usize;
+    cmp::min(bits, HISTOGRAM_BUCKETS - 1)
+}
@@ -72,7 +72,16 @@ cfg_os_poll! {
         pub(crate) use self::unix::uds;
     }
 
-    cfg_net! {
+    #[cfg(feature = "os-util")]
+    pub(crate) use self::unix::{pipe, process, pty, signals};
+
+    #[cfg(feature = "mock")]
+    pub(crate) use self::unix::mock;
+
+    #[cfg(all(feature = "os-util", any(target_os = "android", target_os = "linux")))]
+    pub(crate) use self::unix::{inotify, timer};
+
+    cfg_io_source! {
         pub(crate) use self::unix::IoSourceState;
     }
 }
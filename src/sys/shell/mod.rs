@@ -23,12 +23,13 @@ cfg_uds! {
     pub(crate) mod uds;
 }
 
-cfg_net! {
+cfg_io_source! {
     use std::io;
+    #[cfg(unix)]
+    use std::os::unix::io::RawFd;
     #[cfg(windows)]
     use std::os::windows::io::RawSocket;
 
-    #[cfg(windows)]
     use crate::{Registry, Token, Interest};
 
     pub(crate) struct IoSourceState;
@@ -73,4 +74,31 @@ cfg_net! {
             os_required!()
         }
     }
+
+    #[cfg(unix)]
+    impl IoSourceState {
+        pub fn register(
+            &mut self,
+            registry: &Registry,
+            token: Token,
+            interests: Interest,
+            fd: RawFd,
+        ) -> io::Result<()> {
+            crate::poll::selector(registry).register(fd, token, interests)
+        }
+
+        pub fn reregister(
+            &mut self,
+            registry: &Registry,
+            token: Token,
+            interests: Interest,
+            fd: RawFd,
+        ) -> io::Result<()> {
+            crate::poll::selector(registry).reregister(fd, token, interests)
+        }
+
+        pub fn deregister(&mut self, registry: &Registry, fd: RawFd) -> io::Result<()> {
+            crate::poll::selector(registry).deregister(fd)
+        }
+    }
 }
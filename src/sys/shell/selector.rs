@@ -39,8 +39,8 @@ cfg_any_os_util! {
     }
 }
 
-cfg_net! {
-    #[cfg(debug_assertions)]
+cfg_io_source! {
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
     impl Selector {
         pub fn id(&self) -> usize {
             os_required!();
@@ -1,11 +1,207 @@
 use std::io;
 use std::net::{self, SocketAddr};
+use std::time::Duration;
 
-pub fn connect(_: SocketAddr) -> io::Result<net::TcpStream> {
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use crate::net::TcpInfo;
+#[cfg(not(target_os = "openbsd"))]
+use crate::net::TcpKeepalive;
+
+#[cfg(unix)]
+pub(crate) type TcpSocket = std::os::unix::io::RawFd;
+#[cfg(windows)]
+pub(crate) type TcpSocket = std::os::windows::io::RawSocket;
+
+pub(crate) fn new_v4_socket() -> io::Result<TcpSocket> {
+    os_required!();
+}
+
+pub(crate) fn new_v6_socket() -> io::Result<TcpSocket> {
+    os_required!();
+}
+
+pub(crate) fn bind(_: TcpSocket, _: SocketAddr) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn connect(_: TcpSocket, _: SocketAddr) -> io::Result<net::TcpStream> {
+    os_required!();
+}
+
+pub(crate) fn listen(_: TcpSocket, _: u32) -> io::Result<net::TcpListener> {
     os_required!();
 }
 
-pub fn bind(_: SocketAddr) -> io::Result<net::TcpListener> {
+pub(crate) fn close(_: TcpSocket) {
+    os_required!();
+}
+
+pub(crate) fn set_reuseaddr(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn get_reuseaddr(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+pub(crate) fn set_reuseport(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+pub(crate) fn get_reuseport(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
+pub(crate) fn set_only_v6(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn get_only_v6(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
+pub(crate) fn set_send_buffer_size(_: TcpSocket, _: u32) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn get_send_buffer_size(_: TcpSocket) -> io::Result<u32> {
+    os_required!();
+}
+
+pub(crate) fn set_recv_buffer_size(_: TcpSocket, _: u32) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn get_recv_buffer_size(_: TcpSocket) -> io::Result<u32> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_bind_address_no_port(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_bind_address_no_port(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
+pub(crate) fn set_keepalive(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn get_keepalive(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
+#[cfg(not(target_os = "openbsd"))]
+pub(crate) fn set_keepalive_params(_: TcpSocket, _: TcpKeepalive) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(not(target_os = "openbsd"))]
+pub(crate) fn get_keepalive_params(_: TcpSocket) -> io::Result<TcpKeepalive> {
+    os_required!();
+}
+
+pub(crate) fn set_linger(_: TcpSocket, _: Option<Duration>) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn get_linger(_: TcpSocket) -> io::Result<Option<Duration>> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_user_timeout(_: TcpSocket, _: Duration) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_user_timeout(_: TcpSocket) -> io::Result<Duration> {
+    os_required!();
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) fn set_notsent_lowat(_: TcpSocket, _: u32) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) fn get_notsent_lowat(_: TcpSocket) -> io::Result<u32> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_quickack(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_quickack(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_cork(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_cork(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
+#[cfg(unix)]
+pub(crate) fn set_recv_lowat(_: TcpSocket, _: u32) -> io::Result<()> {
+    os_required!();
+}
+
+#[cfg(unix)]
+pub(crate) fn get_recv_lowat(_: TcpSocket) -> io::Result<u32> {
+    os_required!();
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) fn get_tcp_info(_: TcpSocket) -> io::Result<TcpInfo> {
+    os_required!();
+}
+
+pub(crate) fn get_recv_queue_len(_: TcpSocket) -> io::Result<usize> {
+    os_required!();
+}
+
+cfg_send_queue_len! {
+    pub(crate) fn get_send_queue_len(_: TcpSocket) -> io::Result<usize> {
+        os_required!();
+    }
+}
+
+pub(crate) fn get_localaddr(_: TcpSocket) -> io::Result<SocketAddr> {
     os_required!();
 }
 
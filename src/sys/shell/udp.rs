@@ -4,3 +4,13 @@ use std::net::{self, SocketAddr};
 pub fn bind(_: SocketAddr) -> io::Result<net::UdpSocket> {
     os_required!()
 }
+
+pub(crate) fn recv_queue_len(_: &net::UdpSocket) -> io::Result<usize> {
+    os_required!()
+}
+
+cfg_send_queue_len! {
+    pub(crate) fn send_queue_len(_: &net::UdpSocket) -> io::Result<usize> {
+        os_required!()
+    }
+}
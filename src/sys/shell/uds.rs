@@ -24,6 +24,16 @@ pub(crate) mod datagram {
         os_required!()
     }
 
+    pub(crate) fn recv_queue_len(_: &net::UnixDatagram) -> io::Result<usize> {
+        os_required!()
+    }
+
+    cfg_send_queue_len! {
+        pub(crate) fn send_queue_len(_: &net::UnixDatagram) -> io::Result<usize> {
+            os_required!()
+        }
+    }
+
     pub(crate) fn recv_from(
         _: &net::UnixDatagram,
         _: &mut [u8],
@@ -72,4 +82,14 @@ pub(crate) mod stream {
     pub(crate) fn peer_addr(_: &net::UnixStream) -> io::Result<SocketAddr> {
         os_required!()
     }
+
+    pub(crate) fn recv_queue_len(_: &net::UnixStream) -> io::Result<usize> {
+        os_required!()
+    }
+
+    cfg_send_queue_len! {
+        pub(crate) fn send_queue_len(_: &net::UnixStream) -> io::Result<usize> {
+            os_required!()
+        }
+    }
 }
@@ -0,0 +1,28 @@
+use std::ffi::CString;
+use std::fs::File;
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::Path;
+
+pub fn new() -> io::Result<File> {
+    syscall!(inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK))
+        .map(|fd| unsafe { File::from_raw_fd(fd) })
+}
+
+/// Returns the watch descriptor, see `inotify_add_watch(2)`.
+pub fn add_watch(inotify: &File, path: &Path, mask: u32) -> io::Result<c_int> {
+    let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "path must not contain null bytes",
+        )
+    })?;
+    syscall!(inotify_add_watch(inotify.as_raw_fd(), path.as_ptr(), mask))
+}
+
+pub fn remove_watch(inotify: &File, wd: c_int) -> io::Result<()> {
+    // The type of `wd` differs between Linux (`c_int`) and Android (`u32`).
+    syscall!(inotify_rm_watch(inotify.as_raw_fd(), wd as _)).map(|_| ())
+}
@@ -0,0 +1,537 @@
+//! Mock readiness used by the `mock` feature.
+//!
+//! The OS selector is still used for all file descriptors, its events are
+//! converted into the `Event` type below. The readiness of mock event sources
+//! is kept in `Mock`, which is returned before any events from the OS. Changes
+//! to it wake up a polling thread using a pipe registered with the selector.
+
+use std::cmp;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::pipe;
+use super::selector::{self, Selector};
+use crate::{Interest, Token};
+
+pub(crate) use crate::readiness::flags as readiness;
+
+use self::readiness::*;
+
+#[derive(Clone)]
+pub struct Event {
+    token: Token,
+    /// Only the readiness of events returned by the OS selector is kept, the
+    /// events themselves aren't `Send` and `Sync` on all platforms.
+    readiness: u8,
+}
+
+impl Event {
+    pub fn new(token: Token, readiness: u8) -> Event {
+        Event { token, readiness }
+    }
+
+    /// Convert an event returned by the OS selector.
+    fn from_sys(event: &selector::Event) -> Event {
+        use self::selector::event::*;
+
+        let flags = [
+            (is_readable(event), READABLE),
+            (is_writable(event), WRITABLE),
+            (is_error(event), ERROR),
+            (is_read_closed(event), READ_CLOSED),
+            (is_write_closed(event), WRITE_CLOSED),
+            (is_priority(event), PRIORITY),
+            (is_aio(event), AIO),
+            (is_lio(event), LIO),
+        ];
+        let readiness = flags
+            .iter()
+            .filter(|(set, _)| *set)
+            .fold(0, |readiness, (_, flag)| readiness | flag);
+        Event {
+            token: token(event),
+            readiness,
+        }
+    }
+}
+
+pub type Events = Vec<Event>;
+
+pub mod event {
+    use std::fmt;
+
+    use super::readiness::{self, *};
+    use super::Event;
+    use crate::Token;
+
+    pub fn token(event: &Event) -> Token {
+        event.token
+    }
+
+    pub fn is_readable(event: &Event) -> bool {
+        (event.readiness & READABLE) != 0
+    }
+
+    pub fn is_writable(event: &Event) -> bool {
+        (event.readiness & WRITABLE) != 0
+    }
+
+    pub fn is_error(event: &Event) -> bool {
+        (event.readiness & ERROR) != 0
+    }
+
+    pub fn is_read_closed(event: &Event) -> bool {
+        (event.readiness & READ_CLOSED) != 0
+    }
+
+    pub fn is_write_closed(event: &Event) -> bool {
+        (event.readiness & WRITE_CLOSED) != 0
+    }
+
+    pub fn is_priority(event: &Event) -> bool {
+        (event.readiness & PRIORITY) != 0
+    }
+
+    pub fn is_aio(event: &Event) -> bool {
+        (event.readiness & AIO) != 0
+    }
+
+    pub fn is_lio(event: &Event) -> bool {
+        (event.readiness & LIO) != 0
+    }
+
+    pub fn debug_details(f: &mut fmt::Formatter<'_>, event: &Event) -> fmt::Result {
+        fn check_flag(got: &u8, want: &u8) -> bool {
+            (got & want) != 0
+        }
+        debug_detail!(
+            ReadinessDetails(u8),
+            check_flag,
+            readiness::READABLE,
+            readiness::WRITABLE,
+            readiness::ERROR,
+            readiness::READ_CLOSED,
+            readiness::WRITE_CLOSED,
+            readiness::PRIORITY,
+            readiness::AIO,
+            readiness::LIO,
+        );
+
+        f.debug_struct("mock_event")
+            .field("token", &event.token)
+            .field("readiness", &ReadinessDetails(event.readiness))
+            .finish()
+    }
+}
+
+/// Token of the wake-up pipe of `Mock`, its events are never returned.
+pub const WAKE: Token = Token(usize::max_value() - 1);
+
+/// Returns a unique id for a mock event source.
+pub fn next_id() -> usize {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Readiness of the mock event sources registered with a `Registry`, shared
+/// between all its clones.
+#[derive(Clone, Debug)]
+pub struct Mock {
+    state: Arc<Mutex<State>>,
+    wake: Arc<Wake>,
+}
+
+#[derive(Debug)]
+struct State {
+    /// Registrations, indexed by the id of the mock event source.
+    registrations: BTreeMap<usize, Registration>,
+    /// Readiness of edge-triggered registrations, in order of `set_readiness`
+    /// calls.
+    pending: VecDeque<(usize, u8)>,
+    /// Readiness to set at a later time, see `Mock::schedule`.
+    timers: Vec<Timer>,
+}
+
+#[derive(Debug)]
+struct Registration {
+    token: Token,
+    interests: Interest,
+    /// Last set readiness, returned by level-triggered registrations.
+    readiness: u8,
+    /// False if a one-shot registration returned its event.
+    armed: bool,
+}
+
+#[derive(Debug)]
+struct Timer {
+    deadline: Instant,
+    id: usize,
+    readiness: u8,
+}
+
+/// Pipe used to wake up a thread polling the OS selector after the readiness
+/// of a mock event source changed.
+#[derive(Debug)]
+struct Wake {
+    sender: File,
+    receiver: File,
+    selector: Selector,
+}
+
+impl Wake {
+    fn new(selector: &Selector) -> io::Result<Wake> {
+        let (sender, receiver) = pipe::new()?;
+        // Level-triggered so we don't have to rearm it, we drain the pipe
+        // every time we receive an event anyway.
+        let interests = Interest::READABLE.add(Interest::LEVEL);
+        selector.register(receiver.as_raw_fd(), WAKE, interests)?;
+        Ok(Wake {
+            sender,
+            receiver,
+            selector: selector.try_clone()?,
+        })
+    }
+
+    fn wake(&self) {
+        // If the pipe is full the poll is already woken up.
+        let _ = (&self.sender).write(&[1]);
+    }
+
+    fn drain(&self) {
+        let mut buf = [0; 64];
+        while let Ok(n) = (&self.receiver).read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for Wake {
+    fn drop(&mut self) {
+        let _ = self.selector.deregister(self.receiver.as_raw_fd());
+    }
+}
+
+/// Returns the readiness flags `interests` can return.
+fn mask(interests: Interest) -> u8 {
+    let mut mask = ERROR | READ_CLOSED | WRITE_CLOSED;
+    if interests.is_readable() {
+        mask |= READABLE;
+    }
+    if interests.is_writable() {
+        mask |= WRITABLE;
+    }
+    if interests.is_priority() {
+        mask |= PRIORITY;
+    }
+    if interests.is_aio() {
+        mask |= AIO;
+    }
+    if interests.is_lio() {
+        mask |= LIO;
+    }
+    mask
+}
+
+fn not_registered() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        "mock event source not registered with `Registry`",
+    )
+}
+
+impl Mock {
+    pub fn new(selector: &Selector) -> io::Result<Mock> {
+        Ok(Mock {
+            state: Arc::new(Mutex::new(State {
+                registrations: BTreeMap::new(),
+                pending: VecDeque::new(),
+                timers: Vec::new(),
+            })),
+            wake: Arc::new(Wake::new(selector)?),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The state is always valid, even if a thread panicked.
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn register(&self, id: usize, token: Token, interests: Interest) -> io::Result<()> {
+        let mut state = self.lock();
+        if state.registrations.contains_key(&id) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "mock event source already registered with `Registry`",
+            ));
+        }
+        let registration = Registration {
+            token,
+            interests,
+            readiness: 0,
+            armed: true,
+        };
+        state.registrations.insert(id, registration);
+        Ok(())
+    }
+
+    pub fn reregister(&self, id: usize, token: Token, interests: Interest) -> io::Result<()> {
+        let mut state = self.lock();
+        let registration = state
+            .registrations
+            .get_mut(&id)
+            .ok_or_else(not_registered)?;
+        registration.token = token;
+        registration.interests = interests;
+        registration.armed = true;
+        Ok(())
+    }
+
+    pub fn deregister(&self, id: usize) -> io::Result<()> {
+        let mut state = self.lock();
+        state.registrations.remove(&id).ok_or_else(not_registered)?;
+        state.pending.retain(|(pending_id, _)| *pending_id != id);
+        state.timers.retain(|timer| timer.id != id);
+        Ok(())
+    }
+
+    /// Returns the token and interests of all registrations.
+    #[cfg(feature = "registration-checks")]
+    pub fn registrations(&self) -> Vec<(Token, Interest)> {
+        self.lock()
+            .registrations
+            .values()
+            .map(|registration| (registration.token, registration.interests))
+            .collect()
+    }
+
+    /// Set the readiness of all mock event sources registered with `token`.
+    pub fn set_readiness(&self, token: Token, readiness: u8) -> io::Result<()> {
+        let mut state = self.lock();
+        let State {
+            registrations,
+            pending,
+            ..
+        } = &mut *state;
+
+        let mut found = false;
+        let mut wake = false;
+        for (id, registration) in registrations.iter_mut() {
+            if registration.token == token {
+                found = true;
+                wake |= registration.set(*id, readiness, readiness, pending);
+            }
+        }
+        drop(state);
+
+        if wake {
+            self.wake.wake();
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no mock event source registered with token",
+            ))
+        }
+    }
+
+    /// Update the readiness of the mock event source with `id`, if registered.
+    ///
+    /// `readiness` is the current readiness, returned by level-triggered
+    /// registrations, `edge` the readiness that changed since the last update,
+    /// returned by edge-triggered registrations.
+    pub fn update(&self, id: usize, readiness: u8, edge: u8) {
+        let mut state = self.lock();
+        let State {
+            registrations,
+            pending,
+            ..
+        } = &mut *state;
+        let wake = match registrations.get_mut(&id) {
+            Some(registration) => registration.set(id, readiness, edge, pending),
+            None => false,
+        };
+        drop(state);
+
+        if wake {
+            self.wake.wake();
+        }
+    }
+
+    /// Add `readiness` to the mock event source with `id` at `deadline`.
+    pub fn schedule(&self, id: usize, deadline: Instant, readiness: u8) {
+        self.lock().timers.push(Timer {
+            deadline,
+            id,
+            readiness,
+        });
+        // Poll might need to use a shorter timeout.
+        self.wake.wake();
+    }
+
+    /// Poll for events, first returning the readiness of mock event sources,
+    /// then polling `selector` for events.
+    pub fn select(
+        &self,
+        selector: &Selector,
+        events: &mut Events,
+        timeout: Option<Duration>,
+    ) -> io::Result<()> {
+        events.clear();
+        let capacity = events.capacity();
+        let end = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            let (deadline, level) = self.fill(events, capacity, true);
+            if events.len() >= capacity {
+                return Ok(());
+            }
+
+            // Don't block if we already have events, but include any events
+            // from the OS, e.g. from a `Waker`. Otherwise wake up for the
+            // first timer.
+            let now = Instant::now();
+            let timeout = if !events.is_empty() {
+                Some(Duration::from_millis(0))
+            } else {
+                let remaining = end.map(|end| end.saturating_duration_since(now));
+                match deadline {
+                    Some(deadline) => {
+                        let until = deadline.saturating_duration_since(now);
+                        Some(remaining.map_or(until, |remaining| cmp::min(remaining, until)))
+                    }
+                    None => remaining,
+                }
+            };
+            let mut sys_events = selector::Events::with_capacity(capacity - events.len());
+            match selector.select(&mut sys_events, timeout) {
+                Ok(()) => {}
+                // Don't lose the events of mock event sources.
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted && !events.is_empty() => {
+                    return Ok(())
+                }
+                Err(err) => return Err(err),
+            }
+
+            let mut woken = false;
+            for event in sys_events.iter() {
+                if selector::event::token(event) == WAKE {
+                    woken = true;
+                } else {
+                    events.push(Event::from_sys(event));
+                }
+            }
+            if woken {
+                self.wake.drain();
+            }
+            if woken || deadline.is_some() {
+                // Readiness set, or timers expired, while polling.
+                let _ = self.fill(events, capacity, !level);
+            }
+
+            // Only woken up, e.g. because a timer was added, so poll again if
+            // there is time left.
+            let expired = match end {
+                Some(end) => Instant::now() >= end,
+                None => false,
+            };
+            if !events.is_empty() || timeout == Some(Duration::from_millis(0)) || expired {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Fill `events` with the readiness of mock event sources, edge-triggered
+    /// first in order of `set_readiness` calls, then level-triggered in order
+    /// of registration, if `level` is true. Returns the deadline of the first
+    /// timer, if any, and whether any level-triggered events were added.
+    fn fill(&self, events: &mut Events, capacity: usize, level: bool) -> (Option<Instant>, bool) {
+        let mut state = self.lock();
+        let State {
+            registrations,
+            pending,
+            timers,
+        } = &mut *state;
+
+        let now = Instant::now();
+        let mut deadline: Option<Instant> = None;
+        timers.sort_by_key(|timer| timer.deadline);
+        timers.retain(|timer| {
+            if timer.deadline <= now {
+                if let Some(registration) = registrations.get_mut(&timer.id) {
+                    let readiness = registration.readiness | timer.readiness;
+                    let _ = registration.set(timer.id, readiness, timer.readiness, pending);
+                }
+                false
+            } else {
+                if deadline.is_none() {
+                    deadline = Some(timer.deadline);
+                }
+                true
+            }
+        });
+
+        while events.len() < capacity {
+            let (id, readiness) = match pending.pop_front() {
+                Some(pending) => pending,
+                None => break,
+            };
+            if let Some(registration) = registrations.get_mut(&id) {
+                registration.add_event(events, readiness);
+            }
+        }
+
+        let len = events.len();
+        for registration in registrations.values_mut() {
+            if !level || events.len() >= capacity {
+                break;
+            }
+            if registration.interests.is_level() {
+                let readiness = registration.readiness & mask(registration.interests);
+                registration.add_event(events, readiness);
+            }
+        }
+        (deadline, events.len() != len)
+    }
+}
+
+impl Registration {
+    /// Set the readiness, returns true if an event is ready to be returned.
+    fn set(
+        &mut self,
+        id: usize,
+        readiness: u8,
+        edge: u8,
+        pending: &mut VecDeque<(usize, u8)>,
+    ) -> bool {
+        self.readiness = readiness;
+        let mask = mask(self.interests);
+        if self.interests.is_level() {
+            return self.armed && (readiness & mask) != 0;
+        }
+        let edge = edge & mask;
+        if edge == 0 {
+            return false;
+        }
+        // Merge the readiness with an event that isn't returned yet.
+        match pending.iter_mut().find(|(pending_id, _)| *pending_id == id) {
+            Some((_, pending_readiness)) => *pending_readiness |= edge,
+            None => pending.push_back((id, edge)),
+        }
+        self.armed
+    }
+
+    fn add_event(&mut self, events: &mut Events, readiness: u8) {
+        if self.armed && readiness != 0 {
+            events.push(Event::new(self.token, readiness));
+            self.armed = !self.interests.is_oneshot();
+        }
+    }
+}
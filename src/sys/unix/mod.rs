@@ -17,7 +17,14 @@ cfg_os_poll! {
     mod net;
 
     mod selector;
-    pub(crate) use self::selector::{event, Event, Events, Selector};
+    pub(crate) use self::selector::Selector;
+    #[cfg(not(feature = "mock"))]
+    pub(crate) use self::selector::{event, Event, Events};
+
+    #[cfg(feature = "mock")]
+    pub(crate) mod mock;
+    #[cfg(feature = "mock")]
+    pub(crate) use self::mock::{event, Event, Events};
 
     mod sourcefd;
     pub use self::sourcefd::SourceFd;
@@ -38,12 +45,34 @@ cfg_os_poll! {
         pub use self::uds::SocketAddr;
     }
 
-    cfg_net! {
+    #[cfg(any(feature = "os-util", feature = "mock", feature = "poll-fallback"))]
+    pub(crate) mod pipe;
+
+    #[cfg(feature = "os-util")]
+    pub(crate) mod process;
+
+    #[cfg(feature = "os-util")]
+    pub(crate) mod pty;
+
+    #[cfg(feature = "os-util")]
+    pub(crate) mod signals;
+
+    #[cfg(all(feature = "os-util", any(target_os = "android", target_os = "linux")))]
+    pub(crate) mod inotify;
+
+    #[cfg(all(feature = "os-util", any(target_os = "android", target_os = "linux")))]
+    pub(crate) mod timer;
+
+    cfg_io_source! {
+        use crate::{poll, Interest, Registry, Token};
         use std::io;
+        use std::os::unix::io::RawFd;
 
         // Both `kqueue` and `epoll` don't need to hold any user space state.
+        #[cfg(not(any(feature = "poll-fallback", all(feature = "io-uring", target_os = "linux"))))]
         pub(crate) struct IoSourceState;
 
+        #[cfg(not(any(feature = "poll-fallback", all(feature = "io-uring", target_os = "linux"))))]
         impl IoSourceState {
             pub fn new() -> IoSourceState {
                 IoSourceState
@@ -57,6 +86,121 @@ cfg_os_poll! {
                 // return.
                 f(io)
             }
+
+            pub fn register(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+                fd: RawFd,
+            ) -> io::Result<()> {
+                poll::selector(registry).register(fd, token, interests)
+            }
+
+            pub fn reregister(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+                fd: RawFd,
+            ) -> io::Result<()> {
+                poll::selector(registry).reregister(fd, token, interests)
+            }
+
+            pub fn deregister(&mut self, registry: &Registry, fd: RawFd) -> io::Result<()> {
+                poll::selector(registry).deregister(fd)
+            }
+        }
+
+        /// A poll request of io_uring holds a reference to the file, which
+        /// means that simply closing the file descriptor doesn't remove the
+        /// registration (and doesn't close the file). So we need to keep track
+        /// of the registration to remove it when the source is dropped.
+        ///
+        /// The `poll(2)` selector keeps its registrations in user space, so
+        /// they need to be removed as well. Furthermore it needs to be told
+        /// when an I/O operation would block, to emulate edge triggers.
+        #[cfg(any(feature = "poll-fallback", all(feature = "io-uring", target_os = "linux")))]
+        pub(crate) struct IoSourceState {
+            // This is `None` if the source is not registered.
+            registration: Option<(Selector, RawFd)>,
+        }
+
+        #[cfg(any(feature = "poll-fallback", all(feature = "io-uring", target_os = "linux")))]
+        impl IoSourceState {
+            pub fn new() -> IoSourceState {
+                IoSourceState { registration: None }
+            }
+
+            #[cfg(not(feature = "poll-fallback"))]
+            pub fn do_io<T, F, R>(&self, f: F, io: &T) -> io::Result<R>
+            where
+                F: FnOnce(&T) -> io::Result<R>,
+            {
+                // Poll requests are rearmed by the selector, so we can just
+                // call the function and return.
+                f(io)
+            }
+
+            #[cfg(feature = "poll-fallback")]
+            pub fn do_io<T, F, R>(&self, f: F, io: &T) -> io::Result<R>
+            where
+                F: FnOnce(&T) -> io::Result<R>,
+            {
+                let result = f(io);
+                if let Some((ref selector, fd)) = self.registration {
+                    // The I/O operation might have changed the readiness of
+                    // the source, e.g. consumed all data, so we need to
+                    // report any new readiness again.
+                    if let Err(err) = selector.rearm(fd, &result) {
+                        // The result of the I/O operation is still valid, the
+                        // source just might not get any new events.
+                        log::error!("error rearming poll registration: {}", err);
+                    }
+                }
+                result
+            }
+
+            pub fn register(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+                fd: RawFd,
+            ) -> io::Result<()> {
+                if self.registration.is_some() {
+                    return Err(io::ErrorKind::AlreadyExists.into());
+                }
+                let selector = poll::selector(registry);
+                selector.register(fd, token, interests)?;
+                self.registration = Some((selector.try_clone()?, fd));
+                Ok(())
+            }
+
+            pub fn reregister(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+                fd: RawFd,
+            ) -> io::Result<()> {
+                poll::selector(registry).reregister(fd, token, interests)
+            }
+
+            pub fn deregister(&mut self, registry: &Registry, fd: RawFd) -> io::Result<()> {
+                poll::selector(registry).deregister(fd).map(|()| {
+                    self.registration = None;
+                })
+            }
+        }
+
+        #[cfg(any(feature = "poll-fallback", all(feature = "io-uring", target_os = "linux")))]
+        impl Drop for IoSourceState {
+            fn drop(&mut self) {
+                if let Some((selector, fd)) = self.registration.take() {
+                    let _ = selector.deregister(fd);
+                }
+            }
         }
     }
 }
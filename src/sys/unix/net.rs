@@ -1,7 +1,7 @@
 #[cfg(all(feature = "os-poll", any(feature = "tcp", feature = "udp")))]
 use std::net::SocketAddr;
 
-#[cfg(all(feature = "os-poll", any(feature = "tcp", feature = "udp")))]
+#[cfg(all(feature = "os-poll", feature = "udp"))]
 pub(crate) fn new_ip_socket(
     addr: SocketAddr,
     socket_type: libc::c_int,
@@ -56,34 +56,148 @@ pub(crate) fn new_socket(
     socket
 }
 
+/// A type with the same memory layout as `libc::sockaddr`, used to convert a
+/// `SocketAddr` into its system representation.
+///
+/// The layout of the `SocketAddrV4` and `SocketAddrV6` types in the standard
+/// library is unspecified, so they can't be passed to the system directly.
 #[cfg(all(feature = "os-poll", any(feature = "tcp", feature = "udp")))]
-pub(crate) fn socket_addr(addr: &SocketAddr) -> (*const libc::sockaddr, libc::socklen_t) {
-    use std::mem::size_of_val;
+#[repr(C)]
+pub(crate) union SocketAddrCRepr {
+    v4: libc::sockaddr_in,
+    v6: libc::sockaddr_in6,
+}
+
+#[cfg(all(feature = "os-poll", any(feature = "tcp", feature = "udp")))]
+impl SocketAddrCRepr {
+    pub(crate) fn as_ptr(&self) -> *const libc::sockaddr {
+        self as *const _ as *const libc::sockaddr
+    }
+}
+
+/// Converts a Rust `SocketAddr` into the system representation.
+#[cfg(all(feature = "os-poll", any(feature = "tcp", feature = "udp")))]
+pub(crate) fn socket_addr(addr: &SocketAddr) -> (SocketAddrCRepr, libc::socklen_t) {
+    use std::mem::{size_of, zeroed};
 
     match addr {
-        SocketAddr::V4(ref addr) => (
-            addr as *const _ as *const libc::sockaddr,
-            size_of_val(addr) as libc::socklen_t,
-        ),
-        SocketAddr::V6(ref addr) => (
-            addr as *const _ as *const libc::sockaddr,
-            size_of_val(addr) as libc::socklen_t,
-        ),
+        SocketAddr::V4(ref addr) => {
+            // This is safe as all fields are integers, for which zero is a
+            // valid value.
+            let mut sockaddr: libc::sockaddr_in = unsafe { zeroed() };
+            sockaddr.sin_family = libc::AF_INET as libc::sa_family_t;
+            sockaddr.sin_port = addr.port().to_be();
+            // `s_addr` is stored in network byte order, which is the same order
+            // as the octets.
+            sockaddr.sin_addr.s_addr = u32::from_ne_bytes(addr.ip().octets());
+            #[cfg(any(
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "ios",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            {
+                sockaddr.sin_len = size_of::<libc::sockaddr_in>() as u8;
+            }
+            (
+                SocketAddrCRepr { v4: sockaddr },
+                size_of::<libc::sockaddr_in>() as libc::socklen_t,
+            )
+        }
+        SocketAddr::V6(ref addr) => {
+            // Safe for the same reason as above.
+            let mut sockaddr: libc::sockaddr_in6 = unsafe { zeroed() };
+            sockaddr.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sockaddr.sin6_port = addr.port().to_be();
+            sockaddr.sin6_addr.s6_addr = addr.ip().octets();
+            sockaddr.sin6_flowinfo = addr.flowinfo();
+            sockaddr.sin6_scope_id = addr.scope_id();
+            #[cfg(any(
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "ios",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            {
+                sockaddr.sin6_len = size_of::<libc::sockaddr_in6>() as u8;
+            }
+            (
+                SocketAddrCRepr { v6: sockaddr },
+                size_of::<libc::sockaddr_in6>() as libc::socklen_t,
+            )
+        }
     }
 }
 
+/// Converts the system representation of an address into a `SocketAddr`.
+///
 /// `storage` must be initialised to `sockaddr_in` or `sockaddr_in6`.
 #[cfg(all(feature = "os-poll", feature = "tcp"))]
 pub(crate) unsafe fn to_socket_addr(
     storage: *const libc::sockaddr_storage,
 ) -> std::io::Result<SocketAddr> {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+
     match (*storage).ss_family as libc::c_int {
-        libc::AF_INET => Ok(SocketAddr::V4(
-            *(storage as *const libc::sockaddr_in as *const _),
-        )),
-        libc::AF_INET6 => Ok(SocketAddr::V6(
-            *(storage as *const libc::sockaddr_in6 as *const _),
-        )),
+        libc::AF_INET => {
+            let addr = &*(storage as *const libc::sockaddr_in);
+            let ip = Ipv4Addr::from(addr.sin_addr.s_addr.to_ne_bytes());
+            let port = u16::from_be(addr.sin_port);
+            Ok(SocketAddr::V4(SocketAddrV4::new(ip, port)))
+        }
+        libc::AF_INET6 => {
+            let addr = &*(storage as *const libc::sockaddr_in6);
+            let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+            let port = u16::from_be(addr.sin6_port);
+            Ok(SocketAddr::V6(SocketAddrV6::new(
+                ip,
+                port,
+                addr.sin6_flowinfo,
+                addr.sin6_scope_id,
+            )))
+        }
         _ => Err(std::io::ErrorKind::InvalidInput.into()),
     }
 }
+
+/// Returns the number of bytes in the receive queue of `socket`, using
+/// `ioctl(FIONREAD)`.
+#[cfg(all(
+    feature = "os-poll",
+    any(feature = "tcp", feature = "udp", feature = "uds")
+))]
+pub(crate) fn recv_queue_len(socket: libc::c_int) -> std::io::Result<usize> {
+    let mut len: libc::c_int = 0;
+    syscall!(ioctl(socket, libc::FIONREAD as _, &mut len)).map(|_| len as usize)
+}
+
+cfg_send_queue_len! {
+    /// Returns the number of bytes in the send queue of `socket`, i.e. bytes not
+    /// yet sent or, for TCP, not yet acknowledged by the peer.
+    #[cfg(all(feature = "os-poll", any(feature = "tcp", feature = "udp", feature = "uds")))]
+    pub(crate) fn send_queue_len(socket: libc::c_int) -> std::io::Result<usize> {
+        let mut len: libc::c_int = 0;
+        // `TIOCOUTQ` is the same as `SIOCOUTQ`.
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        syscall!(ioctl(socket, libc::TIOCOUTQ as _, &mut len))?;
+        #[cfg(any(target_os = "freebsd", target_os = "netbsd"))]
+        syscall!(ioctl(socket, libc::FIONWRITE as _, &mut len))?;
+        // Darwin doesn't support `FIONWRITE`, but has a socket option.
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        {
+            let mut length = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+            syscall!(getsockopt(
+                socket,
+                libc::SOL_SOCKET,
+                libc::SO_NWRITE,
+                &mut len as *mut libc::c_int as *mut libc::c_void,
+                &mut length,
+            ))?;
+        }
+        Ok(len as usize)
+    }
+}
@@ -0,0 +1,42 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{FromRawFd, RawFd};
+
+/// Create a new non-blocking pipe, returning the sending and receiving ends.
+pub fn new() -> io::Result<(File, File)> {
+    let mut fds: [RawFd; 2] = [-1, -1];
+
+    #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+    syscall!(pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC))?;
+
+    // Darwin doesn't have `pipe2`, so we need to set the flags ourselves.
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    {
+        syscall!(pipe(fds.as_mut_ptr()))?;
+        for fd in fds.iter() {
+            if let Err(err) = syscall!(fcntl(*fd, libc::F_SETFL, libc::O_NONBLOCK))
+                .and_then(|_| syscall!(fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC)))
+            {
+                // Ensure both ends are closed and return the error.
+                let _ = unsafe { (libc::close(fds[0]), libc::close(fds[1])) };
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(unsafe { (File::from_raw_fd(fds[1]), File::from_raw_fd(fds[0])) })
+}
+
+#[cfg_attr(not(feature = "os-util"), allow(dead_code))]
+pub fn set_nonblocking(fd: RawFd, nonblocking: bool) -> io::Result<()> {
+    let flags = syscall!(fcntl(fd, libc::F_GETFL))?;
+    let new_flags = if nonblocking {
+        flags | libc::O_NONBLOCK
+    } else {
+        flags & !libc::O_NONBLOCK
+    };
+    if flags != new_flags {
+        syscall!(fcntl(fd, libc::F_SETFL, new_flags))?;
+    }
+    Ok(())
+}
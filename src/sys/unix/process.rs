@@ -0,0 +1,91 @@
+use crate::sys::signals::pipe;
+
+#[cfg(target_os = "linux")]
+use std::fs::File;
+use std::io;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::FromRawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::process::Child;
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// `pidfd_open(2)` was added in Linux 5.3, this is set to false once we find
+/// out it's not available.
+///
+/// Android's seccomp filter kills the process for unknown system calls, so
+/// we don't try it there.
+#[cfg(target_os = "linux")]
+static HAS_PIDFD: AtomicBool = AtomicBool::new(true);
+
+/// Readiness for the exit of a child process.
+#[derive(Debug)]
+pub enum Exit {
+    /// A `pidfd`, which becomes readable once the process exits.
+    #[cfg(target_os = "linux")]
+    Pidfd(File),
+    /// `SIGCHLD`, which is delivered for every child process, so this is
+    /// readable whenever any child process exits (or stops).
+    Signal(pipe::Signals),
+}
+
+impl Exit {
+    pub fn new(child: &mut Child) -> io::Result<Exit> {
+        #[cfg(target_os = "linux")]
+        {
+            if HAS_PIDFD.load(Ordering::Relaxed) {
+                let pid = child.id() as libc::pid_t;
+                match syscall!(syscall(libc::SYS_pidfd_open, pid, 0)) {
+                    // `pidfd_open` always sets `O_CLOEXEC`.
+                    Ok(fd) => return Ok(Exit::Pidfd(unsafe { File::from_raw_fd(fd as RawFd) })),
+                    // Some seccomp filters return `EPERM` for unknown system
+                    // calls.
+                    Err(ref err)
+                        if err.raw_os_error() == Some(libc::ENOSYS)
+                            || err.raw_os_error() == Some(libc::EPERM) =>
+                    {
+                        HAS_PIDFD.store(false, Ordering::Relaxed);
+                    }
+                    // The process has already been reaped, the fallback below
+                    // handles that.
+                    Err(ref err) if err.raw_os_error() == Some(libc::ESRCH) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        let signals = pipe::Signals::new(&[libc::SIGCHLD])?;
+        // The process could have exited before we installed the signal
+        // handler, in which case we'll never receive the signal.
+        if child.try_wait()?.is_some() {
+            signals.notify(libc::SIGCHLD)?;
+        }
+        Ok(Exit::Signal(signals))
+    }
+
+    /// Reset the readiness, before checking if the process has exited.
+    pub fn reset(&self) -> io::Result<()> {
+        match self {
+            // Remains readable after the process exits.
+            #[cfg(target_os = "linux")]
+            Exit::Pidfd(_) => Ok(()),
+            Exit::Signal(signals) => loop {
+                match signals.receive() {
+                    Ok(_) => continue,
+                    Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                    Err(err) => return Err(err),
+                }
+            },
+        }
+    }
+}
+
+impl AsRawFd for Exit {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            #[cfg(target_os = "linux")]
+            Exit::Pidfd(fd) => fd.as_raw_fd(),
+            Exit::Signal(signals) => signals.as_raw_fd(),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::ffi::{CStr, OsStr};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use super::pipe::set_nonblocking;
+
+/// Maximum length of the path of a slave device, including the null byte.
+const PATH_MAX: usize = 128;
+
+/// Create a new pseudo-terminal, returning the master and slave sides.
+///
+/// The master side is non-blocking, the slave side is blocking and not made
+/// the controlling terminal of the calling process.
+pub fn new() -> io::Result<(File, File)> {
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    let master = syscall!(posix_openpt(
+        libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC
+    ))
+    .map(|fd| unsafe { File::from_raw_fd(fd) })?;
+
+    // Not all platforms support `O_CLOEXEC` in `posix_openpt`, so we need to
+    // set the flag ourselves.
+    #[cfg(not(any(target_os = "android", target_os = "freebsd", target_os = "linux")))]
+    let master = syscall!(posix_openpt(libc::O_RDWR | libc::O_NOCTTY))
+        .map(|fd| unsafe { File::from_raw_fd(fd) })
+        .and_then(|master| {
+            syscall!(fcntl(master.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC)).map(|_| master)
+        })?;
+
+    let fd = master.as_raw_fd();
+    syscall!(grantpt(fd))?;
+    syscall!(unlockpt(fd))?;
+
+    let mut path = [0; PATH_MAX];
+    slave_path(fd, &mut path)?;
+    let path = unsafe { CStr::from_ptr(path.as_ptr()) };
+    // `OpenOptions` sets `O_CLOEXEC` for us.
+    let slave = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(OsStr::from_bytes(path.to_bytes()))?;
+
+    set_nonblocking(fd, true)?;
+    Ok((master, slave))
+}
+
+/// Write the null terminated path of the slave device of `master` into `buf`.
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd"
+))]
+fn slave_path(master: RawFd, buf: &mut [c_char; PATH_MAX]) -> io::Result<()> {
+    // Unlike most functions `ptsname_r` returns the error, rather than
+    // setting `errno`.
+    match unsafe { libc::ptsname_r(master, buf.as_mut_ptr(), buf.len()) } {
+        0 => Ok(()),
+        err => Err(io::Error::from_raw_os_error(err)),
+    }
+}
+
+/// Write the null terminated path of the slave device of `master` into `buf`.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn slave_path(master: RawFd, buf: &mut [c_char; PATH_MAX]) -> io::Result<()> {
+    // Darwin doesn't have `ptsname_r`, but `ptsname` uses this `ioctl` with a
+    // shared buffer.
+    syscall!(ioctl(master, libc::TIOCPTYGNAME as _, buf.as_mut_ptr())).map(|_| ())
+}
+
+/// Write the null terminated path of the slave device of `master` into `buf`.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd"
+)))]
+fn slave_path(master: RawFd, buf: &mut [c_char; PATH_MAX]) -> io::Result<()> {
+    // NOTE: `ptsname` isn't thread-safe, but it's the only option here.
+    let path = unsafe { libc::ptsname(master) };
+    if path.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    let path = unsafe { CStr::from_ptr(path) }.to_bytes_with_nul();
+    if path.len() > buf.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "pseudo-terminal path too long",
+        ));
+    }
+    for (dst, src) in buf.iter_mut().zip(path) {
+        *dst = *src as c_char;
+    }
+    Ok(())
+}
+
+pub fn window_size(fd: RawFd) -> io::Result<libc::winsize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    syscall!(ioctl(fd, libc::TIOCGWINSZ as _, &mut size)).map(|_| size)
+}
+
+pub fn set_window_size(fd: RawFd, size: &libc::winsize) -> io::Result<()> {
+    syscall!(ioctl(fd, libc::TIOCSWINSZ as _, size)).map(|_| ())
+}
+
+/// Start a new session and make `fd` its controlling terminal.
+///
+/// Only meant to be called in a child process after forking, between `fork`
+/// and `exec`. Calling it again in the same process does nothing, which is
+/// needed as a `Command` keeps the `pre_exec` hooks of every `Slave::spawn`.
+pub fn set_controlling_terminal(fd: RawFd) -> io::Result<()> {
+    // `setsid` fails if the process already leads its own session.
+    if syscall!(getsid(0))? != unsafe { libc::getpid() } {
+        syscall!(setsid())?;
+    }
+    // This succeeds if `fd` is the controlling terminal already.
+    syscall!(ioctl(fd, libc::TIOCSCTTY as _, 0)).map(|_| ())
+}
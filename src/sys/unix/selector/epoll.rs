@@ -1,22 +1,67 @@
 use crate::{Interest, Token};
 
-use libc::{EPOLLET, EPOLLIN, EPOLLOUT, EPOLLRDHUP};
+use libc::{EPOLLET, EPOLLIN, EPOLLONESHOT, EPOLLOUT, EPOLLPRI, EPOLLRDHUP};
 use log::error;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::fs;
 use std::os::unix::io::{AsRawFd, RawFd};
-#[cfg(debug_assertions)]
-use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(all(
+    target_os = "linux",
+    not(any(target_arch = "mips", target_arch = "mips64"))
+))]
+use std::sync::atomic::AtomicBool;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::sync::atomic::AtomicI32;
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
+use std::sync::atomic::AtomicUsize;
+#[cfg(any(
+    debug_assertions,
+    feature = "registration-checks",
+    target_os = "android",
+    target_os = "linux"
+))]
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::{cmp, i32, io, ptr};
 
 /// Unique id for use as `SelectorId`.
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
 
+/// `epoll_pwait2(2)` was added in Linux 5.11, this is set to false once we
+/// find out it's not available. The number of the system call is the same
+/// on all architectures, except MIPS, but it isn't defined by libc.
+///
+/// Android's seccomp filter kills the process for unknown system calls, so
+/// we don't try it there.
+#[cfg(all(
+    target_os = "linux",
+    not(any(target_arch = "mips", target_arch = "mips64"))
+))]
+static HAS_EPOLL_PWAIT2: AtomicBool = AtomicBool::new(true);
+
+#[cfg(all(
+    target_os = "linux",
+    not(any(target_arch = "mips", target_arch = "mips64"))
+))]
+const SYS_EPOLL_PWAIT2: libc::c_long = 441;
+
+/// Data of the timer registration, see `Selector::timer`. This is the
+/// reserved `Token(usize::MAX)`, which users can't register.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TIMER: u64 = usize::max_value() as u64;
+
+const NANOS_PER_MILLI: u32 = 1_000_000;
+
 #[derive(Debug)]
 pub struct Selector {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
     id: usize,
     ep: RawFd,
+    /// `timerfd` used for timeouts with sub-millisecond precision if
+    /// `epoll_pwait2(2)` is not available, or -1 if not yet created.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    timer: AtomicI32,
 }
 
 impl Selector {
@@ -25,27 +70,65 @@ impl Selector {
         // 21. But `EPOLL_CLOEXEC` is an alias for `O_CLOEXEC` on all platforms,
         // so we use that instead.
         syscall!(epoll_create1(libc::O_CLOEXEC)).map(|ep| Selector {
-            #[cfg(debug_assertions)]
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
             id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             ep,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            timer: AtomicI32::new(-1),
         })
     }
 
     pub fn try_clone(&self) -> io::Result<Selector> {
         syscall!(dup(self.ep)).map(|ep| Selector {
             // It's the same selector, so we use the same id.
-            #[cfg(debug_assertions)]
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
             id: self.id,
             ep,
+            // Only the original selector is used to poll.
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            timer: AtomicI32::new(-1),
         })
     }
 
     pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
-        let timeout = timeout
-            .map(|to| cmp::min(to.as_millis(), libc::c_int::max_value() as u128) as libc::c_int)
-            .unwrap_or(-1);
-
         events.clear();
+
+        #[cfg(all(
+            target_os = "linux",
+            not(any(target_arch = "mips", target_arch = "mips64"))
+        ))]
+        {
+            if HAS_EPOLL_PWAIT2.load(Ordering::Relaxed) {
+                match self.epoll_pwait2(events, timeout) {
+                    // Some seccomp filters return `EPERM` for unknown system
+                    // calls.
+                    Err(ref err)
+                        if err.raw_os_error() == Some(libc::ENOSYS)
+                            || err.raw_os_error() == Some(libc::EPERM) =>
+                    {
+                        HAS_EPOLL_PWAIT2.store(false, Ordering::Relaxed);
+                    }
+                    res => return res,
+                }
+            }
+        }
+
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if let Some(to) = timeout {
+                if to.subsec_nanos() % NANOS_PER_MILLI != 0 {
+                    // If we can't create a timer we'll round the timeout up.
+                    if let Ok(timer) = self.timer() {
+                        return self.select_with_timer(events, timer, to);
+                    }
+                }
+            }
+        }
+
+        self.epoll_wait(events, timeout.map(millis_rounded_up).unwrap_or(-1))
+    }
+
+    fn epoll_wait(&self, events: &mut Events, timeout: libc::c_int) -> io::Result<()> {
         syscall!(epoll_wait(
             self.ep,
             events.as_mut_ptr(),
@@ -59,7 +142,115 @@ impl Selector {
         })
     }
 
+    #[cfg(all(
+        target_os = "linux",
+        not(any(target_arch = "mips", target_arch = "mips64"))
+    ))]
+    fn epoll_pwait2(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        /// `struct __kernel_timespec`, which always uses 64 bit fields.
+        #[repr(C)]
+        struct KernelTimespec {
+            tv_sec: i64,
+            tv_nsec: i64,
+        }
+
+        let timeout = timeout.map(|to| KernelTimespec {
+            tv_sec: cmp::min(to.as_secs(), i64::max_value() as u64) as i64,
+            tv_nsec: i64::from(to.subsec_nanos()),
+        });
+        let timeout = timeout
+            .as_ref()
+            .map_or(ptr::null(), |to| to as *const KernelTimespec);
+        syscall!(syscall(
+            SYS_EPOLL_PWAIT2,
+            self.ep,
+            events.as_mut_ptr(),
+            events.capacity() as libc::c_int,
+            timeout,
+            ptr::null::<libc::sigset_t>(),
+            0 as libc::size_t,
+        ))
+        .map(|n_events| {
+            // This is safe because `epoll_pwait2` ensures that `n_events` are
+            // assigned.
+            unsafe { events.set_len(n_events as usize) };
+        })
+    }
+
+    /// Returns the `timerfd`, creating and registering it if needed.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    fn timer(&self) -> io::Result<RawFd> {
+        let timer = self.timer.load(Ordering::Relaxed);
+        if timer != -1 {
+            return Ok(timer);
+        }
+
+        let timer = syscall!(timerfd_create(
+            libc::CLOCK_MONOTONIC,
+            libc::TFD_CLOEXEC | libc::TFD_NONBLOCK
+        ))?;
+        let mut event = libc::epoll_event {
+            events: (EPOLLIN | EPOLLET) as u32,
+            u64: TIMER,
+        };
+        if let Err(err) = syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_ADD, timer, &mut event)) {
+            let _ = syscall!(close(timer));
+            return Err(err);
+        }
+        // Only the thread polling calls this, so no need to compare.
+        self.timer.store(timer, Ordering::Relaxed);
+        Ok(timer)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    fn select_with_timer(
+        &self,
+        events: &mut Events,
+        timer: RawFd,
+        timeout: Duration,
+    ) -> io::Result<()> {
+        let mut value = libc::itimerspec {
+            it_interval: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+            it_value: libc::timespec {
+                tv_sec: cmp::min(timeout.as_secs(), libc::time_t::max_value() as u64)
+                    as libc::time_t,
+                // The cast is needed for platforms where C's long is only 32
+                // bits.
+                tv_nsec: libc::c_long::from(timeout.subsec_nanos() as i32),
+            },
+        };
+        syscall!(timerfd_settime(timer, 0, &value, ptr::null_mut()))?;
+
+        let res = self.epoll_wait(events, -1);
+
+        // Disarming the timer also resets its readiness, so we won't get a
+        // stale event on the next call.
+        let disarm = unsafe { std::mem::zeroed() };
+        syscall!(timerfd_settime(timer, 0, &disarm, &mut value))?;
+        res?;
+
+        let expired = value.it_value.tv_sec == 0 && value.it_value.tv_nsec == 0;
+        if expired {
+            // Remove the timer's event.
+            let timer_event = events.iter().position(|event| event.u64 == TIMER);
+            if let Some(index) = timer_event {
+                let _ = events.remove(index);
+            }
+        }
+        Ok(())
+    }
+
     pub fn register(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        if interests.is_exclusive() && (interests.is_oneshot() || interests.is_priority()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "exclusive registrations can't be combined with one-shot or priority interests",
+            ));
+        }
+
         let mut event = libc::epoll_event {
             events: interests_to_epoll(interests),
             u64: usize::from(token) as u64,
@@ -69,6 +260,13 @@ impl Selector {
     }
 
     pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        if interests.is_exclusive() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "exclusive registrations can't be reregistered",
+            ));
+        }
+
         let mut event = libc::epoll_event {
             events: interests_to_epoll(interests),
             u64: usize::from(token) as u64,
@@ -80,11 +278,55 @@ impl Selector {
     pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
         syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_DEL, fd, ptr::null_mut())).map(|_| ())
     }
+
+    /// Returns the file descriptor, events and data of all registrations, as
+    /// reported by the kernel in `/proc/self/fdinfo`. The registration of the
+    /// timer is not included.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn inspect(&self) -> io::Result<Vec<(RawFd, u32, u64)>> {
+        let path = format!("/proc/self/fdinfo/{}", self.ep);
+        let fdinfo = fs::read_to_string(path)?;
+        let mut registrations = Vec::new();
+        for line in fdinfo.lines().filter(|line| line.starts_with("tfd:")) {
+            let registration = parse_fdinfo_line(line).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "invalid epoll fdinfo")
+            })?;
+            if registration.2 != TIMER {
+                registrations.push(registration);
+            }
+        }
+        Ok(registrations)
+    }
+}
+
+/// Parses a line of an epoll fdinfo, which looks like the following.
+///
+/// ```text
+/// tfd:        5 events:       19 data:                0  pos:0 ino:61 sdev:7
+/// ```
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn parse_fdinfo_line(line: &str) -> Option<(RawFd, u32, u64)> {
+    let mut fd = None;
+    let mut events = None;
+    let mut data = None;
+    let mut fields = line.split_whitespace();
+    while let Some(key) = fields.next() {
+        match key {
+            "tfd:" => fd = fields.next().and_then(|v| v.parse().ok()),
+            "events:" => events = fields.next().and_then(|v| u32::from_str_radix(v, 16).ok()),
+            "data:" => data = fields.next().and_then(|v| u64::from_str_radix(v, 16).ok()),
+            _ => {}
+        }
+    }
+    match (fd, events, data) {
+        (Some(fd), Some(events), Some(data)) => Some((fd, events, data)),
+        _ => None,
+    }
 }
 
-cfg_net! {
+cfg_io_source! {
     impl Selector {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         pub fn id(&self) -> usize {
             self.id
         }
@@ -102,20 +344,59 @@ impl Drop for Selector {
         if let Err(err) = syscall!(close(self.ep)) {
             error!("error closing epoll: {}", err);
         }
+
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            let timer = self.timer.load(Ordering::Relaxed);
+            if timer != -1 {
+                if let Err(err) = syscall!(close(timer)) {
+                    error!("error closing timerfd: {}", err);
+                }
+            }
+        }
     }
 }
 
+/// Converts `timeout` into milliseconds, rounding up so that we never return
+/// before the timeout elapsed.
+fn millis_rounded_up(timeout: Duration) -> libc::c_int {
+    let mut millis = timeout.as_millis();
+    if timeout.subsec_nanos() % NANOS_PER_MILLI != 0 {
+        millis += 1;
+    }
+    cmp::min(millis, libc::c_int::max_value() as u128) as libc::c_int
+}
+
 fn interests_to_epoll(interests: Interest) -> u32 {
-    let mut kind = EPOLLET;
+    let mut kind = if interests.is_level() { 0 } else { EPOLLET };
 
     if interests.is_readable() {
-        kind = kind | EPOLLIN | EPOLLRDHUP;
+        kind |= EPOLLIN;
+        // `EPOLLRDHUP` is not allowed in combination with `EPOLLEXCLUSIVE`.
+        if !interests.is_exclusive() {
+            kind |= EPOLLRDHUP;
+        }
     }
 
     if interests.is_writable() {
         kind |= EPOLLOUT;
     }
 
+    if interests.is_priority() {
+        kind |= EPOLLPRI;
+    }
+
+    if interests.is_oneshot() {
+        kind |= EPOLLONESHOT;
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        if interests.is_exclusive() {
+            kind |= libc::EPOLLEXCLUSIVE;
+        }
+    }
+
     kind as u32
 }
 
@@ -125,7 +406,7 @@ pub type Events = Vec<Event>;
 pub mod event {
     use std::fmt;
 
-    use crate::sys::Event;
+    use super::Event;
     use crate::Token;
 
     pub fn token(event: &Event) -> Token {
@@ -175,6 +456,8 @@ pub mod event {
         false
     }
 
+    // The mock events only keep the readiness.
+    #[cfg_attr(feature = "mock", allow(dead_code))]
     pub fn debug_details(f: &mut fmt::Formatter<'_>, event: &Event) -> fmt::Result {
         #[allow(clippy::trivially_copy_pass_by_ref)]
         fn check_events(got: &u32, want: &libc::c_int) -> bool {
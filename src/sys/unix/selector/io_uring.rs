@@ -0,0 +1,839 @@
+//! Selector backed by io_uring's poll operations.
+//!
+//! Every registration is turned into an `IORING_OP_POLL_ADD` request on a
+//! single ring that is shared between the `Poll` and all its `Registry`s.
+//! Requests are only pushed onto the submission queue, they are submitted
+//! along with the next call to `select`. Only if a thread is currently blocked
+//! in `select`, on deregistering or if the submission queue is full are they
+//! submitted right away.
+//!
+//! Edge-triggered registrations use multishot poll requests, which stay armed
+//! after posting a completion. Level-triggered registrations use single-shot
+//! requests that are rearmed at the start of the next call to `select`, once
+//! the event was returned to the user. One-shot registrations use single-shot
+//! requests that are only rearmed by `reregister`.
+//!
+//! The completion of a poll request has the same layout as an `epoll_event`
+//! (the `POLL*` and `EPOLL*` constants share their values), so we reuse the
+//! event types of the epoll selector.
+
+use crate::{Interest, Token};
+
+use libc::{EPOLLEXCLUSIVE, EPOLLIN, EPOLLOUT, EPOLLPRI, EPOLLRDBAND, EPOLLRDHUP};
+use log::{error, trace};
+use std::collections::{HashMap, VecDeque};
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use std::{fmt, io, mem, ptr};
+
+use super::epoll::{Event, Events};
+
+/// Unique id for use as `SelectorId`.
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Number of entries in the submission queue, the completion queue is twice
+/// as large.
+const ENTRIES: u32 = 256;
+
+/// Bit set in the `user_data` of `IORING_OP_POLL_REMOVE` requests, the other
+/// bits are the `user_data` of the poll request to remove.
+const REMOVE: u64 = 1 << 63;
+
+#[derive(Debug)]
+pub struct Selector {
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
+    id: usize,
+    ring: Arc<Ring>,
+}
+
+impl Selector {
+    pub fn new() -> io::Result<Selector> {
+        Ring::new().map(|ring| Selector {
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            ring: Arc::new(ring),
+        })
+    }
+
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        Ok(Selector {
+            // It's the same selector, so we use the same id.
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
+            id: self.id,
+            ring: self.ring.clone(),
+        })
+    }
+
+    pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        events.clear();
+        let deadline = timeout.map(|to| Instant::now() + to);
+        loop {
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let timed_out = self.ring.wait(timeout)?;
+            self.ring.state().reap(events);
+
+            // Completions of removed requests don't result in events, so we
+            // could end up here without any. Rather than returning early we
+            // wait again for the remainder of the timeout.
+            if !events.is_empty() || timed_out || timeout == Some(Duration::from_secs(0)) {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn register(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        if interests.is_exclusive() && (interests.is_oneshot() || interests.is_priority()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "exclusive registrations can't be combined with one-shot or priority interests",
+            ));
+        }
+
+        let mut state = self.ring.state();
+        if state.registrations.contains_key(&fd) {
+            // Same error as `epoll_ctl(2)` returns.
+            return Err(io::Error::from_raw_os_error(libc::EEXIST));
+        }
+        state.registrations.insert(
+            fd,
+            Registration {
+                token,
+                interests,
+                request: None,
+                reported: None,
+            },
+        );
+        state.arm(fd)?;
+        self.ring.submit_if_polling(&mut state)
+    }
+
+    pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        if interests.is_exclusive() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "exclusive registrations can't be reregistered",
+            ));
+        }
+
+        let mut state = self.ring.state();
+        if let Some(request) = state.disarm(fd)? {
+            state.push_remove(request)?;
+        }
+        let registration = state.registration(fd)?;
+        registration.token = token;
+        registration.interests = interests;
+        state.arm(fd)?;
+        self.ring.submit_if_polling(&mut state)
+    }
+
+    pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        let mut state = self.ring.state();
+        let request = state.disarm(fd)?;
+        let _ = state.registrations.remove(&fd);
+        match request {
+            // The poll request holds a reference to the file, which means
+            // that closing `fd` wouldn't actually close the file until the
+            // request is removed. So we submit the removal right away, but
+            // don't wait for it to complete as that would block `select`.
+            Some(request) => {
+                state.push_remove(request)?;
+                state.submit()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Inspecting the registrations is only supported by epoll.
+    pub fn inspect(&self) -> io::Result<Vec<(RawFd, u32, u64)>> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "inspecting registrations is only supported by the epoll selector",
+        ))
+    }
+}
+
+cfg_io_source! {
+    impl Selector {
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
+        pub fn id(&self) -> usize {
+            self.id
+        }
+    }
+}
+
+impl AsRawFd for Selector {
+    fn as_raw_fd(&self) -> RawFd {
+        self.ring.fd
+    }
+}
+
+/// The io_uring instance, shared between a selector and its clones.
+struct Ring {
+    fd: RawFd,
+    /// Mapping of the submission and completion queue rings, the queues in
+    /// `state` point into it.
+    _rings: Mmap,
+    /// Mapping of the submission queue entries.
+    _sqes: Mmap,
+    state: Mutex<State>,
+    /// True if a thread is blocked in `select`.
+    polling: AtomicBool,
+}
+
+impl Ring {
+    fn new() -> io::Result<Ring> {
+        let mut params: Params = unsafe { mem::zeroed() };
+        let fd = syscall!(syscall(
+            libc::SYS_io_uring_setup,
+            ENTRIES,
+            &mut params as *mut Params
+        ))? as RawFd;
+        // Make sure the ring is closed if any of the below fails.
+        let ring_fd = RingFd(fd);
+
+        if params.features & IORING_FEAT_SINGLE_MMAP == 0
+            || params.features & IORING_FEAT_EXT_ARG == 0
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "io_uring selector requires Linux 5.13 or later",
+            ));
+        }
+
+        let sq_len = params.sq_off.array as usize + params.sq_entries as usize * 4;
+        let cq_len =
+            params.cq_off.cqes as usize + params.cq_entries as usize * mem::size_of::<Cqe>();
+        let rings = Mmap::new(fd, sq_len.max(cq_len), IORING_OFF_SQ_RING)?;
+        let sqes = Mmap::new(
+            fd,
+            params.sq_entries as usize * mem::size_of::<Sqe>(),
+            IORING_OFF_SQES,
+        )?;
+
+        let sq = unsafe {
+            SubmissionQueue {
+                head: rings.at(params.sq_off.head),
+                tail: rings.at(params.sq_off.tail),
+                mask: *rings.at::<u32>(params.sq_off.ring_mask),
+                entries: *rings.at::<u32>(params.sq_off.ring_entries),
+                sqes: sqes.at(0),
+            }
+        };
+        // We always fill the submission queue entries in order, so the
+        // indirection array is a fixed identity mapping.
+        let array: *mut u32 = rings.at(params.sq_off.array);
+        for i in 0..sq.entries {
+            unsafe { ptr::write(array.add(i as usize), i) };
+        }
+        let cq = unsafe {
+            CompletionQueue {
+                head: rings.at(params.cq_off.head),
+                tail: rings.at(params.cq_off.tail),
+                mask: *rings.at::<u32>(params.cq_off.ring_mask),
+                cqes: rings.at(params.cq_off.cqes),
+            }
+        };
+
+        mem::forget(ring_fd);
+        Ok(Ring {
+            fd,
+            _rings: rings,
+            _sqes: sqes,
+            state: Mutex::new(State {
+                fd,
+                sq,
+                cq,
+                registrations: HashMap::new(),
+                requests: HashMap::new(),
+                next_request: 1,
+                rearm: Vec::new(),
+                backlog: VecDeque::new(),
+                reaped: 0,
+            }),
+            polling: AtomicBool::new(false),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Submit all queued requests and wait for at least one completion, or
+    /// until `timeout` elapses. Returns true if the timeout elapsed.
+    fn wait(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        let (to_submit, backlog) = {
+            let mut state = self.state();
+            state.rearm()?;
+            self.polling.store(true, Ordering::Release);
+            (state.sq.pending(), !state.backlog.is_empty())
+        };
+
+        let ts = timeout.map(|to| KernelTimespec {
+            tv_sec: to.as_secs() as i64,
+            tv_nsec: to.subsec_nanos() as i64,
+        });
+        let arg = GetEventsArg {
+            sigmask: 0,
+            sigmask_sz: 0,
+            pad: 0,
+            ts: ts
+                .as_ref()
+                .map_or(0, |ts| ts as *const KernelTimespec as u64),
+        };
+        let min_complete = if backlog || timeout == Some(Duration::from_secs(0)) {
+            0
+        } else {
+            1
+        };
+        let res = enter(
+            self.fd,
+            to_submit,
+            min_complete,
+            IORING_ENTER_GETEVENTS | IORING_ENTER_EXT_ARG,
+            &arg,
+        );
+        self.polling.store(false, Ordering::Release);
+        match res {
+            Ok(_) => Ok(false),
+            Err(ref err) if err.raw_os_error() == Some(libc::ETIME) => Ok(true),
+            // Completion queue overflowed, we need to reap some completions
+            // before the kernel accepts new submissions.
+            Err(ref err) if err.raw_os_error() == Some(libc::EBUSY) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Submit all queued requests if a thread is blocked in `select`, as it
+    /// would otherwise miss events for the new requests.
+    fn submit_if_polling(&self, state: &mut State) -> io::Result<()> {
+        if self.polling.load(Ordering::Acquire) {
+            state.submit()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Debug for Ring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ring").field("fd", &self.fd).finish()
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        // Closing the ring cancels all requests still in flight. The mappings
+        // are unmapped once the `Mmap`s are dropped.
+        if let Err(err) = syscall!(close(self.fd)) {
+            error!("error closing io_uring: {}", err);
+        }
+    }
+}
+
+/// Closes the ring's file descriptor if `Ring::new` fails.
+struct RingFd(RawFd);
+
+impl Drop for RingFd {
+    fn drop(&mut self) {
+        let _ = unsafe { libc::close(self.0) };
+    }
+}
+
+/// User space state of the ring, protected by the mutex in `Ring`.
+struct State {
+    /// File descriptor of the ring, owned by `Ring`.
+    fd: RawFd,
+    sq: SubmissionQueue,
+    cq: CompletionQueue,
+    registrations: HashMap<RawFd, Registration>,
+    /// Armed poll requests, mapping `user_data` to the registered fd.
+    requests: HashMap<u64, RawFd>,
+    next_request: u64,
+    /// Registrations that need to be rearmed in the next call to `select`.
+    rearm: Vec<RawFd>,
+    /// Completions popped to make room in the completion queue while
+    /// submitting requests.
+    backlog: VecDeque<Cqe>,
+    /// Number of calls to `reap`, used to coalesce completions for the same
+    /// registration into a single event.
+    reaped: u64,
+}
+
+// The raw pointers in the queues point into the mappings owned by `Ring`,
+// access to them is synchronised by the mutex around `State`.
+unsafe impl Send for State {}
+
+#[derive(Debug)]
+struct Registration {
+    token: Token,
+    interests: Interest,
+    /// `user_data` of the armed poll request, if any.
+    request: Option<u64>,
+    /// Call to `reap` and index of the event this registration was last
+    /// reported in.
+    reported: Option<(u64, usize)>,
+}
+
+impl State {
+    fn registration(&mut self, fd: RawFd) -> io::Result<&mut Registration> {
+        self.registrations
+            .get_mut(&fd)
+            // Same error as `epoll_ctl(2)` returns.
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))
+    }
+
+    /// Queue a poll request for the registration of `fd`.
+    fn arm(&mut self, fd: RawFd) -> io::Result<()> {
+        let user_data = self.next_request;
+        let registration = self.registration(fd)?;
+        let interests = registration.interests;
+        let multishot = !interests.is_level() && !interests.is_oneshot();
+        let sqe = Sqe {
+            opcode: IORING_OP_POLL_ADD,
+            fd,
+            op_flags: interests_to_poll(interests),
+            len: if multishot { IORING_POLL_ADD_MULTI } else { 0 },
+            user_data,
+            ..Sqe::default()
+        };
+        registration.request = Some(user_data);
+        self.next_request += 1;
+        let _ = self.requests.insert(user_data, fd);
+        self.push(sqe)
+    }
+
+    /// Disarm the registration of `fd`, returning the poll request that needs
+    /// to be removed, if any. Any completions for the request are ignored
+    /// after this.
+    fn disarm(&mut self, fd: RawFd) -> io::Result<Option<u64>> {
+        let request = self.registration(fd)?.request.take();
+        if let Some(request) = request {
+            let _ = self.requests.remove(&request);
+        }
+        Ok(request)
+    }
+
+    /// Queue the removal of the poll `request`.
+    fn push_remove(&mut self, request: u64) -> io::Result<()> {
+        self.push(Sqe {
+            opcode: IORING_OP_POLL_REMOVE,
+            fd: -1,
+            addr: request,
+            user_data: REMOVE | request,
+            ..Sqe::default()
+        })
+    }
+
+    /// Rearm all registrations that have been reported and need rearming.
+    fn rearm(&mut self) -> io::Result<()> {
+        for fd in mem::replace(&mut self.rearm, Vec::new()) {
+            match self.registrations.get(&fd) {
+                // Registration could be deregistered or reregistered in the
+                // meantime.
+                Some(registration) if registration.request.is_none() => self.arm(fd)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue `sqe`, submitting all queued requests first if the submission
+    /// queue is full.
+    fn push(&mut self, sqe: Sqe) -> io::Result<()> {
+        if self.sq.pending() == self.sq.entries {
+            self.submit()?;
+        }
+        if self.sq.push(sqe) {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "io_uring submission queue is full",
+            ))
+        }
+    }
+
+    /// Submit all queued requests.
+    fn submit(&mut self) -> io::Result<()> {
+        loop {
+            let to_submit = self.sq.pending();
+            if to_submit == 0 {
+                return Ok(());
+            }
+            match enter(self.fd, to_submit, 0, 0, ptr::null()) {
+                Ok(_) => return Ok(()),
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                // Completion queue overflowed, the kernel only accepts new
+                // submissions once we've made room. The popped completions
+                // are processed in the next call to `select`.
+                Err(ref err) if err.raw_os_error() == Some(libc::EBUSY) => {
+                    let backlog = self.backlog.len();
+                    while let Some(cqe) = self.cq.pop() {
+                        self.backlog.push_back(cqe);
+                    }
+                    if self.backlog.len() == backlog {
+                        return Err(io::Error::from_raw_os_error(libc::EBUSY));
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Move completions into `events`.
+    fn reap(&mut self, events: &mut Events) {
+        self.reaped += 1;
+        while events.len() < events.capacity() {
+            let cqe = match self.backlog.pop_front().or_else(|| self.cq.pop()) {
+                Some(cqe) => cqe,
+                None => break,
+            };
+            if cqe.user_data & REMOVE != 0 {
+                // The request couldn't be removed as it's being completed,
+                // e.g. when it was ready at the time it was submitted. If
+                // this fails we'll find out the next time around.
+                if cqe.res == -libc::EALREADY {
+                    let _ = self.push_remove(cqe.user_data & !REMOVE);
+                }
+                continue;
+            }
+            let fd = match self.requests.get(&cqe.user_data) {
+                Some(fd) => *fd,
+                // Poll request was disarmed.
+                None => continue,
+            };
+            let more = cqe.flags & IORING_CQE_F_MORE != 0;
+            if !more {
+                let _ = self.requests.remove(&cqe.user_data);
+            }
+            let registration = match self.registrations.get_mut(&fd) {
+                Some(registration) => registration,
+                None => continue,
+            };
+            let mut readiness = if cqe.res < 0 {
+                trace!(
+                    "poll request for fd={} failed: {}",
+                    fd,
+                    io::Error::from_raw_os_error(-cqe.res)
+                );
+                libc::EPOLLERR as u32
+            } else {
+                cqe.res as u32
+            };
+            // See `interests_to_poll`.
+            readiness &= !(EPOLLRDBAND as u32);
+            // The readiness is based on the wake up, which for sockets
+            // includes `EPOLLPRI` for all incoming data.
+            if readiness & EPOLLPRI as u32 != 0 && !has_priority_data(fd) {
+                readiness &= !(EPOLLPRI as u32);
+            }
+
+            if !more {
+                registration.request = None;
+                // Level-triggered registrations are rearmed once the event
+                // is returned, edge-triggered registrations only end up
+                // here if the kernel ended the multishot request, e.g. when
+                // the completion queue overflowed. One-shot registrations
+                // are only rearmed if nothing is reported.
+                if cqe.res >= 0 && (!registration.interests.is_oneshot() || readiness == 0) {
+                    self.rearm.push(fd);
+                }
+            }
+
+            if readiness == 0 {
+                continue;
+            }
+            match registration.reported {
+                Some((reaped, index)) if reaped == self.reaped => {
+                    events[index].events |= readiness;
+                }
+                _ => {
+                    registration.reported = Some((self.reaped, events.len()));
+                    events.push(Event {
+                        events: readiness,
+                        u64: usize::from(registration.token) as u64,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Edge- or level-triggered and one-shot modes are not part of the poll mask,
+/// they're determined by rearming the request (or not), see `State::arm`.
+fn interests_to_poll(interests: Interest) -> u32 {
+    let mut kind = 0;
+
+    if interests.is_readable() {
+        kind |= EPOLLIN;
+        // `EPOLLRDHUP` is not allowed in combination with `EPOLLEXCLUSIVE`.
+        if !interests.is_exclusive() {
+            kind |= EPOLLRDHUP;
+        }
+    }
+
+    if interests.is_writable() {
+        kind |= EPOLLOUT;
+    }
+
+    if interests.is_priority() {
+        // io_uring ignores wake ups for poll requests that are only
+        // interested in `EPOLLPRI`, so we also poll for `EPOLLRDBAND` (which
+        // is included in the wake ups of sockets) and filter it out when
+        // reaping the completion.
+        kind |= EPOLLPRI | EPOLLRDBAND;
+    }
+
+    if interests.is_exclusive() {
+        kind |= EPOLLEXCLUSIVE;
+    }
+
+    kind as u32
+}
+
+/// Returns true if `fd` has priority data, false if it hasn't.
+fn has_priority_data(fd: RawFd) -> bool {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLPRI,
+        revents: 0,
+    };
+    match syscall!(poll(&mut pollfd, 1, 0)) {
+        Ok(_) => pollfd.revents & libc::POLLPRI != 0,
+        // Rather report a spurious event than miss one.
+        Err(_) => true,
+    }
+}
+
+fn enter(
+    fd: RawFd,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    arg: *const GetEventsArg,
+) -> io::Result<libc::c_long> {
+    syscall!(syscall(
+        libc::SYS_io_uring_enter,
+        fd,
+        to_submit,
+        min_complete,
+        flags,
+        arg,
+        mem::size_of::<GetEventsArg>(),
+    ))
+}
+
+struct SubmissionQueue {
+    head: *const AtomicU32,
+    tail: *const AtomicU32,
+    mask: u32,
+    entries: u32,
+    sqes: *mut Sqe,
+}
+
+impl SubmissionQueue {
+    /// Returns false if the queue is full.
+    fn push(&mut self, sqe: Sqe) -> bool {
+        let (head, tail) = unsafe {
+            (
+                (*self.head).load(Ordering::Acquire),
+                // Only we update the tail.
+                (*self.tail).load(Ordering::Relaxed),
+            )
+        };
+        if tail.wrapping_sub(head) == self.entries {
+            return false;
+        }
+        unsafe {
+            ptr::write(self.sqes.add((tail & self.mask) as usize), sqe);
+            (*self.tail).store(tail.wrapping_add(1), Ordering::Release);
+        }
+        true
+    }
+
+    /// Number of entries not yet consumed by the kernel.
+    fn pending(&self) -> u32 {
+        unsafe {
+            (*self.tail)
+                .load(Ordering::Relaxed)
+                .wrapping_sub((*self.head).load(Ordering::Acquire))
+        }
+    }
+}
+
+struct CompletionQueue {
+    head: *const AtomicU32,
+    tail: *const AtomicU32,
+    mask: u32,
+    cqes: *const Cqe,
+}
+
+impl CompletionQueue {
+    fn pop(&mut self) -> Option<Cqe> {
+        unsafe {
+            // Only we update the head.
+            let head = (*self.head).load(Ordering::Relaxed);
+            if head == (*self.tail).load(Ordering::Acquire) {
+                return None;
+            }
+            let cqe = ptr::read(self.cqes.add((head & self.mask) as usize));
+            (*self.head).store(head.wrapping_add(1), Ordering::Release);
+            Some(cqe)
+        }
+    }
+}
+
+/// Shared memory mapping of (part of) the ring.
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl Mmap {
+    fn new(fd: RawFd, len: usize, offset: libc::off_t) -> io::Result<Mmap> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_POPULATE,
+                fd,
+                offset,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Mmap { ptr, len })
+        }
+    }
+
+    /// Returns a pointer to `offset` bytes into the mapping.
+    fn at<T>(&self, offset: u32) -> *mut T {
+        unsafe { (self.ptr as *mut u8).add(offset as usize) as *mut T }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        let _ = unsafe { libc::munmap(self.ptr, self.len) };
+    }
+}
+
+// The mappings are only accessed through `State`, see above.
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+// Definitions below are taken from `linux/io_uring.h`, which are not
+// available in libc.
+
+const IORING_OFF_SQ_RING: libc::off_t = 0;
+const IORING_OFF_SQES: libc::off_t = 0x1000_0000;
+
+const IORING_FEAT_SINGLE_MMAP: u32 = 1 << 0;
+const IORING_FEAT_EXT_ARG: u32 = 1 << 8;
+
+const IORING_ENTER_GETEVENTS: u32 = 1 << 0;
+const IORING_ENTER_EXT_ARG: u32 = 1 << 3;
+
+const IORING_OP_POLL_ADD: u8 = 6;
+const IORING_OP_POLL_REMOVE: u8 = 7;
+
+const IORING_POLL_ADD_MULTI: u32 = 1 << 0;
+
+const IORING_CQE_F_MORE: u32 = 1 << 1;
+
+#[repr(C)]
+struct SqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+struct CqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+struct Params {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqringOffsets,
+    cq_off: CqringOffsets,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Sqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    off: u64,
+    addr: u64,
+    len: u32,
+    /// `poll32_events` for `IORING_OP_POLL_ADD`.
+    op_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    splice_fd_in: i32,
+    addr3: u64,
+    pad: u64,
+}
+
+#[repr(C)]
+struct Cqe {
+    user_data: u64,
+    res: i32,
+    flags: u32,
+}
+
+#[repr(C)]
+struct GetEventsArg {
+    sigmask: u64,
+    sigmask_sz: u32,
+    pad: u32,
+    ts: u64,
+}
+
+#[repr(C)]
+struct KernelTimespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+#[test]
+fn assert_abi_sizes() {
+    assert_eq!(mem::size_of::<Params>(), 120);
+    assert_eq!(mem::size_of::<Sqe>(), 64);
+    assert_eq!(mem::size_of::<Cqe>(), 16);
+    assert_eq!(mem::size_of::<GetEventsArg>(), 24);
+}
@@ -3,13 +3,13 @@ use log::error;
 use std::mem::MaybeUninit;
 use std::ops::{Deref, DerefMut};
 use std::os::unix::io::{AsRawFd, RawFd};
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use std::{cmp, io, ptr, slice};
 
 /// Unique id for use as `SelectorId`.
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
 
 // Type of the `nchanges` and `nevents` parameters in the `kevent` function.
@@ -66,7 +66,7 @@ macro_rules! kevent {
 
 #[derive(Debug)]
 pub struct Selector {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
     id: usize,
     kq: RawFd,
 }
@@ -76,7 +76,7 @@ impl Selector {
         syscall!(kqueue())
             .and_then(|kq| syscall!(fcntl(kq, libc::F_SETFD, libc::FD_CLOEXEC)).map(|_| kq))
             .map(|kq| Selector {
-                #[cfg(debug_assertions)]
+                #[cfg(any(debug_assertions, feature = "registration-checks"))]
                 id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
                 kq,
             })
@@ -85,7 +85,7 @@ impl Selector {
     pub fn try_clone(&self) -> io::Result<Selector> {
         syscall!(dup(self.kq)).map(|kq| Selector {
             // It's the same selector, so we use the same id.
-            #[cfg(debug_assertions)]
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
             id: self.id,
             kq,
         })
@@ -122,10 +122,13 @@ impl Selector {
     }
 
     pub fn register(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
-        let flags = libc::EV_CLEAR | libc::EV_RECEIPT | libc::EV_ADD;
-        // At most we need two changes, but maybe we only need 1.
-        let mut changes: [MaybeUninit<libc::kevent>; 2] =
-            [MaybeUninit::uninit(), MaybeUninit::uninit()];
+        let flags = interests_to_flags(interests) | libc::EV_RECEIPT | libc::EV_ADD;
+        // At most we need three changes, but maybe we only need 1.
+        let mut changes: [MaybeUninit<libc::kevent>; 3] = [
+            MaybeUninit::uninit(),
+            MaybeUninit::uninit(),
+            MaybeUninit::uninit(),
+        ];
         let mut n_changes = 0;
 
         if interests.is_writable() {
@@ -140,6 +143,20 @@ impl Selector {
             n_changes += 1;
         }
 
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd"
+        ))]
+        {
+            if interests.is_priority() {
+                let kevent = except_kevent(fd, flags, token);
+                changes[n_changes] = MaybeUninit::new(kevent);
+                n_changes += 1;
+            }
+        }
+
         // Older versions of macOS (OS X 10.11 and 10.10 have been witnessed)
         // can return EPIPE when registering a pipe file descriptor where the
         // other end has already disappeared. For example code that creates a
@@ -161,25 +178,51 @@ impl Selector {
     }
 
     pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
-        let flags = libc::EV_CLEAR | libc::EV_RECEIPT;
+        // `EV_ENABLE` enables filters again that were disabled by
+        // `EV_DISPATCH`, `EV_ADD` alone doesn't on all platforms.
+        let flags = interests_to_flags(interests) | libc::EV_RECEIPT;
         let write_flags = if interests.is_writable() {
-            flags | libc::EV_ADD
+            flags | libc::EV_ADD | libc::EV_ENABLE
         } else {
             flags | libc::EV_DELETE
         };
         let read_flags = if interests.is_readable() {
-            flags | libc::EV_ADD
+            flags | libc::EV_ADD | libc::EV_ENABLE
         } else {
             flags | libc::EV_DELETE
         };
 
+        #[cfg(not(any(
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd"
+        )))]
         let mut changes: [libc::kevent; 2] = [
             kevent!(fd, libc::EVFILT_WRITE, write_flags, token.0),
             kevent!(fd, libc::EVFILT_READ, read_flags, token.0),
         ];
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd"
+        ))]
+        let mut changes: [libc::kevent; 3] = {
+            let except_flags = if interests.is_priority() {
+                flags | libc::EV_ADD | libc::EV_ENABLE
+            } else {
+                flags | libc::EV_DELETE
+            };
+            [
+                kevent!(fd, libc::EVFILT_WRITE, write_flags, token.0),
+                kevent!(fd, libc::EVFILT_READ, read_flags, token.0),
+                except_kevent(fd, except_flags, token),
+            ]
+        };
 
         // Since there is no way to check with which interests the fd was
-        // registered we modify both readable and write, adding it when required
+        // registered we modify all filters, adding it when required
         // and removing it otherwise, ignoring the ENOENT error when it comes
         // up. The ENOENT error informs us that a filter we're trying to remove
         // wasn't there in first place, but we don't really care since our goal
@@ -195,16 +238,34 @@ impl Selector {
 
     pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
         let flags = libc::EV_DELETE | libc::EV_RECEIPT;
+        #[cfg(not(any(
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd"
+        )))]
         let mut changes: [libc::kevent; 2] = [
             kevent!(fd, libc::EVFILT_WRITE, flags, 0),
             kevent!(fd, libc::EVFILT_READ, flags, 0),
         ];
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd"
+        ))]
+        let mut changes: [libc::kevent; 3] = [
+            kevent!(fd, libc::EVFILT_WRITE, flags, 0),
+            kevent!(fd, libc::EVFILT_READ, flags, 0),
+            except_kevent(fd, flags, Token(0)),
+        ];
 
         // Since there is no way to check with which interests the fd was
-        // registered we remove both filters (readable and writeable) and ignore
-        // the ENOENT error when it comes up. The ENOENT error informs us that
-        // the filter wasn't there in first place, but we don't really care
-        // about that since our goal is to remove it.
+        // registered we remove all filters (readable, writeable and, where
+        // supported, priority) and ignore the ENOENT error when it comes up.
+        // The ENOENT error informs us that the filter wasn't there in first
+        // place, but we don't really care about that since our goal is to
+        // remove it.
         kevent_register(self.kq, &mut changes, &[libc::ENOENT as Data])
     }
 
@@ -249,6 +310,38 @@ impl Selector {
     }
 }
 
+/// Returns a `kevent` for priority (out-of-band data) readiness.
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "openbsd"
+))]
+fn except_kevent(fd: RawFd, flags: Flags, token: Token) -> libc::kevent {
+    let mut kevent = kevent!(fd, libc::EVFILT_EXCEPT, flags, token.0);
+    kevent.fflags = libc::NOTE_OOB;
+    kevent
+}
+
+/// Returns the registration mode `flags` for `interests`.
+fn interests_to_flags(interests: Interest) -> Flags {
+    let mut flags = if interests.is_level() {
+        0
+    } else {
+        libc::EV_CLEAR
+    };
+
+    // `EV_DISPATCH` disables, rather than deletes (`EV_ONESHOT`), the filter
+    // after an event is returned. This way reregistering (which uses
+    // `EV_ENABLE`) enables it again and deregistering doesn't have to deal
+    // with missing filters.
+    if interests.is_oneshot() {
+        flags |= libc::EV_DISPATCH;
+    }
+
+    flags
+}
+
 /// Register `changes` with `kq`ueue.
 fn kevent_register(
     kq: RawFd,
@@ -292,8 +385,8 @@ fn check_errors(events: &[libc::kevent], ignored_errors: &[Data]) -> io::Result<
     Ok(())
 }
 
-cfg_net! {
-    #[cfg(debug_assertions)]
+cfg_io_source! {
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
     impl Selector {
         pub fn id(&self) -> usize {
             self.id
@@ -349,7 +442,7 @@ unsafe impl Sync for Events {}
 pub mod event {
     use std::fmt;
 
-    use crate::sys::Event;
+    use super::Event;
     use crate::Token;
 
     use super::{Filter, Flags};
@@ -393,9 +486,26 @@ pub mod event {
         event.filter == libc::EVFILT_WRITE && event.flags & libc::EV_EOF != 0
     }
 
-    pub fn is_priority(_: &Event) -> bool {
-        // kqueue doesn't have priority indicators.
-        false
+    #[allow(unused_variables)] // `event` is not used on some platforms.
+    pub fn is_priority(event: &Event) -> bool {
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd"
+        ))]
+        {
+            event.filter == libc::EVFILT_EXCEPT
+        }
+        #[cfg(not(any(
+            target_os = "dragonfly",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd"
+        )))]
+        {
+            false
+        }
     }
 
     #[allow(unused_variables)] // `event` is not used on some platforms.
@@ -432,6 +542,8 @@ pub mod event {
         }
     }
 
+    // The mock events only keep the readiness.
+    #[cfg_attr(feature = "mock", allow(dead_code))]
     pub fn debug_details(f: &mut fmt::Formatter<'_>, event: &Event) -> fmt::Result {
         debug_detail!(
             FilterDetails(Filter),
@@ -1,25 +1,65 @@
-#[cfg(any(target_os = "linux", target_os = "android", target_os = "solaris"))]
+#[cfg(feature = "poll-fallback")]
+mod poll;
+
+#[cfg(feature = "poll-fallback")]
+pub(crate) use self::poll::{event, Event, Events, Selector};
+
+// The io_uring selector only uses the event types.
+#[cfg(all(
+    any(target_os = "linux", target_os = "android", target_os = "solaris"),
+    not(feature = "poll-fallback")
+))]
+#[cfg_attr(all(feature = "io-uring", target_os = "linux"), allow(dead_code))]
 mod epoll;
 
-#[cfg(any(target_os = "linux", target_os = "android", target_os = "solaris"))]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android", target_os = "solaris"),
+    not(any(
+        feature = "poll-fallback",
+        all(feature = "io-uring", target_os = "linux")
+    ))
+))]
 pub(crate) use self::epoll::{event, Event, Events, Selector};
 
-#[cfg(any(
-    target_os = "dragonfly",
-    target_os = "freebsd",
-    target_os = "ios",
-    target_os = "macos",
-    target_os = "netbsd",
-    target_os = "openbsd"
+#[cfg(all(
+    feature = "io-uring",
+    target_os = "linux",
+    not(feature = "poll-fallback")
+))]
+mod io_uring;
+
+#[cfg(all(
+    feature = "io-uring",
+    target_os = "linux",
+    not(feature = "poll-fallback")
+))]
+pub(crate) use self::{
+    epoll::{event, Event, Events},
+    io_uring::Selector,
+};
+
+#[cfg(all(
+    any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ),
+    not(feature = "poll-fallback")
 ))]
 mod kqueue;
 
-#[cfg(any(
-    target_os = "dragonfly",
-    target_os = "freebsd",
-    target_os = "ios",
-    target_os = "macos",
-    target_os = "netbsd",
-    target_os = "openbsd"
+#[cfg(all(
+    any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ),
+    not(feature = "poll-fallback")
 ))]
 pub(crate) use self::kqueue::{event, Event, Events, Selector};
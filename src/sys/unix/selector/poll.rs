@@ -0,0 +1,548 @@
+//! Selector based on `poll(2)`, for platforms (or sandboxes) where neither
+//! epoll nor kqueue is available.
+//!
+//! `poll(2)` is level-triggered, so to provide the edge-triggered contract
+//! documented on `Poll` readiness that is returned is disarmed until the
+//! registration is rearmed. This happens after every I/O operation on the
+//! source (see `IoSourceState::do_io`), as it might have changed the
+//! readiness, or when the source is reregistered. An operation that returns a
+//! `WouldBlock` error always rearms the registration.
+//!
+//! The file descriptors are polled in a copy of the registrations, which
+//! means that a thread registering a source needs to wake up the thread in
+//! `select`. For this (and for the `Waker`) the selector uses a self-pipe.
+
+use crate::sys::unix::pipe;
+use crate::{Interest, Token};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use std::{cmp, io};
+
+/// Unique id for use as `SelectorId`.
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// `POLLRDHUP` is not defined in libc.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const POLLRDHUP: libc::c_short = 0x2000;
+
+#[derive(Debug)]
+pub struct Selector {
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
+    id: usize,
+    state: Arc<SelectorState>,
+}
+
+impl Selector {
+    pub fn new() -> io::Result<Selector> {
+        SelectorState::new().map(|state| Selector {
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            state: Arc::new(state),
+        })
+    }
+
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        Ok(Selector {
+            // It's the same selector, so we use the same id.
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
+            id: self.id,
+            state: self.state.clone(),
+        })
+    }
+
+    pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        events.clear();
+        // A timeout that overflows is treated as no timeout.
+        let deadline = timeout.and_then(|to| Instant::now().checked_add(to));
+        loop {
+            let timeout = deadline
+                .map(|deadline| {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    // Round up so that we never return before the deadline.
+                    let mut millis = timeout.as_millis();
+                    if timeout.subsec_nanos() % 1_000_000 != 0 {
+                        millis += 1;
+                    }
+                    cmp::min(millis, libc::c_int::max_value() as u128) as libc::c_int
+                })
+                .unwrap_or(-1);
+
+            let mut fds = self.state.poll_fds();
+            let res = syscall!(poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout));
+            self.state.polling.store(false, Ordering::Release);
+            if res? == 0 {
+                // Timed out.
+                return Ok(());
+            }
+
+            if fds[0].revents != 0 {
+                self.state.empty_pipe();
+            }
+            self.state.registrations().reap(&fds[1..], events);
+
+            // Registrations changing while polling, or readiness we've since
+            // disarmed, wakes us up without events. In which case we poll
+            // again for the remainder of the timeout.
+            if !events.is_empty() || timeout == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn register(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        if interests.is_exclusive() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "exclusive registrations are not supported by the poll selector",
+            ));
+        }
+
+        {
+            let mut registrations = self.state.registrations();
+            if registrations.fds.contains_key(&fd) {
+                // Same error as `epoll_ctl(2)` returns.
+                return Err(io::Error::from_raw_os_error(libc::EEXIST));
+            }
+            let _ = registrations.fds.insert(
+                fd,
+                Registration {
+                    token,
+                    interests,
+                    armed: interests_to_poll(interests),
+                },
+            );
+        }
+        self.state.notify()
+    }
+
+    pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        if interests.is_exclusive() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "exclusive registrations are not supported by the poll selector",
+            ));
+        }
+
+        {
+            let mut registrations = self.state.registrations();
+            let registration = registrations.get(fd)?;
+            registration.token = token;
+            registration.interests = interests;
+            registration.armed = interests_to_poll(interests);
+        }
+        self.state.notify()
+    }
+
+    pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        let mut registrations = self.state.registrations();
+        registrations
+            .fds
+            .remove(&fd)
+            .map(|_| ())
+            // Same error as `epoll_ctl(2)` returns.
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))
+        // No need to wake up a thread polling the file descriptor, if it's
+        // ready the readiness is simply ignored.
+    }
+
+    /// Rearm the registration of `fd` after an I/O operation that returned
+    /// `result`.
+    pub fn rearm<R>(&self, fd: RawFd, result: &io::Result<R>) -> io::Result<()> {
+        {
+            let mut registrations = self.state.registrations();
+            let registration = match registrations.fds.get_mut(&fd) {
+                Some(registration) => registration,
+                // Deregistered, or registered with another selector.
+                None => return Ok(()),
+            };
+            if registration.interests.is_oneshot() || registration.interests.is_level() {
+                // Only reregistering rearms a one-shot registration and
+                // level-triggered registrations are never disarmed.
+                return Ok(());
+            }
+            let full = interests_to_poll(registration.interests);
+            let disarmed = full & !registration.armed;
+            if disarmed == 0 {
+                return Ok(());
+            }
+
+            match result {
+                // The source is drained, so any readiness from now on is a new
+                // edge, even if it arrives before the next call to `select`.
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    registration.armed = full;
+                }
+                _ => {
+                    // Only rearm the readiness that is no longer present, e.g.
+                    // if not all data was read we won't report it again.
+                    let mut pollfd = libc::pollfd {
+                        fd,
+                        events: disarmed & !ALWAYS,
+                        revents: 0,
+                    };
+                    let _ = syscall!(poll(&mut pollfd, 1, 0))?;
+                    if pollfd.revents & ALWAYS != 0 {
+                        // Still in an error or hang up state, which `poll(2)`
+                        // would keep returning.
+                        return Ok(());
+                    }
+                    registration.armed |= disarmed & !pollfd.revents;
+                }
+            }
+        }
+        self.state.notify()
+    }
+
+    /// Returns an event for `token` from the current or next call to
+    /// `select`.
+    pub fn wake(&self, token: Token) -> io::Result<()> {
+        {
+            let mut registrations = self.state.registrations();
+            if !registrations.woken.contains(&token) {
+                registrations.woken.push(token);
+            }
+        }
+        self.state.write_pipe()
+    }
+
+    /// Inspecting the registrations is only supported by epoll.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn inspect(&self) -> io::Result<Vec<(RawFd, u32, u64)>> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "inspecting registrations is only supported by the epoll selector",
+        ))
+    }
+}
+
+cfg_io_source! {
+    impl Selector {
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
+        pub fn id(&self) -> usize {
+            self.id
+        }
+    }
+}
+
+impl AsRawFd for Selector {
+    /// Returns the reading end of the self-pipe, which is only readable when
+    /// the selector is woken up.
+    fn as_raw_fd(&self) -> RawFd {
+        self.state.receiver.as_raw_fd()
+    }
+}
+
+/// State shared between a selector and its clones.
+#[derive(Debug)]
+struct SelectorState {
+    registrations: Mutex<Registrations>,
+    /// Self-pipe used to wake up the thread in `select`.
+    sender: File,
+    receiver: File,
+    /// True if a thread is blocked in `select`.
+    polling: AtomicBool,
+}
+
+impl SelectorState {
+    fn new() -> io::Result<SelectorState> {
+        let (sender, receiver) = pipe::new()?;
+        Ok(SelectorState {
+            registrations: Mutex::new(Registrations {
+                fds: HashMap::new(),
+                woken: Vec::new(),
+            }),
+            sender,
+            receiver,
+            polling: AtomicBool::new(false),
+        })
+    }
+
+    fn registrations(&self) -> MutexGuard<'_, Registrations> {
+        self.registrations.lock().unwrap()
+    }
+
+    /// Returns the file descriptors to poll, the first one being the reading
+    /// end of the self-pipe.
+    ///
+    /// This marks the selector as polling while holding the lock, so that any
+    /// change made to the registrations after the copy wakes up the thread.
+    fn poll_fds(&self) -> Vec<libc::pollfd> {
+        let registrations = self.registrations();
+        self.polling.store(true, Ordering::Release);
+        let mut fds = Vec::with_capacity(registrations.fds.len() + 1);
+        fds.push(libc::pollfd {
+            fd: self.receiver.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        });
+        for (fd, registration) in registrations.fds.iter() {
+            // Disarmed file descriptors are skipped entirely, as `poll(2)`
+            // always returns `POLLERR` and `POLLHUP`.
+            if registration.armed != 0 {
+                fds.push(libc::pollfd {
+                    fd: *fd,
+                    events: registration.armed & !ALWAYS,
+                    revents: 0,
+                });
+            }
+        }
+        fds
+    }
+
+    /// Wake up the thread in `select`, if any, to pick up changes to the
+    /// registrations.
+    fn notify(&self) -> io::Result<()> {
+        if self.polling.load(Ordering::Acquire) {
+            self.write_pipe()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_pipe(&self) -> io::Result<()> {
+        match (&self.sender).write(&[1]) {
+            Ok(_) => Ok(()),
+            // The pipe is full, so the thread in `select` will be woken up
+            // already.
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => self.write_pipe(),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Empty the self-pipe, ignoring any errors.
+    fn empty_pipe(&self) {
+        let mut buf = [0; 64];
+        loop {
+            match (&self.receiver).read(&mut buf) {
+                Ok(n) if n > 0 => continue,
+                _ => return,
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Registrations {
+    fds: HashMap<RawFd, Registration>,
+    /// Tokens of `Waker`s that were woken.
+    woken: Vec<Token>,
+}
+
+#[derive(Debug)]
+struct Registration {
+    token: Token,
+    interests: Interest,
+    /// The `poll(2)` events we still report, see the module documentation.
+    armed: libc::c_short,
+}
+
+impl Registrations {
+    fn get(&mut self, fd: RawFd) -> io::Result<&mut Registration> {
+        self.fds
+            .get_mut(&fd)
+            // Same error as `epoll_ctl(2)` returns.
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))
+    }
+
+    /// Move the readiness in `fds` into `events`, disarming it.
+    fn reap(&mut self, fds: &[libc::pollfd], events: &mut Events) {
+        // Tokens that don't fit are returned by the next call to `select`.
+        let n = cmp::min(self.woken.len(), events.capacity() - events.len());
+        for token in self.woken.drain(..n) {
+            events.push(Event {
+                token,
+                events: libc::POLLIN,
+            });
+        }
+
+        for pollfd in fds {
+            if events.len() == events.capacity() {
+                break;
+            }
+            let registration = match self.fds.get_mut(&pollfd.fd) {
+                Some(registration) => registration,
+                // Deregistered while polling.
+                None => continue,
+            };
+            // Ignore readiness disarmed (or changed by reregistering) while
+            // polling.
+            if pollfd.revents & registration.armed == 0 {
+                continue;
+            }
+            // Like epoll we report the complete readiness once a (new) edge is
+            // detected, e.g. `POLLIN` along with `POLLRDHUP`.
+            let full = interests_to_poll(registration.interests);
+            let mut revents = pollfd.revents;
+            if full & !pollfd.events & !ALWAYS != 0 {
+                // Some events were disarmed and thus not polled, e.g. `POLLOUT`
+                // for a pipe of which the reading end was closed.
+                let mut complete = libc::pollfd {
+                    fd: pollfd.fd,
+                    events: full & !ALWAYS,
+                    revents: 0,
+                };
+                if unsafe { libc::poll(&mut complete, 1, 0) } != -1 {
+                    revents |= complete.revents;
+                }
+            }
+            let readiness = revents & full;
+
+            if registration.interests.is_oneshot() {
+                registration.armed = 0;
+            } else if registration.interests.is_level() {
+                // Level-triggered registrations are never disarmed.
+            } else if readiness & ALWAYS != 0 {
+                // These are returned by `poll(2)` regardless of the requested
+                // events, so the only way to disarm them is to skip the file
+                // descriptor entirely.
+                registration.armed = 0;
+            } else {
+                registration.armed &= !readiness;
+            }
+
+            if readiness & libc::POLLNVAL != 0 {
+                // The file descriptor was closed without deregistering it
+                // first, which other selectors silently ignore as well.
+                continue;
+            }
+            events.push(Event {
+                token: registration.token,
+                events: readiness,
+            });
+        }
+    }
+}
+
+/// Events `poll(2)` always returns, regardless of the requested events.
+const ALWAYS: libc::c_short = libc::POLLERR | libc::POLLHUP | libc::POLLNVAL;
+
+/// Returns the `poll(2)` events we report for `interests`.
+fn interests_to_poll(interests: Interest) -> libc::c_short {
+    let mut kind = ALWAYS;
+
+    if interests.is_readable() {
+        kind |= libc::POLLIN;
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            kind |= POLLRDHUP;
+        }
+    }
+
+    if interests.is_writable() {
+        kind |= libc::POLLOUT;
+    }
+
+    if interests.is_priority() {
+        kind |= libc::POLLPRI;
+    }
+
+    kind
+}
+
+#[derive(Clone, Debug)]
+pub struct Event {
+    token: Token,
+    events: libc::c_short,
+}
+
+pub type Events = Vec<Event>;
+
+pub mod event {
+    use std::fmt;
+
+    use super::Event;
+    use crate::Token;
+
+    pub fn token(event: &Event) -> Token {
+        event.token
+    }
+
+    pub fn is_readable(event: &Event) -> bool {
+        (event.events & libc::POLLIN) != 0 || (event.events & libc::POLLPRI) != 0
+    }
+
+    pub fn is_writable(event: &Event) -> bool {
+        (event.events & libc::POLLOUT) != 0
+    }
+
+    pub fn is_error(event: &Event) -> bool {
+        (event.events & libc::POLLERR) != 0
+    }
+
+    pub fn is_read_closed(event: &Event) -> bool {
+        // Both halves of the socket have closed
+        event.events & libc::POLLHUP != 0
+            // Socket has received FIN or called shutdown(SHUT_RD)
+            || read_hup(event)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    fn read_hup(event: &Event) -> bool {
+        event.events & super::POLLRDHUP != 0
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    fn read_hup(_: &Event) -> bool {
+        // Not supported.
+        false
+    }
+
+    pub fn is_write_closed(event: &Event) -> bool {
+        // Both halves of the socket have closed
+        event.events & libc::POLLHUP != 0
+            // Unix pipe write end has closed
+            || (event.events & libc::POLLOUT != 0 && event.events & libc::POLLERR != 0)
+    }
+
+    pub fn is_priority(event: &Event) -> bool {
+        (event.events & libc::POLLPRI) != 0
+    }
+
+    pub fn is_aio(_: &Event) -> bool {
+        // Not supported.
+        false
+    }
+
+    pub fn is_lio(_: &Event) -> bool {
+        // Not supported.
+        false
+    }
+
+    // The mock events only keep the readiness.
+    #[cfg_attr(feature = "mock", allow(dead_code))]
+    pub fn debug_details(f: &mut fmt::Formatter<'_>, event: &Event) -> fmt::Result {
+        #[allow(clippy::trivially_copy_pass_by_ref)]
+        fn check_events(got: &libc::c_short, want: &libc::c_short) -> bool {
+            (*got & want) != 0
+        }
+        debug_detail!(
+            EventsDetails(libc::c_short),
+            check_events,
+            libc::POLLIN,
+            libc::POLLPRI,
+            libc::POLLOUT,
+            libc::POLLRDNORM,
+            libc::POLLRDBAND,
+            libc::POLLWRNORM,
+            libc::POLLWRBAND,
+            libc::POLLERR,
+            libc::POLLHUP,
+            libc::POLLNVAL,
+        );
+
+        f.debug_struct("pollfd")
+            .field("events", &EventsDetails(event.events))
+            .field("token", &event.token)
+            .finish()
+    }
+}
@@ -0,0 +1,469 @@
+use std::os::raw::c_int;
+use std::sync::{Mutex, Once};
+use std::{io, ptr};
+
+/// Largest supported signal number.
+const MAX_SIGNAL: c_int = 128;
+
+fn check_signal(signal: c_int) -> io::Result<()> {
+    if signal > 0 && signal <= MAX_SIGNAL {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid signal number",
+        ))
+    }
+}
+
+/// Per signal state shared by all `Signals`, indexed by signal number.
+///
+/// Used to only undo changes to the process' signal state once the last
+/// `Signals` handling the signal is dropped.
+struct Shared {
+    /// Number of `Signals` handling the signal.
+    count: usize,
+    /// Whether the signal should be unblocked once `count` drops to zero, only
+    /// used by `signalfd`.
+    #[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+    restore: bool,
+    /// Whether the self-pipe's signal handler is installed, in which case
+    /// the disposition it replaced is saved in `pipe::PREVIOUS`.
+    handler: bool,
+}
+
+fn shared() -> &'static Mutex<Vec<Shared>> {
+    static INIT: Once = Once::new();
+    static mut SHARED: *const Mutex<Vec<Shared>> = ptr::null();
+
+    INIT.call_once(|| {
+        let shared = (0..=MAX_SIGNAL)
+            .map(|_| Shared {
+                count: 0,
+                restore: false,
+                handler: false,
+            })
+            .collect();
+        unsafe { SHARED = Box::into_raw(Box::new(Mutex::new(shared))) };
+    });
+    unsafe { &*SHARED }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod signalfd {
+    use super::{check_signal, shared};
+
+    use log::error;
+
+    use std::fs::File;
+    use std::os::raw::c_int;
+    use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+    use std::{fmt, io, mem, ptr};
+
+    /// Signals backed by `signalfd`.
+    ///
+    /// For `signalfd` to receive the signals they must be blocked, so we block
+    /// them in the calling thread when creating it and unblock them again
+    /// once the last `Signals` handling the signals is dropped.
+    pub struct Signals {
+        fd: File,
+        signals: Vec<c_int>,
+    }
+
+    impl Signals {
+        pub fn new(signals: &[c_int]) -> io::Result<Signals> {
+            let mut set = empty_set();
+            for signal in signals.iter().copied() {
+                check_signal(signal)?;
+                syscall!(sigaddset(&mut set, signal))?;
+            }
+
+            let fd = syscall!(signalfd(-1, &set, libc::SFD_CLOEXEC | libc::SFD_NONBLOCK))?;
+            let fd = unsafe { File::from_raw_fd(fd) };
+
+            let mut old = empty_set();
+            sigmask(libc::SIG_BLOCK, &set, &mut old)?;
+            let mut shared = shared().lock().unwrap();
+            for signal in signals.iter().copied() {
+                let shared = &mut shared[signal as usize];
+                if shared.count == 0 {
+                    // Only unblock the signal later if we blocked it.
+                    shared.restore = unsafe { libc::sigismember(&old, signal) } == 0;
+                }
+                shared.count += 1;
+            }
+
+            Ok(Signals {
+                fd,
+                signals: signals.to_vec(),
+            })
+        }
+
+        pub fn receive(&self) -> io::Result<c_int> {
+            let mut info: libc::signalfd_siginfo = unsafe { mem::zeroed() };
+            let size = mem::size_of::<libc::signalfd_siginfo>();
+            let n = syscall!(read(
+                self.fd.as_raw_fd(),
+                &mut info as *mut _ as *mut libc::c_void,
+                size
+            ))?;
+            debug_assert_eq!(n as usize, size);
+            Ok(info.ssi_signo as c_int)
+        }
+    }
+
+    impl Drop for Signals {
+        fn drop(&mut self) {
+            let mut set = empty_set();
+            let mut shared = shared().lock().unwrap();
+            for signal in self.signals.iter().copied() {
+                let shared = &mut shared[signal as usize];
+                shared.count -= 1;
+                if shared.count == 0 && shared.restore {
+                    unsafe { libc::sigaddset(&mut set, signal) };
+                }
+            }
+            if let Err(err) = sigmask(libc::SIG_UNBLOCK, &set, ptr::null_mut()) {
+                error!("error unblocking signals: {}", err);
+            }
+        }
+    }
+
+    impl fmt::Debug for Signals {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Signals")
+                .field("fd", &self.fd)
+                .field("signals", &self.signals)
+                .finish()
+        }
+    }
+
+    impl AsRawFd for Signals {
+        fn as_raw_fd(&self) -> RawFd {
+            self.fd.as_raw_fd()
+        }
+    }
+
+    fn empty_set() -> libc::sigset_t {
+        let mut set: libc::sigset_t = unsafe { mem::zeroed() };
+        unsafe { libc::sigemptyset(&mut set) };
+        set
+    }
+
+    fn sigmask(how: c_int, set: *const libc::sigset_t, old: *mut libc::sigset_t) -> io::Result<()> {
+        // `pthread_sigmask` returns the error, rather then setting `errno`.
+        match unsafe { libc::pthread_sigmask(how, set, old) } {
+            0 => Ok(()),
+            err => Err(io::Error::from_raw_os_error(err)),
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::signalfd::Signals;
+
+/// Also used on Linux and Android for the `SIGCHLD` fallback of
+/// `unix::Process`, which needs every `Signals` to receive the signal.
+pub mod pipe {
+    use super::{check_signal, shared, MAX_SIGNAL};
+
+    use log::error;
+
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::os::raw::c_int;
+    use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+    use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+    use std::{fmt, mem, ptr};
+
+    #[cfg(target_os = "solaris")]
+    use libc::___errno as errno_location;
+    #[cfg(any(target_os = "netbsd", target_os = "openbsd"))]
+    use libc::__errno as errno_location;
+    #[cfg(target_os = "android")]
+    use libc::__errno as errno_location;
+    #[cfg(target_os = "linux")]
+    use libc::__errno_location as errno_location;
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos"
+    ))]
+    use libc::__error as errno_location;
+
+    /// A self-pipe written to by the signal handler.
+    ///
+    /// Slots are never freed, only reused, so that the signal handler can
+    /// walk the list without taking a lock and without the risk of writing to
+    /// a file descriptor that has been closed (and possibly reused).
+    struct Slot {
+        /// Whether a `Signals` is using this slot.
+        in_use: AtomicBool,
+        /// Signals to write to the pipe, indexed by signal number.
+        signals: Box<[AtomicBool]>,
+        sender: RawFd,
+        receiver: RawFd,
+        next: *mut Slot,
+    }
+
+    // Safety: the file descriptors and `next` are never changed once the slot
+    // is added to `SLOTS`.
+    unsafe impl Send for Slot {}
+    unsafe impl Sync for Slot {}
+
+    static SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+
+    /// Dispositions of the signals before `handler` was installed, indexed by
+    /// signal number. Only written with the lock of `shared` held and before
+    /// installing the handler, so that the handler can read it without
+    /// locking.
+    static PREVIOUS: AtomicPtr<libc::sigaction> = AtomicPtr::new(ptr::null_mut());
+
+    /// Returns `PREVIOUS`, allocating it if needed. Must be called with the
+    /// lock of `shared` held.
+    fn previous() -> *mut libc::sigaction {
+        let previous = PREVIOUS.load(Ordering::Acquire);
+        if !previous.is_null() {
+            return previous;
+        }
+        let previous: Box<[libc::sigaction]> =
+            (0..=MAX_SIGNAL).map(|_| unsafe { mem::zeroed() }).collect();
+        let previous = Box::into_raw(previous) as *mut libc::sigaction;
+        PREVIOUS.store(previous, Ordering::Release);
+        previous
+    }
+
+    /// Claim an unused slot, or create a new one.
+    fn claim() -> io::Result<&'static Slot> {
+        let mut next = SLOTS.load(Ordering::Acquire);
+        while let Some(slot) = unsafe { next.as_ref() } {
+            if slot
+                .in_use
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                // Remove any signals left behind by the previous user.
+                let mut buf = [0u8; 64];
+                while unsafe { libc::read(slot.receiver, buf.as_mut_ptr() as *mut _, buf.len()) }
+                    > 0
+                {}
+                return Ok(slot);
+            }
+            next = slot.next;
+        }
+
+        let (receiver, sender) = pipe()?;
+        let slot = Box::into_raw(Box::new(Slot {
+            in_use: AtomicBool::new(true),
+            signals: (0..=MAX_SIGNAL).map(|_| AtomicBool::new(false)).collect(),
+            sender,
+            receiver,
+            next: ptr::null_mut(),
+        }));
+        let mut head = SLOTS.load(Ordering::Acquire);
+        loop {
+            unsafe { (*slot).next = head };
+            match SLOTS.compare_exchange(head, slot, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Ok(unsafe { &*slot }),
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn pipe() -> io::Result<(RawFd, RawFd)> {
+        let mut fds: [RawFd; 2] = [-1, -1];
+        syscall!(pipe(fds.as_mut_ptr()))?;
+        for fd in fds.iter().copied() {
+            if let Err(err) = syscall!(fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK))
+                .and_then(|_| syscall!(fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC)))
+            {
+                let _ = unsafe { libc::close(fds[0]) };
+                let _ = unsafe { libc::close(fds[1]) };
+                return Err(err);
+            }
+        }
+        Ok((fds[0], fds[1]))
+    }
+
+    extern "C" fn handler(signal: c_int) {
+        // Writing to the pipe could overwrite `errno`, which would be very
+        // confusing for the interrupted code.
+        let errno = unsafe { *errno_location() };
+        let mut handled = false;
+        let mut next = SLOTS.load(Ordering::Acquire);
+        while let Some(slot) = unsafe { next.as_ref() } {
+            if slot.in_use.load(Ordering::Acquire)
+                && slot.signals[signal as usize].load(Ordering::Acquire)
+            {
+                handled = true;
+                let byte = signal as u8;
+                // If the pipe is full the signal is coalesced with the ones
+                // already in the pipe, just like the OS coalesces signals.
+                let _ = unsafe { libc::write(slot.sender, &byte as *const u8 as *const _, 1) };
+            }
+            next = slot.next;
+        }
+
+        if !handled {
+            // No `Signals` handles the signal anymore, so we deliver the
+            // signal as if we never installed the handler. The signal is
+            // blocked while the handler runs, so it's delivered once we
+            // return. `PREVIOUS` is set before the handler is installed.
+            unsafe {
+                let previous = PREVIOUS.load(Ordering::Acquire);
+                libc::sigaction(signal, previous.add(signal as usize), ptr::null_mut());
+                libc::raise(signal);
+            }
+        }
+        unsafe { *errno_location() = errno };
+    }
+
+    /// Signals backed by a self-pipe.
+    ///
+    /// A signal handler is installed for the signals, which writes the number
+    /// of the signal to the pipe. Once the last `Signals` handling a signal is
+    /// dropped the previous disposition of the signal is restored.
+    pub struct Signals {
+        slot: &'static Slot,
+        /// Duplicate of the receiving end of the pipe, so the selector only
+        /// sees this `Signals`.
+        receiver: File,
+        signals: Vec<c_int>,
+    }
+
+    impl Signals {
+        pub fn new(signals: &[c_int]) -> io::Result<Signals> {
+            for signal in signals.iter().copied() {
+                check_signal(signal)?;
+            }
+
+            let slot = claim()?;
+            let receiver = match syscall!(fcntl(slot.receiver, libc::F_DUPFD_CLOEXEC, 0)) {
+                Ok(fd) => unsafe { File::from_raw_fd(fd) },
+                Err(err) => {
+                    slot.in_use.store(false, Ordering::Release);
+                    return Err(err);
+                }
+            };
+            // Ensure the slot is released on error.
+            let mut this = Signals {
+                slot,
+                receiver,
+                signals: Vec::with_capacity(signals.len()),
+            };
+
+            let mut shared = shared().lock().unwrap();
+            let previous = previous();
+            for signal in signals.iter().copied() {
+                slot.signals[signal as usize].store(true, Ordering::Release);
+
+                let mut action: libc::sigaction = unsafe { mem::zeroed() };
+                action.sa_sigaction = handler as extern "C" fn(c_int) as libc::sighandler_t;
+                action.sa_flags = libc::SA_RESTART;
+                unsafe { libc::sigemptyset(&mut action.sa_mask) };
+                // Only save the disposition if it's not our own handler.
+                let shared = &mut shared[signal as usize];
+                let old = if shared.handler {
+                    ptr::null_mut()
+                } else {
+                    unsafe { previous.add(signal as usize) }
+                };
+                if let Err(err) = syscall!(sigaction(signal, &action, old)) {
+                    // Set before installing the handler so that no signal is
+                    // missed, but `Drop` only clears the signals in
+                    // `this.signals`.
+                    slot.signals[signal as usize].store(false, Ordering::Release);
+                    return Err(err);
+                }
+
+                this.signals.push(signal);
+                shared.handler = true;
+                shared.count += 1;
+            }
+            Ok(this)
+        }
+
+        pub fn receive(&self) -> io::Result<c_int> {
+            let mut buf = [0; 1];
+            (&self.receiver)
+                .read_exact(&mut buf)
+                .map(|()| c_int::from(buf[0]))
+        }
+
+        /// Make `signal` pending for this `Signals` only.
+        pub fn notify(&self, signal: c_int) -> io::Result<()> {
+            let byte = signal as u8;
+            match syscall!(write(
+                self.slot.sender,
+                &byte as *const u8 as *const libc::c_void,
+                1
+            )) {
+                Ok(_) => Ok(()),
+                // Pipe is full, so it's readable already.
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => Ok(()),
+                Err(err) => Err(err),
+            }
+        }
+    }
+
+    impl Drop for Signals {
+        fn drop(&mut self) {
+            let mut shared = shared().lock().unwrap();
+            for signal in self.signals.iter().copied() {
+                self.slot.signals[signal as usize].store(false, Ordering::Release);
+                let shared = &mut shared[signal as usize];
+                shared.count -= 1;
+                if shared.count == 0 && shared.handler {
+                    shared.handler = false;
+                    let previous = unsafe { PREVIOUS.load(Ordering::Acquire).add(signal as usize) };
+                    if let Err(err) = syscall!(sigaction(signal, previous, ptr::null_mut())) {
+                        error!("error restoring signal disposition: {}", err);
+                    }
+                }
+            }
+            self.slot.in_use.store(false, Ordering::Release);
+        }
+    }
+
+    impl fmt::Debug for Signals {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Signals")
+                .field("receiver", &self.receiver)
+                .field("signals", &self.signals)
+                .finish()
+        }
+    }
+
+    impl AsRawFd for Signals {
+        fn as_raw_fd(&self) -> RawFd {
+            self.receiver.as_raw_fd()
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub use self::pipe::Signals;
+
+#[test]
+fn pipe_restores_disposition() {
+    fn disposition(signal: c_int) -> libc::sighandler_t {
+        let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+        assert_eq!(
+            unsafe { libc::sigaction(signal, ptr::null(), &mut action) },
+            0
+        );
+        action.sa_sigaction
+    }
+
+    // Rust ignores `SIGPIPE` by default, which should still be the case after
+    // the `Signals` handling it is dropped.
+    assert_eq!(disposition(libc::SIGPIPE), libc::SIG_IGN);
+    let first = pipe::Signals::new(&[libc::SIGPIPE]).unwrap();
+    let second = pipe::Signals::new(&[libc::SIGPIPE]).unwrap();
+    assert_ne!(disposition(libc::SIGPIPE), libc::SIG_IGN);
+    drop(first);
+    assert_ne!(disposition(libc::SIGPIPE), libc::SIG_IGN);
+    drop(second);
+    assert_eq!(disposition(libc::SIGPIPE), libc::SIG_IGN);
+}
@@ -2,55 +2,691 @@ use std::io;
 use std::mem::{size_of, MaybeUninit};
 use std::net::{self, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::time::Duration;
 
-use crate::sys::unix::net::{new_ip_socket, socket_addr, to_socket_addr};
-
-pub fn connect(addr: SocketAddr) -> io::Result<net::TcpStream> {
-    new_ip_socket(addr, libc::SOCK_STREAM)
-        .and_then(|socket| {
-            let (raw_addr, raw_addr_length) = socket_addr(&addr);
-            syscall!(connect(socket, raw_addr, raw_addr_length))
-                .or_else(|err| match err {
-                    // Connect hasn't finished, but that is fine.
-                    ref err if err.raw_os_error() == Some(libc::EINPROGRESS) => Ok(0),
-                    err => Err(err),
-                })
-                .map(|_| socket)
-                .map_err(|err| {
-                    // Close the socket if we hit an error, ignoring the error
-                    // from closing since we can't pass back two errors.
-                    let _ = unsafe { libc::close(socket) };
-                    err
-                })
-        })
-        .map(|socket| unsafe { net::TcpStream::from_raw_fd(socket) })
+#[cfg(not(target_os = "openbsd"))]
+use crate::net::TcpKeepalive;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use crate::net::{TcpInfo, TcpState};
+use crate::sys::unix::net::{new_socket, socket_addr, to_socket_addr};
+
+/// Option used to set the idle time before keepalive probes are sent.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const KEEPALIVE_TIME: libc::c_int = libc::TCP_KEEPALIVE;
+#[cfg(not(any(target_os = "ios", target_os = "macos", target_os = "openbsd")))]
+const KEEPALIVE_TIME: libc::c_int = libc::TCP_KEEPIDLE;
+
+/// `SO_LINGER` is in ticks rather than seconds on iOS and macOS.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const LINGER: libc::c_int = libc::SO_LINGER_SEC;
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+const LINGER: libc::c_int = libc::SO_LINGER;
+
+// Not defined by all libc versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_USER_TIMEOUT: libc::c_int = 18;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_NOTSENT_LOWAT: libc::c_int = 25;
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const TCP_NOTSENT_LOWAT: libc::c_int = 0x201;
+
+pub(crate) type TcpSocket = libc::c_int;
+
+pub(crate) fn new_v4_socket() -> io::Result<TcpSocket> {
+    new_socket(libc::AF_INET, libc::SOCK_STREAM)
+}
+
+pub(crate) fn new_v6_socket() -> io::Result<TcpSocket> {
+    new_socket(libc::AF_INET6, libc::SOCK_STREAM)
+}
+
+pub(crate) fn bind(socket: TcpSocket, addr: SocketAddr) -> io::Result<()> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    syscall!(bind(socket, raw_addr.as_ptr(), raw_addr_length)).map(|_| ())
+}
+
+pub(crate) fn connect(socket: TcpSocket, addr: SocketAddr) -> io::Result<net::TcpStream> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    match syscall!(connect(socket, raw_addr.as_ptr(), raw_addr_length)) {
+        // Connect hasn't finished, but that is fine.
+        Err(ref err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+        Err(err) => return Err(err),
+        Ok(_) => {}
+    }
+    Ok(unsafe { net::TcpStream::from_raw_fd(socket) })
+}
+
+pub(crate) fn listen(socket: TcpSocket, backlog: u32) -> io::Result<net::TcpListener> {
+    use std::convert::TryInto;
+
+    let backlog = backlog.try_into().unwrap_or(libc::c_int::max_value());
+    syscall!(listen(socket, backlog))?;
+    Ok(unsafe { net::TcpListener::from_raw_fd(socket) })
+}
+
+pub(crate) fn close(socket: TcpSocket) {
+    let _ = unsafe { libc::close(socket) };
+}
+
+pub(crate) fn set_reuseaddr(socket: TcpSocket, reuseaddr: bool) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_REUSEADDR,
+        reuseaddr as libc::c_int,
+    )
+}
+
+pub(crate) fn get_reuseaddr(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, libc::SOL_SOCKET, libc::SO_REUSEADDR).map(|value| value != 0)
+}
+
+#[cfg(not(any(target_os = "solaris", target_os = "illumos")))]
+pub(crate) fn set_reuseport(socket: TcpSocket, reuseport: bool) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_REUSEPORT,
+        reuseport as libc::c_int,
+    )
+}
+
+#[cfg(not(any(target_os = "solaris", target_os = "illumos")))]
+pub(crate) fn get_reuseport(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, libc::SOL_SOCKET, libc::SO_REUSEPORT).map(|value| value != 0)
+}
+
+pub(crate) fn set_only_v6(socket: TcpSocket, only_v6: bool) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::IPPROTO_IPV6,
+        libc::IPV6_V6ONLY,
+        only_v6 as libc::c_int,
+    )
+}
+
+pub(crate) fn get_only_v6(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, libc::IPPROTO_IPV6, libc::IPV6_V6ONLY).map(|value| value != 0)
 }
 
-pub fn bind(addr: SocketAddr) -> io::Result<net::TcpListener> {
-    new_ip_socket(addr, libc::SOCK_STREAM).and_then(|socket| {
-        // Set SO_REUSEADDR (mirrors what libstd does).
-        syscall!(setsockopt(
+pub(crate) fn set_send_buffer_size(socket: TcpSocket, size: u32) -> io::Result<()> {
+    set_opt(socket, libc::SOL_SOCKET, libc::SO_SNDBUF, to_c_int(size))
+}
+
+pub(crate) fn get_send_buffer_size(socket: TcpSocket) -> io::Result<u32> {
+    get_opt(socket, libc::SOL_SOCKET, libc::SO_SNDBUF).map(|size| size as u32)
+}
+
+pub(crate) fn set_recv_buffer_size(socket: TcpSocket, size: u32) -> io::Result<()> {
+    set_opt(socket, libc::SOL_SOCKET, libc::SO_RCVBUF, to_c_int(size))
+}
+
+pub(crate) fn get_recv_buffer_size(socket: TcpSocket) -> io::Result<u32> {
+    get_opt(socket, libc::SOL_SOCKET, libc::SO_RCVBUF).map(|size| size as u32)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_bind_address_no_port(socket: TcpSocket, no_port: bool) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::IPPROTO_IP,
+        libc::IP_BIND_ADDRESS_NO_PORT,
+        no_port as libc::c_int,
+    )
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_bind_address_no_port(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, libc::IPPROTO_IP, libc::IP_BIND_ADDRESS_NO_PORT).map(|value| value != 0)
+}
+
+pub(crate) fn set_keepalive(socket: TcpSocket, keepalive: bool) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_KEEPALIVE,
+        keepalive as libc::c_int,
+    )
+}
+
+pub(crate) fn get_keepalive(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, libc::SOL_SOCKET, libc::SO_KEEPALIVE).map(|value| value != 0)
+}
+
+#[cfg(not(target_os = "openbsd"))]
+pub(crate) fn set_keepalive_params(socket: TcpSocket, keepalive: TcpKeepalive) -> io::Result<()> {
+    set_keepalive(socket, true)?;
+    if let Some(time) = keepalive.time {
+        set_opt(socket, libc::IPPROTO_TCP, KEEPALIVE_TIME, secs(time))?;
+    }
+    if let Some(interval) = keepalive.interval {
+        set_opt(
             socket,
-            libc::SOL_SOCKET,
-            libc::SO_REUSEADDR,
-            &1 as *const libc::c_int as *const libc::c_void,
-            size_of::<libc::c_int>() as libc::socklen_t,
-        ))
-        .and_then(|_| {
-            let (raw_addr, raw_addr_length) = socket_addr(&addr);
-            syscall!(bind(socket, raw_addr, raw_addr_length))
-        })
-        .and_then(|_| syscall!(listen(socket, 1024)))
-        .map_err(|err| {
-            // Close the socket if we hit an error, ignoring the error
-            // from closing since we can't pass back two errors.
-            let _ = unsafe { libc::close(socket) };
-            err
-        })
-        .map(|_| unsafe { net::TcpListener::from_raw_fd(socket) })
+            libc::IPPROTO_TCP,
+            libc::TCP_KEEPINTVL,
+            secs(interval),
+        )?;
+    }
+    if let Some(retries) = keepalive.retries {
+        set_opt(
+            socket,
+            libc::IPPROTO_TCP,
+            libc::TCP_KEEPCNT,
+            to_c_int(retries),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "openbsd"))]
+pub(crate) fn get_keepalive_params(socket: TcpSocket) -> io::Result<TcpKeepalive> {
+    let time = get_opt(socket, libc::IPPROTO_TCP, KEEPALIVE_TIME)?;
+    let interval = get_opt(socket, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL)?;
+    let retries = get_opt(socket, libc::IPPROTO_TCP, libc::TCP_KEEPCNT)?;
+    Ok(TcpKeepalive {
+        time: Some(Duration::from_secs(time as u64)),
+        interval: Some(Duration::from_secs(interval as u64)),
+        retries: Some(retries as u32),
     })
 }
 
+pub(crate) fn set_linger(socket: TcpSocket, linger: Option<Duration>) -> io::Result<()> {
+    let value = libc::linger {
+        l_onoff: linger.is_some() as libc::c_int,
+        l_linger: linger.map(secs).unwrap_or(0),
+    };
+    syscall!(setsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        LINGER,
+        &value as *const libc::linger as *const libc::c_void,
+        size_of::<libc::linger>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+pub(crate) fn get_linger(socket: TcpSocket) -> io::Result<Option<Duration>> {
+    let mut value = libc::linger {
+        l_onoff: 0,
+        l_linger: 0,
+    };
+    let mut length = size_of::<libc::linger>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        LINGER,
+        &mut value as *mut libc::linger as *mut libc::c_void,
+        &mut length,
+    ))?;
+    if value.l_onoff != 0 {
+        Ok(Some(Duration::from_secs(value.l_linger as u64)))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_user_timeout(socket: TcpSocket, timeout: Duration) -> io::Result<()> {
+    // Zero means the OS default, so round anything below a millisecond up.
+    let timeout = if timeout.as_millis() == 0 && timeout.subsec_nanos() != 0 {
+        1
+    } else {
+        timeout.as_millis().min(libc::c_int::max_value() as u128) as libc::c_int
+    };
+    set_opt(socket, libc::IPPROTO_TCP, TCP_USER_TIMEOUT, timeout)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_user_timeout(socket: TcpSocket) -> io::Result<Duration> {
+    get_opt(socket, libc::IPPROTO_TCP, TCP_USER_TIMEOUT)
+        .map(|timeout| Duration::from_millis(timeout as u64))
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) fn set_notsent_lowat(socket: TcpSocket, lowat: u32) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::IPPROTO_TCP,
+        TCP_NOTSENT_LOWAT,
+        to_c_int(lowat),
+    )
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) fn get_notsent_lowat(socket: TcpSocket) -> io::Result<u32> {
+    get_opt(socket, libc::IPPROTO_TCP, TCP_NOTSENT_LOWAT).map(|lowat| lowat as u32)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_quickack(socket: TcpSocket, quickack: bool) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_QUICKACK,
+        quickack as libc::c_int,
+    )
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_quickack(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, libc::IPPROTO_TCP, libc::TCP_QUICKACK).map(|value| value != 0)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_cork(socket: TcpSocket, cork: bool) -> io::Result<()> {
+    set_opt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_CORK,
+        cork as libc::c_int,
+    )
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_cork(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, libc::IPPROTO_TCP, libc::TCP_CORK).map(|value| value != 0)
+}
+
+pub(crate) fn set_recv_lowat(socket: TcpSocket, lowat: u32) -> io::Result<()> {
+    set_opt(socket, libc::SOL_SOCKET, libc::SO_RCVLOWAT, to_c_int(lowat))
+}
+
+pub(crate) fn get_recv_lowat(socket: TcpSocket) -> io::Result<u32> {
+    get_opt(socket, libc::SOL_SOCKET, libc::SO_RCVLOWAT).map(|lowat| lowat as u32)
+}
+
+pub(crate) fn get_recv_queue_len(socket: TcpSocket) -> io::Result<usize> {
+    crate::sys::unix::net::recv_queue_len(socket)
+}
+
+cfg_send_queue_len! {
+    pub(crate) fn get_send_queue_len(socket: TcpSocket) -> io::Result<usize> {
+        crate::sys::unix::net::send_queue_len(socket)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_tcp_info(socket: TcpSocket) -> io::Result<TcpInfo> {
+    let (info, length) = get_info::<tcp_info>(socket, libc::TCP_INFO)?;
+    let mss = info.tcpi_snd_mss;
+    Ok(TcpInfo {
+        state: match info.tcpi_state {
+            1 => TcpState::Established,
+            2 => TcpState::SynSent,
+            // 12 is `TCP_NEW_SYN_RECV`.
+            3 | 12 => TcpState::SynReceived,
+            4 => TcpState::FinWait1,
+            5 => TcpState::FinWait2,
+            6 => TcpState::TimeWait,
+            7 => TcpState::Closed,
+            8 => TcpState::CloseWait,
+            9 => TcpState::LastAck,
+            10 => TcpState::Listen,
+            11 => TcpState::Closing,
+            state => return Err(unknown_state(state)),
+        },
+        rtt: Duration::from_micros(info.tcpi_rtt.into()),
+        rtt_var: Duration::from_micros(info.tcpi_rttvar.into()),
+        rto: Duration::from_micros(info.tcpi_rto.into()),
+        snd_mss: mss,
+        snd_cwnd: info.tcpi_snd_cwnd.saturating_mul(mss),
+        retransmits: Some(info.tcpi_total_retrans),
+        unacked: Some(info.tcpi_unacked),
+        lost: Some(info.tcpi_lost),
+        min_rtt: if contains(&info, length, &info.tcpi_min_rtt) {
+            Some(Duration::from_micros(info.tcpi_min_rtt.into()))
+        } else {
+            None
+        },
+        delivery_rate: if contains(&info, length, &info.tcpi_delivery_rate) {
+            Some(info.tcpi_delivery_rate)
+        } else {
+            None
+        },
+        bytes_sent: if contains(&info, length, &info.tcpi_bytes_sent) {
+            Some(info.tcpi_bytes_sent)
+        } else {
+            None
+        },
+        bytes_received: if contains(&info, length, &info.tcpi_bytes_received) {
+            Some(info.tcpi_bytes_received)
+        } else {
+            None
+        },
+    })
+}
+
+#[cfg(target_os = "freebsd")]
+pub(crate) fn get_tcp_info(socket: TcpSocket) -> io::Result<TcpInfo> {
+    let (info, _) = get_info::<tcp_info>(socket, libc::TCP_INFO)?;
+    Ok(TcpInfo {
+        state: bsd_state(info.tcpi_state)?,
+        rtt: Duration::from_micros(info.tcpi_rtt.into()),
+        rtt_var: Duration::from_micros(info.tcpi_rttvar.into()),
+        rto: Duration::from_micros(info.tcpi_rto.into()),
+        snd_mss: info.tcpi_snd_mss,
+        snd_cwnd: info.tcpi_snd_cwnd,
+        retransmits: Some(info.tcpi_snd_rexmitpack),
+        unacked: None,
+        lost: None,
+        min_rtt: None,
+        delivery_rate: None,
+        bytes_sent: None,
+        bytes_received: None,
+    })
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub(crate) fn get_tcp_info(socket: TcpSocket) -> io::Result<TcpInfo> {
+    let (info, _) = get_info::<tcp_connection_info>(socket, TCP_CONNECTION_INFO)?;
+    Ok(TcpInfo {
+        state: bsd_state(info.tcpi_state)?,
+        rtt: Duration::from_millis(info.tcpi_srtt.into()),
+        rtt_var: Duration::from_millis(info.tcpi_rttvar.into()),
+        rto: Duration::from_millis(info.tcpi_rto.into()),
+        snd_mss: info.tcpi_maxseg,
+        snd_cwnd: info.tcpi_snd_cwnd,
+        retransmits: None,
+        unacked: None,
+        lost: None,
+        min_rtt: None,
+        delivery_rate: None,
+        bytes_sent: Some(info.tcpi_txbytes),
+        bytes_received: Some(info.tcpi_rxbytes),
+    })
+}
+
+/// Returns `true` if the first `length` bytes of `info` include `field`.
+///
+/// Older kernels return a prefix of `tcp_info`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn contains<T, F>(info: &T, length: usize, field: &F) -> bool {
+    let end = field as *const F as usize - info as *const T as usize + size_of::<F>();
+    length >= end
+}
+
+/// Converts the `TCPS_*` states used by BSD derived OSes.
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+fn bsd_state(state: u8) -> io::Result<TcpState> {
+    Ok(match state {
+        0 => TcpState::Closed,
+        1 => TcpState::Listen,
+        2 => TcpState::SynSent,
+        3 => TcpState::SynReceived,
+        4 => TcpState::Established,
+        5 => TcpState::CloseWait,
+        6 => TcpState::FinWait1,
+        7 => TcpState::Closing,
+        8 => TcpState::LastAck,
+        9 => TcpState::FinWait2,
+        10 => TcpState::TimeWait,
+        state => return Err(unknown_state(state)),
+    })
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn unknown_state(state: u8) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unknown TCP state: {}", state),
+    )
+}
+
+/// Returns the `T` returned by `getsockopt(IPPROTO_TCP, name)`, and the
+/// length the OS filled in.
+///
+/// `T` must be a C struct of integers.
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn get_info<T>(socket: TcpSocket, name: libc::c_int) -> io::Result<(T, usize)> {
+    // This is safe as `T` only contains integers, for which zero is a valid
+    // value.
+    let mut info: T = unsafe { std::mem::zeroed() };
+    let mut length = size_of::<T>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        name,
+        &mut info as *mut T as *mut libc::c_void,
+        &mut length,
+    ))?;
+    Ok((info, length as usize))
+}
+
+/// `struct tcp_info` as defined in `linux/tcp.h` (up to Linux 4.19).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[allow(dead_code, non_camel_case_types)]
+#[repr(C)]
+struct tcp_info {
+    tcpi_state: u8,
+    tcpi_ca_state: u8,
+    tcpi_retransmits: u8,
+    tcpi_probes: u8,
+    tcpi_backoff: u8,
+    tcpi_options: u8,
+    tcpi_wscale: u8,
+    tcpi_flags: u8,
+    tcpi_rto: u32,
+    tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+    tcpi_unacked: u32,
+    tcpi_sacked: u32,
+    tcpi_lost: u32,
+    tcpi_retrans: u32,
+    tcpi_fackets: u32,
+    tcpi_last_data_sent: u32,
+    tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    tcpi_last_ack_recv: u32,
+    tcpi_pmtu: u32,
+    tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_advmss: u32,
+    tcpi_reordering: u32,
+    tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+    tcpi_total_retrans: u32,
+    // Linux 4.1.
+    tcpi_pacing_rate: u64,
+    tcpi_max_pacing_rate: u64,
+    tcpi_bytes_acked: u64,
+    tcpi_bytes_received: u64,
+    tcpi_segs_out: u32,
+    tcpi_segs_in: u32,
+    // Linux 4.6.
+    tcpi_notsent_bytes: u32,
+    tcpi_min_rtt: u32,
+    tcpi_data_segs_in: u32,
+    tcpi_data_segs_out: u32,
+    // Linux 4.9.
+    tcpi_delivery_rate: u64,
+    // Linux 4.10.
+    tcpi_busy_time: u64,
+    tcpi_rwnd_limited: u64,
+    tcpi_sndbuf_limited: u64,
+    // Linux 4.18.
+    tcpi_delivered: u32,
+    tcpi_delivered_ce: u32,
+    // Linux 4.19.
+    tcpi_bytes_sent: u64,
+    tcpi_bytes_retrans: u64,
+    tcpi_dsack_dups: u32,
+    tcpi_reord_seen: u32,
+}
+
+/// `struct tcp_info` as defined in `netinet/tcp.h`, without the trailing
+/// padding.
+#[cfg(target_os = "freebsd")]
+#[allow(dead_code, non_camel_case_types)]
+#[repr(C)]
+struct tcp_info {
+    tcpi_state: u8,
+    __tcpi_ca_state: u8,
+    __tcpi_retransmits: u8,
+    __tcpi_probes: u8,
+    __tcpi_backoff: u8,
+    tcpi_options: u8,
+    tcpi_wscale: u8,
+    __tcpi_flags: u8,
+    tcpi_rto: u32,
+    __tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+    __tcpi_unacked: u32,
+    __tcpi_sacked: u32,
+    __tcpi_lost: u32,
+    __tcpi_retrans: u32,
+    __tcpi_fackets: u32,
+    __tcpi_last_data_sent: u32,
+    __tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    __tcpi_last_ack_recv: u32,
+    __tcpi_pmtu: u32,
+    __tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    __tcpi_advmss: u32,
+    __tcpi_reordering: u32,
+    __tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+    tcpi_snd_wnd: u32,
+    tcpi_snd_bwnd: u32,
+    tcpi_snd_nxt: u32,
+    tcpi_rcv_nxt: u32,
+    tcpi_toe_tid: u32,
+    tcpi_snd_rexmitpack: u32,
+    tcpi_rcv_ooopack: u32,
+    tcpi_snd_zerowin: u32,
+}
+
+// Not defined by all libc versions we support.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const TCP_CONNECTION_INFO: libc::c_int = 0x106;
+
+/// `struct tcp_connection_info` as defined in `netinet/tcp.h`.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+#[allow(dead_code, non_camel_case_types)]
+#[repr(C)]
+struct tcp_connection_info {
+    tcpi_state: u8,
+    tcpi_snd_wscale: u8,
+    tcpi_rcv_wscale: u8,
+    __pad1: u8,
+    tcpi_options: u32,
+    tcpi_flags: u32,
+    tcpi_rto: u32,
+    tcpi_maxseg: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_snd_wnd: u32,
+    tcpi_snd_sbbytes: u32,
+    tcpi_rcv_wnd: u32,
+    tcpi_rttcur: u32,
+    tcpi_srtt: u32,
+    tcpi_rttvar: u32,
+    /// TCP Fast Open bit fields.
+    tcpi_tfo: u32,
+    tcpi_txpackets: u64,
+    tcpi_txbytes: u64,
+    tcpi_txretransmitbytes: u64,
+    tcpi_rxpackets: u64,
+    tcpi_rxbytes: u64,
+    tcpi_rxoutoforderbytes: u64,
+    tcpi_txretransmitpackets: u64,
+}
+
+/// Converts `duration` to whole seconds, capped at `c_int::MAX`. Durations
+/// shorter than a second (other than zero) are rounded up to one second, as
+/// zero has a different meaning or is invalid.
+fn secs(duration: Duration) -> libc::c_int {
+    if duration.as_secs() == 0 && duration.subsec_nanos() != 0 {
+        return 1;
+    }
+    duration.as_secs().min(libc::c_int::max_value() as u64) as libc::c_int
+}
+
+pub(crate) fn get_localaddr(socket: TcpSocket) -> io::Result<SocketAddr> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut length = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(getsockname(
+        socket,
+        addr.as_mut_ptr() as *mut _,
+        &mut length
+    ))?;
+    // This is safe because `getsockname` initialised the address.
+    unsafe { to_socket_addr(addr.as_ptr()) }
+}
+
+/// Converts `value` to a `c_int`, capped at `c_int::MAX`. The OS caps sizes
+/// at a (much) lower value anyway.
+fn to_c_int(value: u32) -> libc::c_int {
+    use std::convert::TryInto;
+
+    value.try_into().unwrap_or(libc::c_int::max_value())
+}
+
+fn set_opt(
+    socket: TcpSocket,
+    level: libc::c_int,
+    name: libc::c_int,
+    value: libc::c_int,
+) -> io::Result<()> {
+    syscall!(setsockopt(
+        socket,
+        level,
+        name,
+        &value as *const libc::c_int as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+fn get_opt(socket: TcpSocket, level: libc::c_int, name: libc::c_int) -> io::Result<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut length = size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        level,
+        name,
+        &mut value as *mut libc::c_int as *mut libc::c_void,
+        &mut length,
+    ))
+    .map(|_| value)
+}
+
 pub fn accept(listener: &net::TcpListener) -> io::Result<(net::TcpStream, SocketAddr)> {
     let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
     let mut length = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
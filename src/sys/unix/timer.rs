@@ -0,0 +1,67 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::time::Duration;
+use std::{cmp, mem, ptr};
+
+pub fn new(clock: libc::clockid_t) -> io::Result<File> {
+    syscall!(timerfd_create(
+        clock,
+        libc::TFD_CLOEXEC | libc::TFD_NONBLOCK
+    ))
+    .map(|fd| unsafe { File::from_raw_fd(fd) })
+}
+
+/// Arm (or disarm if `value` is zero) `timer`, see `timerfd_settime(2)`.
+pub fn set(timer: &File, value: Duration, interval: Duration, absolute: bool) -> io::Result<()> {
+    let flags = if absolute { libc::TFD_TIMER_ABSTIME } else { 0 };
+    let value = libc::itimerspec {
+        it_interval: to_timespec(interval),
+        it_value: to_timespec(value),
+    };
+    syscall!(timerfd_settime(
+        timer.as_raw_fd(),
+        flags,
+        &value,
+        ptr::null_mut()
+    ))
+    .map(|_| ())
+}
+
+/// Returns the time until the next expiration of `timer`, or `None` if it's
+/// disarmed.
+pub fn remaining(timer: &File) -> io::Result<Option<Duration>> {
+    let mut value: libc::itimerspec = unsafe { mem::zeroed() };
+    syscall!(timerfd_gettime(timer.as_raw_fd(), &mut value)).map(|_| {
+        let remaining = from_timespec(value.it_value);
+        if remaining == Duration::from_secs(0) {
+            None
+        } else {
+            Some(remaining)
+        }
+    })
+}
+
+/// Returns the number of expirations since the last read, or a `WouldBlock`
+/// error if the timer hasn't expired.
+pub fn read(mut timer: &File) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    timer.read_exact(&mut buf).map(|()| u64::from_ne_bytes(buf))
+}
+
+pub fn now(clock: libc::clockid_t) -> io::Result<Duration> {
+    let mut now: libc::timespec = unsafe { mem::zeroed() };
+    syscall!(clock_gettime(clock, &mut now)).map(|_| from_timespec(now))
+}
+
+fn to_timespec(duration: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: cmp::min(duration.as_secs(), libc::time_t::max_value() as u64) as libc::time_t,
+        // The cast is needed for platforms where C's long is only 32 bits.
+        tv_nsec: libc::c_long::from(duration.subsec_nanos() as i32),
+    }
+}
+
+fn from_timespec(timespec: libc::timespec) -> Duration {
+    Duration::new(timespec.tv_sec as u64, timespec.tv_nsec as u32)
+}
@@ -2,7 +2,7 @@ use crate::sys::unix::net::{new_ip_socket, socket_addr};
 
 use std::io;
 use std::net::{self, SocketAddr};
-use std::os::unix::io::FromRawFd;
+use std::os::unix::io::{AsRawFd, FromRawFd};
 
 pub fn bind(addr: SocketAddr) -> io::Result<net::UdpSocket> {
     // Gives a warning for non Apple platforms.
@@ -24,7 +24,7 @@ pub fn bind(addr: SocketAddr) -> io::Result<net::UdpSocket> {
 
     socket.and_then(|socket| {
         let (raw_addr, raw_addr_length) = socket_addr(&addr);
-        syscall!(bind(socket, raw_addr, raw_addr_length))
+        syscall!(bind(socket, raw_addr.as_ptr(), raw_addr_length))
             .map_err(|err| {
                 // Close the socket if we hit an error, ignoring the error
                 // from closing since we can't pass back two errors.
@@ -34,3 +34,13 @@ pub fn bind(addr: SocketAddr) -> io::Result<net::UdpSocket> {
             .map(|_| unsafe { net::UdpSocket::from_raw_fd(socket) })
     })
 }
+
+pub(crate) fn recv_queue_len(socket: &net::UdpSocket) -> io::Result<usize> {
+    crate::sys::unix::net::recv_queue_len(socket.as_raw_fd())
+}
+
+cfg_send_queue_len! {
+    pub(crate) fn send_queue_len(socket: &net::UdpSocket) -> io::Result<usize> {
+        crate::sys::unix::net::send_queue_len(socket.as_raw_fd())
+    }
+}
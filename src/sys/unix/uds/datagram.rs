@@ -54,3 +54,13 @@ pub(crate) fn recv_from(
     })?;
     Ok((count as usize, socketaddr))
 }
+
+pub(crate) fn recv_queue_len(socket: &net::UnixDatagram) -> io::Result<usize> {
+    crate::sys::unix::net::recv_queue_len(socket.as_raw_fd())
+}
+
+cfg_send_queue_len! {
+    pub(crate) fn send_queue_len(socket: &net::UnixDatagram) -> io::Result<usize> {
+        crate::sys::unix::net::send_queue_len(socket.as_raw_fd())
+    }
+}
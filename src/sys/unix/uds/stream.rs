@@ -37,3 +37,13 @@ pub(crate) fn local_addr(socket: &net::UnixStream) -> io::Result<SocketAddr> {
 pub(crate) fn peer_addr(socket: &net::UnixStream) -> io::Result<SocketAddr> {
     super::peer_addr(socket.as_raw_fd())
 }
+
+pub(crate) fn recv_queue_len(socket: &net::UnixStream) -> io::Result<usize> {
+    crate::sys::unix::net::recv_queue_len(socket.as_raw_fd())
+}
+
+cfg_send_queue_len! {
+    pub(crate) fn send_queue_len(socket: &net::UnixStream) -> io::Result<usize> {
+        crate::sys::unix::net::send_queue_len(socket.as_raw_fd())
+    }
+}
@@ -1,4 +1,7 @@
-#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    not(feature = "poll-fallback")
+))]
 mod eventfd {
     use crate::sys::Selector;
     use crate::{Interest, Token};
@@ -58,10 +61,16 @@ mod eventfd {
     }
 }
 
-#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    not(feature = "poll-fallback")
+))]
 pub use self::eventfd::Waker;
 
-#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+#[cfg(all(
+    any(target_os = "freebsd", target_os = "ios", target_os = "macos"),
+    not(feature = "poll-fallback")
+))]
 mod kqueue {
     use crate::sys::Selector;
     use crate::Token;
@@ -95,14 +104,20 @@ mod kqueue {
     }
 }
 
-#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+#[cfg(all(
+    any(target_os = "freebsd", target_os = "ios", target_os = "macos"),
+    not(feature = "poll-fallback")
+))]
 pub use self::kqueue::Waker;
 
-#[cfg(any(
-    target_os = "dragonfly",
-    target_os = "netbsd",
-    target_os = "openbsd",
-    target_os = "solaris"
+#[cfg(all(
+    any(
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "solaris"
+    ),
+    not(feature = "poll-fallback")
 ))]
 mod pipe {
     use crate::sys::unix::Selector;
@@ -163,10 +178,46 @@ mod pipe {
     }
 }
 
-#[cfg(any(
-    target_os = "dragonfly",
-    target_os = "netbsd",
-    target_os = "openbsd",
-    target_os = "solaris"
+#[cfg(all(
+    any(
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "solaris"
+    ),
+    not(feature = "poll-fallback")
 ))]
 pub use self::pipe::Waker;
+
+#[cfg(feature = "poll-fallback")]
+mod poll {
+    use crate::sys::Selector;
+    use crate::Token;
+
+    use std::io;
+
+    /// Waker backed by the self-pipe of the `poll(2)` selector.
+    ///
+    /// Waking records the token in the selector, which returns it as a
+    /// readable event on the next call to `select`.
+    #[derive(Debug)]
+    pub struct Waker {
+        selector: Selector,
+        token: Token,
+    }
+
+    impl Waker {
+        pub fn new(selector: &Selector, token: Token) -> io::Result<Waker> {
+            selector
+                .try_clone()
+                .map(|selector| Waker { selector, token })
+        }
+
+        pub fn wake(&self) -> io::Result<()> {
+            self.selector.wake(self.token)
+        }
+    }
+}
+
+#[cfg(feature = "poll-fallback")]
+pub use self::poll::Waker;
@@ -1,14 +1,13 @@
 use std::io;
-use std::mem::size_of_val;
+use std::mem;
 use std::net::SocketAddr;
-#[cfg(all(feature = "os-poll", feature = "tcp"))]
-use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
 use std::sync::Once;
 
 use winapi::ctypes::c_int;
-use winapi::shared::ws2def::SOCKADDR;
+use winapi::shared::ws2def::{ADDRESS_FAMILY, AF_INET, AF_INET6, SOCKADDR, SOCKADDR_IN};
+use winapi::shared::ws2ipdef::SOCKADDR_IN6_LH;
 use winapi::um::winsock2::{
-    ioctlsocket, socket, FIONBIO, INVALID_SOCKET, PF_INET, PF_INET6, SOCKET,
+    ioctlsocket, socket, u_long, FIONBIO, FIONREAD, INVALID_SOCKET, SOCKET,
 };
 
 /// Initialise the network stack for Windows.
@@ -23,12 +22,17 @@ pub(crate) fn init() {
 }
 
 /// Create a new non-blocking socket.
-pub(crate) fn new_socket(addr: SocketAddr, socket_type: c_int) -> io::Result<SOCKET> {
+#[cfg(feature = "udp")]
+pub(crate) fn new_ip_socket(addr: SocketAddr, socket_type: c_int) -> io::Result<SOCKET> {
     let domain = match addr {
-        SocketAddr::V4(..) => PF_INET,
-        SocketAddr::V6(..) => PF_INET6,
+        SocketAddr::V4(..) => AF_INET,
+        SocketAddr::V6(..) => AF_INET6,
     };
 
+    new_socket(domain, socket_type)
+}
+
+pub(crate) fn new_socket(domain: c_int, socket_type: c_int) -> io::Result<SOCKET> {
     syscall!(
         socket(domain, socket_type, 0),
         PartialEq::eq,
@@ -39,31 +43,63 @@ pub(crate) fn new_socket(addr: SocketAddr, socket_type: c_int) -> io::Result<SOC
     })
 }
 
-pub(crate) fn socket_addr(addr: &SocketAddr) -> (*const SOCKADDR, c_int) {
-    match addr {
-        SocketAddr::V4(ref addr) => (
-            addr as *const _ as *const SOCKADDR,
-            size_of_val(addr) as c_int,
-        ),
-        SocketAddr::V6(ref addr) => (
-            addr as *const _ as *const SOCKADDR,
-            size_of_val(addr) as c_int,
-        ),
+/// Returns the number of bytes in the receive queue of `socket`, using
+/// `ioctlsocket(FIONREAD)`.
+pub(crate) fn recv_queue_len(socket: SOCKET) -> io::Result<usize> {
+    let mut len: u_long = 0;
+    syscall!(ioctlsocket(socket, FIONREAD, &mut len), PartialEq::ne, 0).map(|_| len as usize)
+}
+
+/// A type with the same memory layout as `SOCKADDR`, used to convert a
+/// `SocketAddr` into its system representation.
+///
+/// The layout of the `SocketAddrV4` and `SocketAddrV6` types in the standard
+/// library is unspecified, so they can't be passed to the system directly.
+#[repr(C)]
+pub(crate) union SocketAddrCRepr {
+    v4: SOCKADDR_IN,
+    v6: SOCKADDR_IN6_LH,
+}
+
+impl SocketAddrCRepr {
+    pub(crate) fn as_ptr(&self) -> *const SOCKADDR {
+        self as *const _ as *const SOCKADDR
     }
 }
 
-#[cfg(all(feature = "os-poll", feature = "tcp"))]
-pub(crate) fn inaddr_any(other: SocketAddr) -> SocketAddr {
-    match other {
-        SocketAddr::V4(..) => {
-            let any = Ipv4Addr::new(0, 0, 0, 0);
-            let addr = SocketAddrV4::new(any, 0);
-            SocketAddr::V4(addr)
+/// Converts a Rust `SocketAddr` into the system representation.
+pub(crate) fn socket_addr(addr: &SocketAddr) -> (SocketAddrCRepr, c_int) {
+    match addr {
+        SocketAddr::V4(ref addr) => {
+            // This is safe as all fields are integers, for which zero is a
+            // valid value.
+            let mut sockaddr: SOCKADDR_IN = unsafe { mem::zeroed() };
+            sockaddr.sin_family = AF_INET as ADDRESS_FAMILY;
+            sockaddr.sin_port = addr.port().to_be();
+            // `S_addr` is stored in network byte order, which is the same order
+            // as the octets.
+            unsafe {
+                *sockaddr.sin_addr.S_un.S_addr_mut() = u32::from_ne_bytes(addr.ip().octets());
+            }
+            (
+                SocketAddrCRepr { v4: sockaddr },
+                mem::size_of::<SOCKADDR_IN>() as c_int,
+            )
         }
-        SocketAddr::V6(..) => {
-            let any = Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0);
-            let addr = SocketAddrV6::new(any, 0, 0, 0);
-            SocketAddr::V6(addr)
+        SocketAddr::V6(ref addr) => {
+            // Safe for the same reason as above.
+            let mut sockaddr: SOCKADDR_IN6_LH = unsafe { mem::zeroed() };
+            sockaddr.sin6_family = AF_INET6 as ADDRESS_FAMILY;
+            sockaddr.sin6_port = addr.port().to_be();
+            sockaddr.sin6_flowinfo = addr.flowinfo();
+            unsafe {
+                *sockaddr.sin6_addr.u.Byte_mut() = addr.ip().octets();
+                *sockaddr.u.sin6_scope_id_mut() = addr.scope_id();
+            }
+            (
+                SocketAddrCRepr { v6: sockaddr },
+                mem::size_of::<SOCKADDR_IN6_LH>() as c_int,
+            )
         }
     }
 }
@@ -10,7 +10,7 @@ use std::collections::VecDeque;
 use std::marker::PhantomPinned;
 use std::os::windows::io::RawSocket;
 use std::pin::Pin;
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
@@ -322,7 +322,7 @@ fn from_overlapped(ptr: *mut OVERLAPPED) -> Pin<Arc<Mutex<SockState>>> {
 /// registered with the `Selector`. If a type that is previously associated with
 /// a `Selector` attempts to register itself with a different `Selector`, the
 /// operation will return with an error. This matches windows behavior.
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
 /// Windows implementaion of `sys::Selector`
@@ -333,7 +333,7 @@ static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 /// This selector is currently only support socket due to `Afd` driver is winsock2 specific.
 #[derive(Debug)]
 pub struct Selector {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "registration-checks"))]
     id: usize,
 
     inner: Arc<SelectorInner>,
@@ -342,10 +342,10 @@ pub struct Selector {
 impl Selector {
     pub fn new() -> io::Result<Selector> {
         SelectorInner::new().map(|inner| {
-            #[cfg(debug_assertions)]
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
             let id = NEXT_ID.fetch_add(1, Ordering::Relaxed) + 1;
             Selector {
-                #[cfg(debug_assertions)]
+                #[cfg(any(debug_assertions, feature = "registration-checks"))]
                 id,
                 inner: Arc::new(inner),
             }
@@ -354,7 +354,7 @@ impl Selector {
 
     pub fn try_clone(&self) -> io::Result<Selector> {
         Ok(Selector {
-            #[cfg(debug_assertions)]
+            #[cfg(any(debug_assertions, feature = "registration-checks"))]
             id: self.id,
             inner: Arc::clone(&self.inner),
         })
@@ -396,7 +396,7 @@ cfg_net! {
             self.inner.reregister(state, token, interests)
         }
 
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "registration-checks"))]
         pub fn id(&self) -> usize {
             self.id
         }
@@ -1,70 +1,248 @@
+use std::convert::TryInto;
 use std::io;
+use std::mem::{size_of, ManuallyDrop};
 use std::net::{self, SocketAddr};
 use std::os::windows::io::FromRawSocket;
 use std::os::windows::raw::SOCKET as StdSocket; // winapi uses usize, stdlib uses u32/u64.
+use std::time::Duration;
 
+use winapi::ctypes::{c_char, c_int};
+use winapi::shared::ws2def::{
+    AF_INET, AF_INET6, IPPROTO_IPV6, IPPROTO_TCP, SOL_SOCKET, SO_KEEPALIVE, SO_LINGER, SO_RCVBUF,
+    SO_REUSEADDR, SO_SNDBUF,
+};
+use winapi::shared::ws2ipdef::IPV6_V6ONLY;
 use winapi::um::winsock2::{
-    bind as win_bind, closesocket, connect as win_connect, listen, SOCKET_ERROR, SOCK_STREAM,
+    bind as win_bind, closesocket, connect as win_connect, getsockopt, listen as win_listen,
+    setsockopt, u_short, LINGER, SOCKET, SOCKET_ERROR, SOCK_STREAM,
 };
 
-use crate::sys::windows::net::{inaddr_any, init, new_socket, socket_addr};
+use crate::net::TcpKeepalive;
+use crate::sys::windows::net::{init, new_socket, recv_queue_len, socket_addr};
+
+// Keepalive options, available since Windows 10 version 1709. Not (yet)
+// defined by winapi.
+const TCP_KEEPALIVE: c_int = 3;
+const TCP_KEEPCNT: c_int = 16;
+const TCP_KEEPINTVL: c_int = 17;
 
-pub fn connect(addr: SocketAddr) -> io::Result<net::TcpStream> {
+pub(crate) type TcpSocket = SOCKET;
+
+pub(crate) fn new_v4_socket() -> io::Result<TcpSocket> {
     init();
-    new_socket(addr, SOCK_STREAM)
-        .and_then(|socket| {
-            // Required for a future `connect_overlapped` operation to be
-            // executed successfully.
-            let any_addr = inaddr_any(addr);
-            let (raw_addr, raw_addr_length) = socket_addr(&any_addr);
-            syscall!(
-                win_bind(socket, raw_addr, raw_addr_length),
-                PartialEq::eq,
-                SOCKET_ERROR
-            )
-            .and_then(|_| {
-                let (raw_addr, raw_addr_length) = socket_addr(&addr);
-                syscall!(
-                    win_connect(socket, raw_addr, raw_addr_length),
-                    PartialEq::eq,
-                    SOCKET_ERROR
-                )
-                .or_else(|err| match err {
-                    ref err if err.kind() == io::ErrorKind::WouldBlock => Ok(0),
-                    err => Err(err),
-                })
-            })
-            .map(|_| socket)
-            .map_err(|err| {
-                // Close the socket if we hit an error, ignoring the error
-                // from closing since we can't pass back two errors.
-                let _ = unsafe { closesocket(socket) };
-                err
-            })
-        })
-        .map(|socket| unsafe { net::TcpStream::from_raw_socket(socket as StdSocket) })
-}
-
-pub fn bind(addr: SocketAddr) -> io::Result<net::TcpListener> {
+    new_socket(AF_INET, SOCK_STREAM)
+}
+
+pub(crate) fn new_v6_socket() -> io::Result<TcpSocket> {
     init();
-    new_socket(addr, SOCK_STREAM).and_then(|socket| {
-        let (raw_addr, raw_addr_length) = socket_addr(&addr);
-        syscall!(
-            win_bind(socket, raw_addr, raw_addr_length,),
-            PartialEq::eq,
-            SOCKET_ERROR
-        )
-        .and_then(|_| syscall!(listen(socket, 1024), PartialEq::eq, SOCKET_ERROR))
-        .map_err(|err| {
-            // Close the socket if we hit an error, ignoring the error
-            // from closing since we can't pass back two errors.
-            let _ = unsafe { closesocket(socket) };
-            err
-        })
-        .map(|_| unsafe { net::TcpListener::from_raw_socket(socket as StdSocket) })
+    new_socket(AF_INET6, SOCK_STREAM)
+}
+
+pub(crate) fn bind(socket: TcpSocket, addr: SocketAddr) -> io::Result<()> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    syscall!(
+        win_bind(socket, raw_addr.as_ptr(), raw_addr_length),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )
+    .map(|_| ())
+}
+
+pub(crate) fn connect(socket: TcpSocket, addr: SocketAddr) -> io::Result<net::TcpStream> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    match syscall!(
+        win_connect(socket, raw_addr.as_ptr(), raw_addr_length),
+        PartialEq::eq,
+        SOCKET_ERROR
+    ) {
+        // Connect hasn't finished, but that is fine.
+        Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+        Err(err) => return Err(err),
+        Ok(_) => {}
+    }
+    Ok(unsafe { net::TcpStream::from_raw_socket(socket as StdSocket) })
+}
+
+pub(crate) fn listen(socket: TcpSocket, backlog: u32) -> io::Result<net::TcpListener> {
+    let backlog = backlog.try_into().unwrap_or(c_int::max_value());
+    syscall!(win_listen(socket, backlog), PartialEq::eq, SOCKET_ERROR)?;
+    Ok(unsafe { net::TcpListener::from_raw_socket(socket as StdSocket) })
+}
+
+pub(crate) fn close(socket: TcpSocket) {
+    let _ = unsafe { closesocket(socket) };
+}
+
+pub(crate) fn set_reuseaddr(socket: TcpSocket, reuseaddr: bool) -> io::Result<()> {
+    set_opt(socket, SOL_SOCKET, SO_REUSEADDR, reuseaddr as c_int)
+}
+
+pub(crate) fn get_reuseaddr(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, SOL_SOCKET, SO_REUSEADDR).map(|value| value != 0)
+}
+
+pub(crate) fn set_only_v6(socket: TcpSocket, only_v6: bool) -> io::Result<()> {
+    set_opt(socket, IPPROTO_IPV6 as c_int, IPV6_V6ONLY, only_v6 as c_int)
+}
+
+pub(crate) fn get_only_v6(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, IPPROTO_IPV6 as c_int, IPV6_V6ONLY).map(|value| value != 0)
+}
+
+pub(crate) fn set_send_buffer_size(socket: TcpSocket, size: u32) -> io::Result<()> {
+    set_opt(socket, SOL_SOCKET, SO_SNDBUF, to_c_int(size))
+}
+
+pub(crate) fn get_send_buffer_size(socket: TcpSocket) -> io::Result<u32> {
+    get_opt(socket, SOL_SOCKET, SO_SNDBUF).map(|size| size as u32)
+}
+
+pub(crate) fn set_recv_buffer_size(socket: TcpSocket, size: u32) -> io::Result<()> {
+    set_opt(socket, SOL_SOCKET, SO_RCVBUF, to_c_int(size))
+}
+
+pub(crate) fn get_recv_buffer_size(socket: TcpSocket) -> io::Result<u32> {
+    get_opt(socket, SOL_SOCKET, SO_RCVBUF).map(|size| size as u32)
+}
+
+pub(crate) fn set_keepalive(socket: TcpSocket, keepalive: bool) -> io::Result<()> {
+    set_opt(socket, SOL_SOCKET, SO_KEEPALIVE, keepalive as c_int)
+}
+
+pub(crate) fn get_keepalive(socket: TcpSocket) -> io::Result<bool> {
+    get_opt(socket, SOL_SOCKET, SO_KEEPALIVE).map(|value| value != 0)
+}
+
+pub(crate) fn set_keepalive_params(socket: TcpSocket, keepalive: TcpKeepalive) -> io::Result<()> {
+    set_keepalive(socket, true)?;
+    if let Some(time) = keepalive.time {
+        set_opt(socket, IPPROTO_TCP as c_int, TCP_KEEPALIVE, secs(time))?;
+    }
+    if let Some(interval) = keepalive.interval {
+        set_opt(socket, IPPROTO_TCP as c_int, TCP_KEEPINTVL, secs(interval))?;
+    }
+    if let Some(retries) = keepalive.retries {
+        set_opt(socket, IPPROTO_TCP as c_int, TCP_KEEPCNT, to_c_int(retries))?;
+    }
+    Ok(())
+}
+
+pub(crate) fn get_keepalive_params(socket: TcpSocket) -> io::Result<TcpKeepalive> {
+    let time = get_opt(socket, IPPROTO_TCP as c_int, TCP_KEEPALIVE)?;
+    let interval = get_opt(socket, IPPROTO_TCP as c_int, TCP_KEEPINTVL)?;
+    let retries = get_opt(socket, IPPROTO_TCP as c_int, TCP_KEEPCNT)?;
+    Ok(TcpKeepalive {
+        time: Some(Duration::from_secs(time as u64)),
+        interval: Some(Duration::from_secs(interval as u64)),
+        retries: Some(retries as u32),
     })
 }
 
+pub(crate) fn set_linger(socket: TcpSocket, linger: Option<Duration>) -> io::Result<()> {
+    let value = LINGER {
+        l_onoff: linger.is_some() as u_short,
+        l_linger: linger
+            .map(|linger| secs(linger).min(u_short::max_value() as c_int) as u_short)
+            .unwrap_or(0),
+    };
+    syscall!(
+        setsockopt(
+            socket,
+            SOL_SOCKET,
+            SO_LINGER,
+            &value as *const LINGER as *const c_char,
+            size_of::<LINGER>() as c_int,
+        ),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )
+    .map(|_| ())
+}
+
+pub(crate) fn get_linger(socket: TcpSocket) -> io::Result<Option<Duration>> {
+    let mut value = LINGER {
+        l_onoff: 0,
+        l_linger: 0,
+    };
+    let mut length = size_of::<LINGER>() as c_int;
+    syscall!(
+        getsockopt(
+            socket,
+            SOL_SOCKET,
+            SO_LINGER,
+            &mut value as *mut LINGER as *mut c_char,
+            &mut length,
+        ),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )?;
+    if value.l_onoff != 0 {
+        Ok(Some(Duration::from_secs(u64::from(value.l_linger))))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Converts `duration` to whole seconds, capped at `c_int::MAX`. Durations
+/// shorter than a second (other than zero) are rounded up to one second, as
+/// zero has a different meaning or is invalid.
+fn secs(duration: Duration) -> c_int {
+    if duration.as_secs() == 0 && duration.subsec_nanos() != 0 {
+        return 1;
+    }
+    duration.as_secs().min(c_int::max_value() as u64) as c_int
+}
+
+pub(crate) fn get_recv_queue_len(socket: TcpSocket) -> io::Result<usize> {
+    recv_queue_len(socket)
+}
+
+pub(crate) fn get_localaddr(socket: TcpSocket) -> io::Result<SocketAddr> {
+    // Use the address conversion of the standard library, making sure the
+    // socket isn't closed afterwards.
+    let listener =
+        ManuallyDrop::new(unsafe { net::TcpListener::from_raw_socket(socket as StdSocket) });
+    listener.local_addr()
+}
+
+/// Converts `value` to a `c_int`, capped at `c_int::MAX`. The OS caps sizes
+/// at a (much) lower value anyway.
+fn to_c_int(value: u32) -> c_int {
+    value.try_into().unwrap_or(c_int::max_value())
+}
+
+fn set_opt(socket: TcpSocket, level: c_int, name: c_int, value: c_int) -> io::Result<()> {
+    syscall!(
+        setsockopt(
+            socket,
+            level,
+            name,
+            &value as *const c_int as *const c_char,
+            size_of::<c_int>() as c_int,
+        ),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )
+    .map(|_| ())
+}
+
+fn get_opt(socket: TcpSocket, level: c_int, name: c_int) -> io::Result<c_int> {
+    let mut value: c_int = 0;
+    let mut length = size_of::<c_int>() as c_int;
+    syscall!(
+        getsockopt(
+            socket,
+            level,
+            name,
+            &mut value as *mut c_int as *mut c_char,
+            &mut length,
+        ),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )
+    .map(|_| value)
+}
+
 pub fn accept(listener: &net::TcpListener) -> io::Result<(net::TcpStream, SocketAddr)> {
     // The non-blocking state of `listener` is inherited. See
     // https://docs.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-accept#remarks.
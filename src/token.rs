@@ -3,12 +3,16 @@
 /// `Token` is a wrapper around `usize` and is used as an argument to
 /// [`Registry::register`] and [`Registry::reregister`].
 ///
+/// `Token(usize::MAX)` is reserved for internal use, registering a source or
+/// creating a [`Waker`] with it returns an `InvalidInput` error.
+///
 /// See [`Poll`] for more documentation on polling.
 ///
 /// [`event::Source`]: ./event/trait.Source.html
 /// [`Poll`]: struct.Poll.html
 /// [`Registry::register`]: struct.Registry.html#method.register
 /// [`Registry::reregister`]: struct.Registry.html#method.reregister
+/// [`Waker`]: struct.Waker.html
 ///
 /// # Example
 ///
@@ -0,0 +1,227 @@
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::Read;
+use std::os::raw::c_int;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::path::Path;
+use std::{cmp, fmt, io, mem, ptr};
+
+use crate::io_source::IoSource;
+use crate::{event, sys, Interest, Registry, Token};
+
+/// Size of the header of a single event, i.e. without the name.
+const HEADER_SIZE: usize = mem::size_of::<libc::inotify_event>();
+
+/// Size of the read buffer, large enough to hold at least a single event with
+/// the longest possible name (`NAME_MAX`).
+const BUF_SIZE: usize = 4096;
+
+/// Filesystem notifications, backed by `inotify`.
+///
+/// Paths are watched using [`add_watch`]. Once the `Inotify` becomes readable
+/// the events can be retrieved using [`read_events`].
+///
+/// [`add_watch`]: #method.add_watch
+/// [`read_events`]: #method.read_events
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use std::env;
+/// use std::fs::{self, File};
+///
+/// use mio::unix::Inotify;
+/// use mio::{Events, Interest, Poll, Token};
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(8);
+///
+/// let dir = env::temp_dir().join("mio_inotify_example");
+/// fs::create_dir_all(&dir)?;
+///
+/// let mut inotify = Inotify::new()?;
+/// let wd = inotify.add_watch(&dir, libc::IN_CREATE)?;
+/// poll.registry().register(&mut inotify, Token(0), Interest::READABLE)?;
+///
+/// File::create(dir.join("file.txt"))?;
+///
+/// poll.poll(&mut events, None)?;
+/// for event in inotify.read_events()? {
+///     assert_eq!(event.wd(), wd);
+///     assert!(event.mask() & libc::IN_CREATE != 0);
+///     assert_eq!(event.name().unwrap(), "file.txt");
+/// }
+/// # fs::remove_dir_all(&dir)?;
+/// #     Ok(())
+/// # }
+/// ```
+pub struct Inotify {
+    inner: IoSource<File>,
+}
+
+/// Watch descriptor, identifying a watch added using [`Inotify::add_watch`].
+///
+/// [`Inotify::add_watch`]: struct.Inotify.html#method.add_watch
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct WatchDescriptor(c_int);
+
+/// Event read from an [`Inotify`].
+///
+/// [`Inotify`]: struct.Inotify.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InotifyEvent {
+    wd: WatchDescriptor,
+    mask: u32,
+    cookie: u32,
+    /// Empty if the event doesn't have a name.
+    name: OsString,
+}
+
+impl Inotify {
+    /// Create a new `Inotify`, without any watches.
+    pub fn new() -> io::Result<Inotify> {
+        sys::inotify::new().map(|inotify| Inotify {
+            inner: IoSource::new(inotify),
+        })
+    }
+
+    /// Watch `path` for the events in `mask`, e.g. `libc::IN_MODIFY`.
+    ///
+    /// If `path` is already watched its mask is replaced (or extended if
+    /// `mask` includes `IN_MASK_ADD`) and the same watch descriptor is
+    /// returned.
+    pub fn add_watch<P: AsRef<Path>>(&self, path: P, mask: u32) -> io::Result<WatchDescriptor> {
+        sys::inotify::add_watch(&self.inner, path.as_ref(), mask).map(WatchDescriptor)
+    }
+
+    /// Remove the watch `wd`.
+    ///
+    /// This generates an `IN_IGNORED` event for the watch.
+    pub fn remove_watch(&self, wd: WatchDescriptor) -> io::Result<()> {
+        sys::inotify::remove_watch(&self.inner, wd.0)
+    }
+
+    /// Read the pending events.
+    ///
+    /// This reads a single buffer of events, so more events may be pending.
+    /// If no events are pending this returns a `WouldBlock` error.
+    pub fn read_events(&self) -> io::Result<Vec<InotifyEvent>> {
+        let mut buf = [0; BUF_SIZE];
+        let n = self.inner.do_io(|mut inotify| inotify.read(&mut buf))?;
+        Ok(parse_events(&buf[..n]))
+    }
+}
+
+/// Parse the `inotify_event` records in `buf`.
+fn parse_events(buf: &[u8]) -> Vec<InotifyEvent> {
+    let mut events = Vec::new();
+    let mut offset = 0;
+    while offset + HEADER_SIZE <= buf.len() {
+        // The buffer isn't (necessarily) aligned for `inotify_event`.
+        let raw: libc::inotify_event =
+            unsafe { ptr::read_unaligned(buf[offset..].as_ptr() as *const _) };
+        let name_start = offset + HEADER_SIZE;
+        // The kernel never returns partial events, but don't panic if it does.
+        let name_end = cmp::min(name_start + raw.len as usize, buf.len());
+        // The name is padded with null bytes.
+        let name = buf[name_start..name_end]
+            .split(|b| *b == 0)
+            .next()
+            .unwrap_or(&[]);
+        events.push(InotifyEvent {
+            wd: WatchDescriptor(raw.wd),
+            mask: raw.mask,
+            cookie: raw.cookie,
+            name: OsStr::from_bytes(name).to_owned(),
+        });
+        offset = name_end;
+    }
+    events
+}
+
+impl InotifyEvent {
+    /// Returns the watch descriptor of the watch the event is for.
+    pub fn wd(&self) -> WatchDescriptor {
+        self.wd
+    }
+
+    /// Returns the mask describing the event, e.g. `libc::IN_MODIFY`.
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    /// Returns the cookie connecting related events, used to pair
+    /// `IN_MOVED_FROM` and `IN_MOVED_TO` events.
+    pub fn cookie(&self) -> u32 {
+        self.cookie
+    }
+
+    /// Returns the name of the file the event is for, if the event is for a
+    /// file inside a watched directory.
+    pub fn name(&self) -> Option<&OsStr> {
+        if self.name.is_empty() {
+            None
+        } else {
+            Some(&self.name)
+        }
+    }
+}
+
+impl event::Source for Inotify {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.inner.deregister(registry)
+    }
+}
+
+impl fmt::Debug for Inotify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl IntoRawFd for Inotify {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_inner().into_raw_fd()
+    }
+}
+
+impl AsRawFd for Inotify {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Inotify {
+    /// Converts a `RawFd` to an `Inotify`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the file descriptor is an
+    /// `inotify` instance in non-blocking mode.
+    unsafe fn from_raw_fd(fd: RawFd) -> Inotify {
+        Inotify {
+            inner: IoSource::new(File::from_raw_fd(fd)),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Unix only extensions.
+
+pub use crate::sys::SourceFd;
+
+cfg_os_poll! {
+    pub mod pipe;
+
+    mod process;
+    pub use self::process::Process;
+
+    pub mod pty;
+
+    mod signals;
+    pub use self::signals::{Pending, Signals};
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+cfg_os_poll! {
+    mod inotify;
+    pub use self::inotify::{Inotify, InotifyEvent, WatchDescriptor};
+
+    mod timer;
+    pub use self::timer::{Clock, Timer};
+}
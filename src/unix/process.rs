@@ -0,0 +1,225 @@
+use std::io;
+use std::process::{Child, Command, ExitStatus, Stdio};
+
+use crate::io_source::IoSource;
+use crate::unix::pipe;
+use crate::{event, sys, Interest, Registry, Token};
+
+/// A child process as an event source.
+///
+/// `Process` wraps a [`Child`] and becomes readable once the child process
+/// exits, after which [`try_wait`] can be used to retrieve its exit status
+/// without blocking.
+///
+/// [`Child`]: std::process::Child
+/// [`try_wait`]: #method.try_wait
+///
+/// # Notes
+///
+/// On Linux 5.3 and later `Process` is backed by a `pidfd`. On older versions,
+/// and other platforms, it relies on a handler for the `SIGCHLD` signal
+/// (replacing any previous handler), see [`Signals`]. As `SIGCHLD` is
+/// delivered for all child processes this means the `Process` can become
+/// readable when another child process exits. For the fallback to work
+/// `SIGCHLD` must not be blocked, so it can't be combined with a `Signals`
+/// handling `SIGCHLD` on Linux.
+///
+/// [`Signals`]: struct.Signals.html
+///
+/// # Examples
+///
+/// Spawning a process and waiting for it to exit.
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use std::process::Command;
+///
+/// use mio::unix::Process;
+/// use mio::{Events, Interest, Poll, Token};
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(8);
+///
+/// let child = Command::new("/bin/true").spawn()?;
+/// let mut process = Process::new(child)?;
+/// poll.registry().register(&mut process, Token(0), Interest::READABLE)?;
+///
+/// let status = loop {
+///     if let Some(status) = process.try_wait()? {
+///         break status;
+///     }
+///     poll.poll(&mut events, None)?;
+/// };
+/// assert!(status.success());
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// Using [`Process::spawn`] to read the output of a process.
+///
+/// [`Process::spawn`]: #method.spawn
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use std::io::{self, Read};
+/// use std::process::Command;
+///
+/// use mio::unix::Process;
+/// use mio::{Events, Interest, Poll, Token};
+///
+/// const STDOUT: Token = Token(0);
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(8);
+///
+/// let mut process = Process::spawn(Command::new("echo").arg("Hello world"))?;
+/// let mut stdout = process.take_stdout().unwrap();
+/// poll.registry().register(&mut stdout, STDOUT, Interest::READABLE)?;
+///
+/// let mut output = Vec::new();
+/// let mut buf = [0; 1024];
+/// loop {
+///     match stdout.read(&mut buf) {
+///         // End of the output.
+///         Ok(0) => break,
+///         Ok(n) => output.extend_from_slice(&buf[..n]),
+///         Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+///             poll.poll(&mut events, None)?;
+///         }
+///         Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+///         Err(err) => return Err(err.into()),
+///     }
+/// }
+/// assert_eq!(output, b"Hello world\n");
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Process {
+    child: Child,
+    inner: IoSource<sys::process::Exit>,
+}
+
+impl Process {
+    /// Create a new `Process` from `child`.
+    pub fn new(mut child: Child) -> io::Result<Process> {
+        sys::process::Exit::new(&mut child).map(|exit| Process {
+            child,
+            inner: IoSource::new(exit),
+        })
+    }
+
+    /// Spawn `command` as a child process, with its standard input, output
+    /// and error piped.
+    ///
+    /// This overwrites any standard I/O configuration of `command`. Use
+    /// [`take_stdin`], [`take_stdout`] and [`take_stderr`] to get
+    /// non-blocking event sources for them.
+    ///
+    /// [`take_stdin`]: #method.take_stdin
+    /// [`take_stdout`]: #method.take_stdout
+    /// [`take_stderr`]: #method.take_stderr
+    pub fn spawn(command: &mut Command) -> io::Result<Process> {
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(Process::new)
+    }
+
+    /// Take the standard input of the child process, if it's piped and not
+    /// taken already.
+    ///
+    /// Dropping the returned `Sender` closes the standard input of the child
+    /// process.
+    pub fn take_stdin(&mut self) -> Option<pipe::Sender> {
+        self.child.stdin.take().map(pipe::Sender::from)
+    }
+
+    /// Take the standard output of the child process, if it's piped and not
+    /// taken already.
+    ///
+    /// Once the child process closes its standard output (e.g. when it
+    /// exits) the `Receiver` receives an event with [`is_read_closed`].
+    ///
+    /// [`is_read_closed`]: ../event/struct.Event.html#method.is_read_closed
+    pub fn take_stdout(&mut self) -> Option<pipe::Receiver> {
+        self.child.stdout.take().map(pipe::Receiver::from)
+    }
+
+    /// Take the standard error of the child process, if it's piped and not
+    /// taken already.
+    ///
+    /// See [`take_stdout`].
+    ///
+    /// [`take_stdout`]: #method.take_stdout
+    pub fn take_stderr(&mut self) -> Option<pipe::Receiver> {
+        self.child.stderr.take().map(pipe::Receiver::from)
+    }
+
+    /// Returns the OS-assigned process identifier of the child process.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Returns the exit status of the child process if it has exited, without
+    /// blocking.
+    ///
+    /// If the child process hasn't exited yet this returns `Ok(None)` and the
+    /// `Process` will become readable once it does.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.inner.do_io(|exit| exit.reset())?;
+        self.child.try_wait()
+    }
+
+    /// Returns a reference to the wrapped `Child`.
+    pub fn child(&self) -> &Child {
+        &self.child
+    }
+
+    /// Returns a mutable reference to the wrapped `Child`, e.g. to kill it or
+    /// to access its standard I/O.
+    ///
+    /// Note that waiting for the child process using the returned reference
+    /// doesn't reset the readiness, use [`try_wait`] for that.
+    ///
+    /// [`try_wait`]: #method.try_wait
+    pub fn child_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Returns the wrapped `Child`.
+    ///
+    /// To ensure no more events are to be received for this process first
+    /// deregister it.
+    pub fn into_child(self) -> Child {
+        self.child
+    }
+}
+
+impl event::Source for Process {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.inner.deregister(registry)
+    }
+}
@@ -0,0 +1,366 @@
+//! Unix pseudo-terminal.
+//!
+//! See the [`new`] function for documentation.
+//!
+//! [`new`]: fn.new.html
+
+use std::fs::File;
+use std::io::{self, IoSlice, IoSliceMut, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+use crate::io_source::IoSource;
+use crate::unix::Process;
+use crate::{event, sys, Interest, Registry, Token};
+
+/// Create a new pseudo-terminal, returning the master and slave sides.
+///
+/// This is a wrapper around `posix_openpt(3)` and related functions. The
+/// [`Master`] side is used by the controlling program, e.g. a terminal
+/// emulator, and is in non-blocking mode. The [`Slave`] side acts as terminal
+/// for the program(s) running inside the pseudo-terminal, see
+/// [`Slave::spawn`].
+///
+/// # Events
+///
+/// The `Master` can be registered with [`READABLE`] interest to receive
+/// [readable events] when the program inside the pseudo-terminal writes
+/// output, and [`WRITABLE`] interest to receive [writable events]. Once all
+/// file descriptors for the slave side are closed, e.g. when the program
+/// exits, the `Master` receives an event with [`is_read_closed`] set.
+///
+/// Note that on Linux reading from the `Master` once the slave side is closed
+/// returns an error (`EIO`), rather than `Ok(0)`. See the example below.
+///
+/// [`Master`]: struct.Master.html
+/// [`Slave`]: struct.Slave.html
+/// [`Slave::spawn`]: struct.Slave.html#method.spawn
+/// [`READABLE`]: ../../struct.Interest.html#associatedconstant.READABLE
+/// [readable events]: ../../event/struct.Event.html#method.is_readable
+/// [`WRITABLE`]: ../../struct.Interest.html#associatedconstant.WRITABLE
+/// [writable events]: ../../event/struct.Event.html#method.is_writable
+/// [`is_read_closed`]: ../../event/struct.Event.html#method.is_read_closed
+///
+/// # Examples
+///
+/// Running a shell inside a pseudo-terminal.
+///
+/// ```
+/// use std::io::{self, Read, Write};
+/// use std::process::Command;
+///
+/// use mio::unix::pty;
+/// use mio::{Events, Interest, Poll, Token};
+///
+/// const MASTER: Token = Token(0);
+///
+/// # fn main() -> io::Result<()> {
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(8);
+///
+/// let (mut master, slave) = pty::new()?;
+/// poll.registry().register(&mut master, MASTER, Interest::READABLE)?;
+///
+/// let mut process = slave.spawn(&mut Command::new("sh"))?;
+/// // Drop our copy of the slave side, so we'll receive an event once the
+/// // shell exits.
+/// drop(slave);
+///
+/// master.write_all(b"echo Hello world; exit\n")?;
+///
+/// let mut output = Vec::new();
+/// let mut buf = [0; 1024];
+/// loop {
+///     match master.read(&mut buf) {
+///         Ok(0) => break,
+///         Ok(n) => output.extend_from_slice(&buf[..n]),
+///         Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+///             poll.poll(&mut events, None)?;
+///         }
+///         Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+///         // The shell exited, closing the slave side.
+///         Err(ref err) if err.raw_os_error() == Some(libc::EIO) => break,
+///         Err(err) => return Err(err),
+///     }
+/// }
+///
+/// let output = String::from_utf8_lossy(&output);
+/// assert!(output.contains("Hello world"));
+/// # drop(process.child_mut().wait());
+/// # Ok(())
+/// # }
+/// ```
+pub fn new() -> io::Result<(Master, Slave)> {
+    sys::pty::new().map(|(master, slave)| {
+        (
+            Master {
+                inner: IoSource::new(master),
+            },
+            Slave { inner: slave },
+        )
+    })
+}
+
+/// Size of a terminal window.
+///
+/// Note that the pixel sizes are not used by the pseudo-terminal itself and
+/// often left as zero.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct WindowSize {
+    /// Number of rows, in characters.
+    pub rows: u16,
+    /// Number of columns, in characters.
+    pub columns: u16,
+    /// Width, in pixels.
+    pub width: u16,
+    /// Height, in pixels.
+    pub height: u16,
+}
+
+impl WindowSize {
+    fn from_sys(size: libc::winsize) -> WindowSize {
+        WindowSize {
+            rows: size.ws_row,
+            columns: size.ws_col,
+            width: size.ws_xpixel,
+            height: size.ws_ypixel,
+        }
+    }
+
+    fn to_sys(self) -> libc::winsize {
+        libc::winsize {
+            ws_row: self.rows,
+            ws_col: self.columns,
+            ws_xpixel: self.width,
+            ws_ypixel: self.height,
+        }
+    }
+}
+
+/// Master side of a pseudo-terminal.
+///
+/// See [`new`] for documentation, including examples.
+///
+/// [`new`]: fn.new.html
+#[derive(Debug)]
+pub struct Master {
+    inner: IoSource<File>,
+}
+
+impl Master {
+    /// Returns the window size of the pseudo-terminal.
+    pub fn window_size(&self) -> io::Result<WindowSize> {
+        sys::pty::window_size(self.as_raw_fd()).map(WindowSize::from_sys)
+    }
+
+    /// Set the window size of the pseudo-terminal.
+    ///
+    /// This sends `SIGWINCH` to the foreground process group of the
+    /// pseudo-terminal, if any, if the size changed.
+    pub fn set_window_size(&self, size: WindowSize) -> io::Result<()> {
+        sys::pty::set_window_size(self.as_raw_fd(), &size.to_sys())
+    }
+}
+
+impl event::Source for Master {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.inner.deregister(registry)
+    }
+}
+
+impl Read for Master {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).read(buf))
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).read_vectored(bufs))
+    }
+}
+
+impl<'a> Read for &'a Master {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).read(buf))
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).read_vectored(bufs))
+    }
+}
+
+impl Write for Master {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).write(buf))
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).write_vectored(bufs))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.do_io(|master| (&*master).flush())
+    }
+}
+
+impl<'a> Write for &'a Master {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).write(buf))
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.inner.do_io(|master| (&*master).write_vectored(bufs))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.do_io(|master| (&*master).flush())
+    }
+}
+
+impl FromRawFd for Master {
+    /// Converts a `RawFd` to a `Master`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the file descriptor is the
+    /// master side of a pseudo-terminal in non-blocking mode.
+    unsafe fn from_raw_fd(fd: RawFd) -> Master {
+        Master {
+            inner: IoSource::new(File::from_raw_fd(fd)),
+        }
+    }
+}
+
+impl AsRawFd for Master {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Master {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_inner().into_raw_fd()
+    }
+}
+
+/// Slave side of a pseudo-terminal.
+///
+/// Unlike the [`Master`] this is in blocking mode and not an event source, it
+/// is meant to be used by the program(s) running inside the pseudo-terminal.
+///
+/// See [`new`] for documentation, including examples.
+///
+/// [`Master`]: struct.Master.html
+/// [`new`]: fn.new.html
+#[derive(Debug)]
+pub struct Slave {
+    inner: File,
+}
+
+impl Slave {
+    /// Spawn `command` as a child process inside the pseudo-terminal.
+    ///
+    /// This overwrites any standard I/O configuration of `command`, setting
+    /// its standard input, output and error to the slave side. The child
+    /// process starts a new session with the pseudo-terminal as controlling
+    /// terminal, e.g. to support job control in shells.
+    ///
+    /// The `Master` only receives an event with `is_read_closed` set once all
+    /// file descriptors for the slave side are closed, including this `Slave`.
+    /// So drop it once all required processes are spawned. For the same reason
+    /// the standard I/O of `command` is set to null after spawning, which
+    /// means it can only be reused with a `Slave`, either this one or another.
+    ///
+    /// Starting the new session is done in a [`pre_exec`] hook, which stays
+    /// part of `command`. It's safe to call `spawn` multiple times with the
+    /// same `command`, but spawning it directly afterwards will fail.
+    ///
+    /// [`pre_exec`]: std::os::unix::process::CommandExt::pre_exec
+    pub fn spawn(&self, command: &mut Command) -> io::Result<Process> {
+        command
+            .stdin(Stdio::from(self.inner.try_clone()?))
+            .stdout(Stdio::from(self.inner.try_clone()?))
+            .stderr(Stdio::from(self.inner.try_clone()?));
+        // Safety: `set_controlling_terminal` only makes async-signal-safe
+        // system calls.
+        unsafe {
+            command.pre_exec(|| sys::pty::set_controlling_terminal(libc::STDIN_FILENO));
+        }
+        let res = command.spawn();
+        // `command` keeps the slave side open, which would prevent the
+        // `Master` from receiving an event once the child process exits.
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        res.and_then(Process::new)
+    }
+}
+
+impl Read for Slave {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+}
+
+impl Write for Slave {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl FromRawFd for Slave {
+    /// Converts a `RawFd` to a `Slave`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the file descriptor is the
+    /// slave side of a pseudo-terminal.
+    unsafe fn from_raw_fd(fd: RawFd) -> Slave {
+        Slave {
+            inner: File::from_raw_fd(fd),
+        }
+    }
+}
+
+impl AsRawFd for Slave {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Slave {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_raw_fd()
+    }
+}
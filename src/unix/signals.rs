@@ -0,0 +1,145 @@
+use std::os::raw::c_int;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::{fmt, io};
+
+use crate::io_source::IoSource;
+use crate::{event, sys, Interest, Registry, Token};
+
+/// Unix signals as an event source.
+///
+/// `Signals` becomes readable once one of its signals is delivered to the
+/// process. After which the pending signals can be retrieved using
+/// [`receive`] or [`pending`].
+///
+/// [`receive`]: #method.receive
+/// [`pending`]: #method.pending
+///
+/// # Notes
+///
+/// Like the OS, `Signals` coalesces multiple deliveries of the same signal,
+/// so a signal delivered twice may only be received once.
+///
+/// On Linux and Android `Signals` is backed by `signalfd`, which requires the
+/// signals to be blocked. The signals are blocked for the thread calling
+/// [`Signals::new`], and threads spawned after it (which inherit the signal
+/// mask). Threads created before the `Signals` may still receive the signals
+/// and will run the default action. It's therefore best to create `Signals`
+/// in the main thread before spawning any other threads. Once the last
+/// `Signals` handling a signal is dropped the signal is unblocked again, if
+/// it wasn't blocked before.
+///
+/// On other platforms a signal handler is installed that writes the signal to
+/// a pipe, replacing any previous handler. Once the last `Signals` handling a
+/// signal is dropped the default action of the signal is restored.
+///
+/// [`Signals::new`]: #method.new
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::unix::Signals;
+/// use mio::{Events, Interest, Poll, Token};
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(8);
+///
+/// let mut signals = Signals::new(&[libc::SIGUSR1])?;
+/// poll.registry().register(&mut signals, Token(0), Interest::READABLE)?;
+///
+/// unsafe { libc::raise(libc::SIGUSR1) };
+///
+/// poll.poll(&mut events, None)?;
+/// assert_eq!(events.iter().next().unwrap().token(), Token(0));
+/// for signal in signals.pending() {
+///     assert_eq!(signal?, libc::SIGUSR1);
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+pub struct Signals {
+    inner: IoSource<sys::signals::Signals>,
+}
+
+impl Signals {
+    /// Create a new `Signals` receiving `signals`, e.g. `libc::SIGTERM`.
+    pub fn new(signals: &[c_int]) -> io::Result<Signals> {
+        sys::signals::Signals::new(signals).map(|signals| Signals {
+            inner: IoSource::new(signals),
+        })
+    }
+
+    /// Receive a single pending signal.
+    ///
+    /// If no signal is pending this returns a `WouldBlock` error.
+    pub fn receive(&self) -> io::Result<c_int> {
+        self.inner.do_io(|signals| signals.receive())
+    }
+
+    /// Returns an iterator over the pending signals.
+    ///
+    /// The iterator ends once no more signals are pending, i.e. once
+    /// [`receive`] would return a `WouldBlock` error.
+    ///
+    /// [`receive`]: #method.receive
+    pub fn pending(&self) -> Pending<'_> {
+        Pending { signals: self }
+    }
+}
+
+impl event::Source for Signals {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.inner.deregister(registry)
+    }
+}
+
+impl fmt::Debug for Signals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// Iterator over pending signals, returned by [`Signals::pending`].
+///
+/// [`Signals::pending`]: struct.Signals.html#method.pending
+#[derive(Debug)]
+pub struct Pending<'a> {
+    signals: &'a Signals,
+}
+
+impl<'a> Iterator for Pending<'a> {
+    type Item = io::Result<c_int>;
+
+    fn next(&mut self) -> Option<io::Result<c_int>> {
+        match self.signals.receive() {
+            Ok(signal) => Some(Ok(signal)),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::fs::File;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::time::Duration;
+use std::{fmt, io};
+
+use crate::io_source::IoSource;
+use crate::{event, sys, Interest, Registry, Token};
+
+/// Clock used by a [`Timer`].
+///
+/// [`Timer`]: struct.Timer.html
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Clock {
+    /// `CLOCK_MONOTONIC`, a clock that doesn't advance while the system is
+    /// suspended. This is the same clock as used by `std::time::Instant`.
+    Monotonic,
+    /// `CLOCK_BOOTTIME`, like `Monotonic` but it does advance while the system
+    /// is suspended.
+    Boottime,
+}
+
+impl Clock {
+    /// Returns the current time of the clock, for use in
+    /// [`Timer::set_deadline`].
+    ///
+    /// [`Timer::set_deadline`]: struct.Timer.html#method.set_deadline
+    pub fn now(self) -> io::Result<Duration> {
+        sys::timer::now(self.as_raw())
+    }
+
+    fn as_raw(self) -> libc::clockid_t {
+        match self {
+            Clock::Monotonic => libc::CLOCK_MONOTONIC,
+            Clock::Boottime => libc::CLOCK_BOOTTIME,
+        }
+    }
+}
+
+/// A timer, backed by `timerfd`.
+///
+/// A timer becomes readable once it expires. Reading it, using [`read`],
+/// returns the number of times it expired since it was last read.
+///
+/// [`read`]: #method.read
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use std::time::Duration;
+///
+/// use mio::unix::{Clock, Timer};
+/// use mio::{Events, Interest, Poll, Token};
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(8);
+///
+/// let mut timer = Timer::new(Clock::Monotonic)?;
+/// poll.registry().register(&mut timer, Token(0), Interest::READABLE)?;
+///
+/// timer.set_timeout(Duration::from_millis(10))?;
+///
+/// poll.poll(&mut events, None)?;
+/// assert_eq!(events.iter().next().unwrap().token(), Token(0));
+/// assert_eq!(timer.read()?, 1);
+/// #     Ok(())
+/// # }
+/// ```
+pub struct Timer {
+    inner: IoSource<File>,
+}
+
+impl Timer {
+    /// Create a new, disarmed, timer using `clock`.
+    pub fn new(clock: Clock) -> io::Result<Timer> {
+        sys::timer::new(clock.as_raw()).map(|timer| Timer {
+            inner: IoSource::new(timer),
+        })
+    }
+
+    /// Arm the timer to expire once, after `timeout`.
+    ///
+    /// A timeout of zero expires the timer immediately. This replaces any
+    /// previous setting.
+    pub fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        // A value of zero would disarm the timer.
+        let timeout = if timeout == Duration::from_secs(0) {
+            Duration::from_nanos(1)
+        } else {
+            timeout
+        };
+        sys::timer::set(&self.inner, timeout, Duration::from_secs(0), false)
+    }
+
+    /// Arm the timer to expire periodically, every `interval`, starting after
+    /// the first `interval`.
+    ///
+    /// This replaces any previous setting. Returns an `InvalidInput` error if
+    /// `interval` is zero.
+    pub fn set_interval(&self, interval: Duration) -> io::Result<()> {
+        if interval == Duration::from_secs(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "timer interval can't be zero",
+            ));
+        }
+        sys::timer::set(&self.inner, interval, interval, false)
+    }
+
+    /// Arm the timer to expire at `deadline`, an absolute time of the timer's
+    /// clock (see [`Clock::now`]), and then every `interval` if provided.
+    ///
+    /// A deadline in the past expires the timer immediately. This replaces any
+    /// previous setting.
+    ///
+    /// [`Clock::now`]: enum.Clock.html#method.now
+    pub fn set_deadline(&self, deadline: Duration, interval: Option<Duration>) -> io::Result<()> {
+        // A value of zero would disarm the timer.
+        let deadline = if deadline == Duration::from_secs(0) {
+            Duration::from_nanos(1)
+        } else {
+            deadline
+        };
+        let interval = interval.unwrap_or_else(|| Duration::from_secs(0));
+        sys::timer::set(&self.inner, deadline, interval, true)
+    }
+
+    /// Disarm the timer.
+    pub fn disarm(&self) -> io::Result<()> {
+        let zero = Duration::from_secs(0);
+        sys::timer::set(&self.inner, zero, zero, false)
+    }
+
+    /// Returns the time until the timer next expires, or `None` if it's
+    /// disarmed.
+    pub fn remaining(&self) -> io::Result<Option<Duration>> {
+        sys::timer::remaining(&self.inner)
+    }
+
+    /// Returns the number of times the timer expired since the last call to
+    /// `read` (or since it was armed).
+    ///
+    /// If the timer hasn't expired this returns a `WouldBlock` error.
+    pub fn read(&self) -> io::Result<u64> {
+        self.inner.do_io(sys::timer::read)
+    }
+}
+
+impl event::Source for Timer {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.inner.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.inner.deregister(registry)
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl IntoRawFd for Timer {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_inner().into_raw_fd()
+    }
+}
+
+impl AsRawFd for Timer {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Timer {
+    /// Converts a `RawFd` to a `Timer`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the file descriptor is a
+    /// `timerfd` in non-blocking mode.
+    unsafe fn from_raw_fd(fd: RawFd) -> Timer {
+        Timer {
+            inner: IoSource::new(File::from_raw_fd(fd)),
+        }
+    }
+}
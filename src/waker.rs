@@ -1,6 +1,10 @@
 use crate::{poll, sys, Registry, Token};
 
 use std::io;
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "stats")]
+use std::sync::Arc;
 
 /// Waker allows cross-thread waking of [`Poll`].
 ///
@@ -76,18 +80,33 @@ use std::io;
 #[derive(Debug)]
 pub struct Waker {
     inner: sys::Waker,
+    /// Number of calls to `wake`, see `Poll::stats`.
+    #[cfg(feature = "stats")]
+    wakeups: Arc<AtomicUsize>,
 }
 
 impl Waker {
     /// Create a new `Waker`.
+    ///
+    /// Returns an `InvalidInput` error if `token` is the reserved
+    /// `Token(usize::MAX)`, see [`Token`].
+    ///
+    /// [`Token`]: struct.Token.html
     pub fn new(registry: &Registry, token: Token) -> io::Result<Waker> {
-        sys::Waker::new(poll::selector(&registry), token).map(|inner| Waker { inner })
+        poll::check_token(token)?;
+        sys::Waker::new(poll::selector(&registry), token).map(|inner| Waker {
+            inner,
+            #[cfg(feature = "stats")]
+            wakeups: poll::wakeups(registry).clone(),
+        })
     }
 
     /// Wake up the [`Poll`] associated with this `Waker`.
     ///
     /// [`Poll`]: struct.Poll.html
     pub fn wake(&self) -> io::Result<()> {
+        #[cfg(feature = "stats")]
+        self.wakeups.fetch_add(1, Ordering::Relaxed);
         self.inner.wake()
     }
 }
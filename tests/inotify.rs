@@ -0,0 +1,230 @@
+#![cfg(all(
+    feature = "os-poll",
+    feature = "os-util",
+    any(target_os = "android", target_os = "linux")
+))]
+
+use mio::unix::{Inotify, InotifyEvent};
+use mio::{Interest, Token};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+use std::path::PathBuf;
+
+mod util;
+use util::{
+    assert_send, assert_socket_close_on_exec, assert_socket_non_blocking, assert_sync,
+    assert_would_block, expect_events, expect_no_events, init_with_poll, temp_file, ExpectEvent,
+};
+
+const ID: Token = Token(0);
+
+/// Create an empty directory `name`.
+fn temp_dir(name: &'static str) -> PathBuf {
+    let path = temp_file(name);
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
+
+/// Read all pending events.
+fn read_all(inotify: &Inotify) -> Vec<InotifyEvent> {
+    let mut events = Vec::new();
+    loop {
+        match inotify.read_events() {
+            Ok(evs) => events.extend(evs),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return events,
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+}
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Inotify>();
+    assert_sync::<Inotify>();
+}
+
+#[test]
+fn inotify_events() {
+    let (mut poll, mut events) = init_with_poll();
+    let dir = temp_dir("inotify_events");
+
+    let mut inotify = Inotify::new().unwrap();
+    assert_socket_non_blocking(&inotify);
+    assert_socket_close_on_exec(&inotify);
+    let wd = inotify
+        .add_watch(&dir, libc::IN_CREATE | libc::IN_MODIFY | libc::IN_DELETE)
+        .unwrap();
+    poll.registry()
+        .register(&mut inotify, ID, Interest::READABLE)
+        .unwrap();
+
+    assert_would_block(inotify.read_events());
+    expect_no_events(&mut poll, &mut events);
+
+    let path = dir.join("file.txt");
+    let mut file = File::create(&path).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    let evs = read_all(&inotify);
+    assert_eq!(evs.len(), 1);
+    assert_eq!(evs[0].wd(), wd);
+    assert_eq!(evs[0].mask(), libc::IN_CREATE);
+    assert_eq!(evs[0].name().unwrap(), "file.txt");
+
+    file.write_all(b"Hello world").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    let evs = read_all(&inotify);
+    assert!(!evs.is_empty());
+    for ev in evs {
+        assert_eq!(ev.wd(), wd);
+        assert_eq!(ev.mask(), libc::IN_MODIFY);
+        assert_eq!(ev.name().unwrap(), "file.txt");
+    }
+
+    drop(file);
+    fs::remove_file(&path).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    let evs = read_all(&inotify);
+    assert_eq!(evs.len(), 1);
+    assert_eq!(evs[0].mask(), libc::IN_DELETE);
+    assert_eq!(evs[0].name().unwrap(), "file.txt");
+}
+
+#[test]
+fn inotify_watch_file() {
+    let (mut poll, mut events) = init_with_poll();
+    let dir = temp_dir("inotify_watch_file");
+    let path = dir.join("file.txt");
+    let mut file = File::create(&path).unwrap();
+
+    let mut inotify = Inotify::new().unwrap();
+    let wd = inotify.add_watch(&path, libc::IN_MODIFY).unwrap();
+    poll.registry()
+        .register(&mut inotify, ID, Interest::READABLE)
+        .unwrap();
+
+    file.write_all(b"Hello world").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    let evs = read_all(&inotify);
+    assert!(!evs.is_empty());
+    for ev in evs {
+        assert_eq!(ev.wd(), wd);
+        assert_eq!(ev.mask(), libc::IN_MODIFY);
+        // Events for the watched file itself don't have a name.
+        assert!(ev.name().is_none());
+    }
+}
+
+#[test]
+fn inotify_many_events() {
+    let (mut poll, mut events) = init_with_poll();
+    let dir = temp_dir("inotify_many_events");
+
+    let mut inotify = Inotify::new().unwrap();
+    inotify.add_watch(&dir, libc::IN_CREATE).unwrap();
+    poll.registry()
+        .register(&mut inotify, ID, Interest::READABLE)
+        .unwrap();
+
+    // More than fit in a single read.
+    const N: usize = 100;
+    let name = |n| format!("{:0>100}", n);
+    for n in 0..N {
+        File::create(dir.join(name(n))).unwrap();
+    }
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    let evs = read_all(&inotify);
+    assert_eq!(evs.len(), N);
+    for (n, ev) in evs.iter().enumerate() {
+        assert_eq!(ev.mask(), libc::IN_CREATE);
+        assert_eq!(ev.name().unwrap().to_str().unwrap(), name(n));
+    }
+}
+
+#[test]
+fn inotify_remove_watch() {
+    let (mut poll, mut events) = init_with_poll();
+    let dir = temp_dir("inotify_remove_watch");
+
+    let mut inotify = Inotify::new().unwrap();
+    let wd = inotify.add_watch(&dir, libc::IN_CREATE).unwrap();
+    // Adding the same path returns the same watch.
+    assert_eq!(inotify.add_watch(&dir, libc::IN_CREATE).unwrap(), wd);
+    poll.registry()
+        .register(&mut inotify, ID, Interest::READABLE)
+        .unwrap();
+
+    inotify.remove_watch(wd).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    let evs = read_all(&inotify);
+    assert_eq!(evs.len(), 1);
+    assert_eq!(evs[0].wd(), wd);
+    assert_eq!(evs[0].mask(), libc::IN_IGNORED);
+
+    File::create(dir.join("file.txt")).unwrap();
+    expect_no_events(&mut poll, &mut events);
+    assert_would_block(inotify.read_events());
+
+    let err = inotify.remove_watch(wd).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn inotify_invalid_path() {
+    let inotify = Inotify::new().unwrap();
+    let err = inotify
+        .add_watch(temp_file("inotify_does_not_exist"), libc::IN_CREATE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    let err = inotify.add_watch("a\0b", libc::IN_CREATE).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn inotify_from_raw_fd() {
+    let (mut poll, mut events) = init_with_poll();
+    let dir = temp_dir("inotify_from_raw_fd");
+
+    let inotify = Inotify::new().unwrap();
+    let fd = inotify.into_raw_fd();
+    let mut inotify = unsafe { Inotify::from_raw_fd(fd) };
+    assert_eq!(inotify.as_raw_fd(), fd);
+
+    inotify.add_watch(&dir, libc::IN_CREATE).unwrap();
+    poll.registry()
+        .register(&mut inotify, ID, Interest::READABLE)
+        .unwrap();
+    File::create(dir.join("file.txt")).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    assert_eq!(read_all(&inotify).len(), 1);
+}
@@ -8,6 +8,15 @@ fn is_tests() {
     assert!(Interest::WRITABLE.is_writable());
     assert!(!Interest::WRITABLE.is_aio());
     assert!(!Interest::WRITABLE.is_lio());
+    assert!(!Interest::READABLE.is_priority());
+    assert!(!Interest::READABLE.is_level());
+    assert!(!Interest::READABLE.is_exclusive());
+    #[cfg(unix)]
+    {
+        assert!((Interest::READABLE | Interest::LEVEL).is_level());
+        assert!((Interest::READABLE | Interest::ONESHOT).is_oneshot());
+        assert!(!(Interest::READABLE | Interest::LEVEL).is_oneshot());
+    }
 }
 
 #[test]
@@ -38,6 +47,38 @@ fn fmt_debug() {
     {
         assert_eq!(format!("{:?}", Interest::LIO), "LIO");
     }
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "solaris"
+    ))]
+    {
+        assert_eq!(format!("{:?}", Interest::PRIORITY), "PRIORITY");
+        assert!(Interest::PRIORITY.is_priority());
+    }
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        assert_eq!(
+            format!("{:?}", Interest::READABLE | Interest::EXCLUSIVE),
+            "READABLE | EXCLUSIVE"
+        );
+        assert!((Interest::READABLE | Interest::EXCLUSIVE).is_exclusive());
+    }
+    #[cfg(unix)]
+    {
+        assert_eq!(
+            format!("{:?}", Interest::READABLE | Interest::LEVEL),
+            "READABLE | LEVEL"
+        );
+        assert_eq!(
+            format!("{:?}", Interest::WRITABLE | Interest::ONESHOT),
+            "WRITABLE | ONESHOT"
+        );
+    }
 }
 
 #[test]
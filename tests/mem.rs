@@ -0,0 +1,467 @@
+#![cfg(all(unix, feature = "mock"))]
+
+use mio::mem::{Network, TcpListener, TcpStream, UdpSocket};
+use mio::{Interest, Poll, Token};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod util;
+use util::{
+    assert_send, assert_sync, assert_would_block, expect_events, expect_no_events, init_with_poll,
+    poll_retry, ExpectEvent, Readiness,
+};
+
+const LISTENER: Token = Token(0);
+const CLIENT: Token = Token(1);
+const SERVER: Token = Token(2);
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Network>();
+    assert_sync::<Network>();
+    assert_send::<TcpListener>();
+    assert_sync::<TcpListener>();
+    assert_send::<TcpStream>();
+    assert_sync::<TcpStream>();
+    assert_send::<UdpSocket>();
+    assert_sync::<UdpSocket>();
+}
+
+#[test]
+fn tcp_smoke() {
+    let (mut poll, mut events) = init_with_poll();
+    let network = Network::new();
+
+    let (listener, mut client, mut server) = connect(&mut poll, &network);
+    let server_addr = listener.local_addr().unwrap();
+    assert_eq!(client.peer_addr().unwrap(), server_addr);
+    assert_eq!(server.local_addr().unwrap(), server_addr);
+    assert_eq!(server.peer_addr().unwrap(), client.local_addr().unwrap());
+    assert_would_block(listener.accept());
+
+    poll.registry()
+        .register(&mut client, CLIENT, Interest::READABLE | Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::WRITABLE)],
+    );
+
+    let mut buf = [0; 16];
+    assert_would_block(server.read(&mut buf));
+    client.write_all(b"Hello world").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SERVER, Interest::READABLE)],
+    );
+    assert_eq!(server.peek(&mut buf).unwrap(), 11);
+    assert_eq!(server.read(&mut buf).unwrap(), 11);
+    assert_eq!(&buf[..11], b"Hello world");
+    assert_would_block(server.read(&mut buf));
+
+    server.write_all(b"Hello back").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::READABLE)],
+    );
+    assert_eq!(client.read(&mut buf).unwrap(), 10);
+    assert_eq!(&buf[..10], b"Hello back");
+
+    // Closing the connection.
+    drop(client);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(
+            SERVER,
+            Readiness::READ_CLOSED | Readiness::WRITE_CLOSED,
+        )],
+    );
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+    assert_eq!(
+        server.write(b"Hello").unwrap_err().kind(),
+        io::ErrorKind::BrokenPipe
+    );
+}
+
+#[test]
+fn tcp_bind() {
+    let network = Network::new();
+
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let listener = TcpListener::bind(&network, addr).unwrap();
+    assert_eq!(listener.local_addr().unwrap(), addr);
+    let err = TcpListener::bind(&network, addr).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+    // Port 0 picks a port.
+    let listener2 = TcpListener::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    assert_ne!(listener2.local_addr().unwrap().port(), 0);
+
+    // Can be bound again once dropped.
+    drop(listener);
+    let _listener = TcpListener::bind(&network, addr).unwrap();
+
+    // Different networks don't share addresses.
+    let _listener = TcpListener::bind(&Network::new(), addr).unwrap();
+}
+
+#[test]
+fn tcp_connection_refused() {
+    let network = Network::new();
+    let err = TcpStream::connect(&network, "127.0.0.1:8080".parse().unwrap()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+
+    // Listening on the unspecified address accepts connections for all
+    // addresses.
+    let _listener = TcpListener::bind(&network, "0.0.0.0:8080".parse().unwrap()).unwrap();
+    let _stream = TcpStream::connect(&network, "127.0.0.1:8080".parse().unwrap()).unwrap();
+}
+
+#[test]
+fn tcp_partial_reads_and_writes() {
+    let (mut poll, _) = init_with_poll();
+    let network = Network::new();
+    network.set_max_read(Some(3));
+    network.set_max_write(Some(4));
+
+    let (_listener, mut client, mut server) = connect(&mut poll, &network);
+
+    assert_eq!(client.write(b"Hello world").unwrap(), 4);
+    assert_eq!(client.write(b"o world").unwrap(), 4);
+    let mut buf = [0; 16];
+    assert_eq!(server.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"Hel");
+    assert_eq!(server.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"lo ");
+
+    network.set_max_read(None);
+    assert_eq!(server.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], b"wo");
+    assert_would_block(server.read(&mut buf));
+}
+
+#[test]
+fn tcp_buffer_full() {
+    let (mut poll, mut events) = init_with_poll();
+    let network = Network::new();
+    network.set_buffer_size(10);
+
+    let (_listener, mut client, mut server) = connect(&mut poll, &network);
+    poll.registry()
+        .register(&mut client, CLIENT, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::WRITABLE)],
+    );
+
+    assert_eq!(client.write(b"Hello world").unwrap(), 10);
+    assert_would_block(client.write(b"d"));
+    poll_retry(&mut poll, &mut events, Some(Duration::from_millis(50)));
+    assert!(events.iter().all(|event| event.token() != CLIENT));
+
+    // Once the peer reads data the stream is writable again.
+    let mut buf = [0; 4];
+    assert_eq!(server.read(&mut buf).unwrap(), 4);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::WRITABLE)],
+    );
+    assert_eq!(client.write(b"d and more").unwrap(), 4);
+    assert_would_block(client.write(b"d"));
+}
+
+#[test]
+fn tcp_latency() {
+    let (mut poll, mut events) = init_with_poll();
+    let network = Network::new();
+    let latency = Duration::from_millis(50);
+    network.set_latency(latency);
+
+    let mut listener = TcpListener::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut listener, LISTENER, Interest::READABLE)
+        .unwrap();
+
+    let start = Instant::now();
+    let mut client = TcpStream::connect(&network, listener.local_addr().unwrap()).unwrap();
+    assert_would_block(listener.accept());
+    // Poll should wake up once the connection is delivered.
+    poll_retry(&mut poll, &mut events, None);
+    assert!(start.elapsed() >= latency);
+    assert_eq!(events.iter().next().unwrap().token(), LISTENER);
+    let (mut server, _) = listener.accept().unwrap();
+    poll.registry()
+        .register(&mut server, SERVER, Interest::READABLE)
+        .unwrap();
+
+    let start = Instant::now();
+    client.write_all(b"Hello").unwrap();
+    client.shutdown(Shutdown::Write).unwrap();
+    let mut buf = [0; 8];
+    assert_would_block(server.read(&mut buf));
+    poll_retry(&mut poll, &mut events, Some(Duration::from_secs(1)));
+    assert!(start.elapsed() >= latency);
+    assert_eq!(events.iter().next().unwrap().token(), SERVER);
+    assert_eq!(server.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"Hello");
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn tcp_reset() {
+    let (mut poll, mut events) = init_with_poll();
+    let network = Network::new();
+
+    let (_listener, mut client, mut server) = connect(&mut poll, &network);
+    poll.registry()
+        .register(&mut client, CLIENT, Interest::READABLE)
+        .unwrap();
+    client.write_all(b"Hello").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SERVER, Interest::READABLE)],
+    );
+
+    server.reset();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![
+            ExpectEvent::new(CLIENT, Readiness::ERROR | Readiness::READ_CLOSED),
+            ExpectEvent::new(SERVER, Readiness::ERROR | Readiness::READ_CLOSED),
+        ],
+    );
+
+    for stream in &mut [&mut client, &mut server] {
+        let mut buf = [0; 8];
+        assert_eq!(
+            stream.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::ConnectionReset
+        );
+        assert_eq!(
+            stream.write(b"Hello").unwrap_err().kind(),
+            io::ErrorKind::ConnectionReset
+        );
+        let err = stream.take_error().unwrap().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+        assert!(stream.take_error().unwrap().is_none());
+    }
+}
+
+#[test]
+fn tcp_shutdown_read() {
+    let (mut poll, _) = init_with_poll();
+    let network = Network::new();
+
+    let (_listener, mut client, mut server) = connect(&mut poll, &network);
+    client.write_all(b"Hello").unwrap();
+    server.shutdown(Shutdown::Read).unwrap();
+    let mut buf = [0; 8];
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+    // Writing is still possible.
+    server.write_all(b"Hello").unwrap();
+    assert_eq!(client.read(&mut buf).unwrap(), 5);
+}
+
+#[test]
+fn tcp_level_triggered() {
+    let (mut poll, mut events) = init_with_poll();
+    let network = Network::new();
+
+    let (_listener, mut client, mut server) = connect(&mut poll, &network);
+    poll.registry()
+        .reregister(&mut server, SERVER, Interest::READABLE | Interest::LEVEL)
+        .unwrap();
+    client.write_all(b"Hello").unwrap();
+    for _ in 0..3 {
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(SERVER, Interest::READABLE)],
+        );
+    }
+    let mut buf = [0; 8];
+    assert_eq!(server.read(&mut buf).unwrap(), 5);
+    expect_no_events(&mut poll, &mut events);
+}
+
+#[test]
+fn tcp_wake_up_poll() {
+    let (mut poll, mut events) = init_with_poll();
+    let network = Network::new();
+
+    let (_listener, mut client, mut server) = connect(&mut poll, &network);
+    poll.registry()
+        .register(&mut client, CLIENT, Interest::READABLE)
+        .unwrap();
+
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        server.write_all(b"Hello").unwrap();
+        server
+    });
+
+    // Polling without a timeout should be woken up by the write from the
+    // other thread.
+    poll_retry(&mut poll, &mut events, None);
+    let event = events.iter().next().unwrap();
+    assert_eq!(event.token(), CLIENT);
+    assert!(event.is_readable());
+    let mut buf = [0; 8];
+    assert_eq!(client.read(&mut buf).unwrap(), 5);
+    drop(handle.join().unwrap());
+}
+
+#[test]
+fn udp_smoke() {
+    let (mut poll, mut events) = init_with_poll();
+    let network = Network::new();
+
+    let mut socket1 = UdpSocket::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    let mut socket2 = UdpSocket::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr1 = socket1.local_addr().unwrap();
+    let addr2 = socket2.local_addr().unwrap();
+    assert_ne!(addr1, addr2);
+    assert_eq!(
+        socket1.peer_addr().unwrap_err().kind(),
+        io::ErrorKind::NotConnected
+    );
+
+    poll.registry()
+        .register(&mut socket1, CLIENT, Interest::WRITABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut socket2, SERVER, Interest::READABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::WRITABLE)],
+    );
+
+    let mut buf = [0; 8];
+    assert_would_block(socket2.recv_from(&mut buf));
+    assert_eq!(socket1.send_to(b"Hello", addr2).unwrap(), 5);
+    assert_eq!(socket1.send_to(b"world", addr2).unwrap(), 5);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SERVER, Interest::READABLE)],
+    );
+    assert_eq!(socket2.peek_from(&mut buf).unwrap(), (5, addr1));
+    assert_eq!(socket2.recv_from(&mut buf).unwrap(), (5, addr1));
+    assert_eq!(&buf[..5], b"Hello");
+    // Datagrams are truncated if the buffer is too small.
+    assert_eq!(socket2.recv_from(&mut buf[..2]).unwrap(), (2, addr1));
+    assert_eq!(&buf[..2], b"wo");
+    assert_would_block(socket2.recv_from(&mut buf));
+
+    // Sending to an address without a socket drops the datagram.
+    assert_eq!(
+        socket1
+            .send_to(b"Hello", "127.0.0.1:1".parse().unwrap())
+            .unwrap(),
+        5
+    );
+
+    socket1.connect(addr2).unwrap();
+    socket2.connect(addr1).unwrap();
+    assert_eq!(socket1.peer_addr().unwrap(), addr2);
+    assert_eq!(socket1.send(b"Hello").unwrap(), 5);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SERVER, Interest::READABLE)],
+    );
+    assert_eq!(socket2.peek(&mut buf).unwrap(), 5);
+    assert_eq!(socket2.recv(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"Hello");
+
+    // Connected sockets only receive datagrams from their peer.
+    let socket3 = UdpSocket::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    socket3.send_to(b"Hello", addr2).unwrap();
+    assert_would_block(socket2.recv(&mut buf));
+}
+
+#[test]
+fn udp_datagram_loss() {
+    fn received(seed: u64, loss: f64) -> Vec<u8> {
+        let network = Network::with_seed(seed);
+        network.set_datagram_loss(loss);
+        let socket1 = UdpSocket::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+        let socket2 = UdpSocket::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr2 = socket2.local_addr().unwrap();
+        for i in 0..100 {
+            socket1.send_to(&[i], addr2).unwrap();
+        }
+        let mut received = Vec::new();
+        let mut buf = [0; 1];
+        while let Ok((1, _)) = socket2.recv_from(&mut buf) {
+            received.push(buf[0]);
+        }
+        received
+    }
+
+    assert_eq!(received(1, 0.0), (0..100).collect::<Vec<_>>());
+    assert!(received(1, 1.0).is_empty());
+
+    let partial = received(1, 0.5);
+    assert!(partial.len() > 10 && partial.len() < 90, "{:?}", partial);
+    // Reproducible using the same seed.
+    assert_eq!(partial, received(1, 0.5));
+    assert_ne!(partial, received(2, 0.5));
+}
+
+#[test]
+fn udp_datagram_reorder() {
+    let network = Network::new();
+    network.set_datagram_reorder(0.5);
+    let socket1 = UdpSocket::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    let socket2 = UdpSocket::bind(&network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr2 = socket2.local_addr().unwrap();
+    for i in 0..100 {
+        socket1.send_to(&[i], addr2).unwrap();
+    }
+
+    let mut received = Vec::new();
+    let mut buf = [0; 1];
+    while let Ok((1, _)) = socket2.recv_from(&mut buf) {
+        received.push(buf[0]);
+    }
+    assert_ne!(received, (0..100).collect::<Vec<_>>());
+    received.sort();
+    assert_eq!(received, (0..100).collect::<Vec<_>>());
+}
+
+/// Create a connected client and server stream, with the server registered
+/// with `SERVER` for readable interest.
+fn connect(poll: &mut Poll, network: &Network) -> (TcpListener, TcpStream, TcpStream) {
+    let mut listener = TcpListener::bind(network, "127.0.0.1:0".parse().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut listener, LISTENER, Interest::READABLE)
+        .unwrap();
+
+    let client = TcpStream::connect(network, listener.local_addr().unwrap()).unwrap();
+    let mut events = mio::Events::with_capacity(8);
+    expect_events(
+        poll,
+        &mut events,
+        vec![ExpectEvent::new(LISTENER, Interest::READABLE)],
+    );
+    let (mut server, peer_addr) = listener.accept().unwrap();
+    assert_eq!(peer_addr, client.local_addr().unwrap());
+    poll.registry()
+        .register(&mut server, SERVER, Interest::READABLE)
+        .unwrap();
+    (listener, client, server)
+}
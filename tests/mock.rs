@@ -0,0 +1,397 @@
+#![cfg(all(unix, feature = "mock"))]
+
+use mio::event::Event;
+use mio::mock::{self, MockSource, Readiness};
+use mio::{Events, Interest, Poll, Token, Waker};
+use std::io;
+use std::time::Duration;
+
+mod util;
+use util::{assert_send, assert_sync, init_with_poll, poll_retry};
+
+const ID1: Token = Token(0);
+const ID2: Token = Token(1);
+const ID3: Token = Token(2);
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<MockSource>();
+    assert_sync::<MockSource>();
+}
+
+#[test]
+fn edge_triggered() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source1 = MockSource::new();
+    let mut source2 = MockSource::new();
+    let mut source3 = MockSource::new();
+    let registry = &poll.registry().try_clone().unwrap();
+    registry
+        .register(&mut source1, ID1, Interest::READABLE)
+        .unwrap();
+    registry
+        .register(&mut source2, ID2, Interest::WRITABLE)
+        .unwrap();
+    registry
+        .register(&mut source3, ID3, Interest::READABLE | Interest::WRITABLE)
+        .unwrap();
+    expect_mock_events(&mut poll, &mut events, &[]);
+
+    // Events are returned in order of `set_readiness` calls.
+    mock::set_readiness(registry, ID3, Readiness::WRITABLE).unwrap();
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    mock::set_readiness(registry, ID2, Readiness::WRITABLE).unwrap();
+    expect_mock_events(
+        &mut poll,
+        &mut events,
+        &[
+            (ID3, Readiness::WRITABLE),
+            (ID1, Readiness::READABLE),
+            (ID2, Readiness::WRITABLE),
+        ],
+    );
+    // Edge-triggered events are only returned once.
+    expect_mock_events(&mut poll, &mut events, &[]);
+
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[(ID1, Readiness::READABLE)]);
+}
+
+#[test]
+fn merge_pending_readiness() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source1 = MockSource::new();
+    let mut source2 = MockSource::new();
+    let registry = &poll.registry().try_clone().unwrap();
+    registry
+        .register(&mut source1, ID1, Interest::READABLE)
+        .unwrap();
+    registry
+        .register(&mut source2, ID2, Interest::READABLE)
+        .unwrap();
+
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    mock::set_readiness(registry, ID2, Readiness::READABLE).unwrap();
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    mock::set_readiness(registry, ID1, Readiness::READ_CLOSED).unwrap();
+    expect_mock_events(
+        &mut poll,
+        &mut events,
+        &[
+            (ID1, Readiness::READABLE | Readiness::READ_CLOSED),
+            (ID2, Readiness::READABLE),
+        ],
+    );
+}
+
+#[test]
+fn filtered_by_interests() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source = MockSource::new();
+    let registry = &poll.registry().try_clone().unwrap();
+    registry
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap();
+
+    mock::set_readiness(registry, ID1, Readiness::WRITABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[]);
+
+    // Closed and error readiness is always returned.
+    let readiness = Readiness::READABLE
+        | Readiness::WRITABLE
+        | Readiness::ERROR
+        | Readiness::READ_CLOSED
+        | Readiness::WRITE_CLOSED;
+    mock::set_readiness(registry, ID1, readiness).unwrap();
+    expect_mock_events(
+        &mut poll,
+        &mut events,
+        &[(
+            ID1,
+            Readiness::READABLE
+                | Readiness::ERROR
+                | Readiness::READ_CLOSED
+                | Readiness::WRITE_CLOSED,
+        )],
+    );
+
+    registry
+        .reregister(&mut source, ID2, Interest::WRITABLE)
+        .unwrap();
+    mock::set_readiness(registry, ID2, Readiness::READABLE | Readiness::WRITABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[(ID2, Readiness::WRITABLE)]);
+}
+
+#[test]
+fn level_triggered() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source1 = MockSource::new();
+    let mut source2 = MockSource::new();
+    let registry = &poll.registry().try_clone().unwrap();
+    registry
+        .register(&mut source1, ID1, Interest::READABLE | Interest::LEVEL)
+        .unwrap();
+    registry
+        .register(&mut source2, ID2, Interest::READABLE)
+        .unwrap();
+
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    mock::set_readiness(registry, ID2, Readiness::READABLE).unwrap();
+    // Edge-triggered events are returned first.
+    expect_mock_events(
+        &mut poll,
+        &mut events,
+        &[(ID2, Readiness::READABLE), (ID1, Readiness::READABLE)],
+    );
+    expect_mock_events(&mut poll, &mut events, &[(ID1, Readiness::READABLE)]);
+    expect_mock_events(&mut poll, &mut events, &[(ID1, Readiness::READABLE)]);
+
+    mock::set_readiness(registry, ID1, Readiness::EMPTY).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[]);
+}
+
+#[test]
+fn oneshot() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source = MockSource::new();
+    let registry = &poll.registry().try_clone().unwrap();
+    registry
+        .register(&mut source, ID1, Interest::READABLE | Interest::ONESHOT)
+        .unwrap();
+
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[(ID1, Readiness::READABLE)]);
+
+    // Disarmed until reregistered.
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[]);
+
+    registry
+        .reregister(&mut source, ID1, Interest::READABLE | Interest::ONESHOT)
+        .unwrap();
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[(ID1, Readiness::READABLE)]);
+}
+
+#[test]
+fn events_capacity() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(2);
+
+    let mut sources = [MockSource::new(), MockSource::new(), MockSource::new()];
+    let registry = &poll.registry().try_clone().unwrap();
+    for (i, source) in sources.iter_mut().enumerate() {
+        registry
+            .register(source, Token(i), Interest::READABLE)
+            .unwrap();
+    }
+    for i in (0..3).rev() {
+        mock::set_readiness(registry, Token(i), Readiness::READABLE).unwrap();
+    }
+
+    expect_mock_events(
+        &mut poll,
+        &mut events,
+        &[
+            (Token(2), Readiness::READABLE),
+            (Token(1), Readiness::READABLE),
+        ],
+    );
+    expect_mock_events(&mut poll, &mut events, &[(Token(0), Readiness::READABLE)]);
+}
+
+#[test]
+fn deregister() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source = MockSource::new();
+    let registry = &poll.registry().try_clone().unwrap();
+    registry
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap();
+    mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap();
+    registry.deregister(&mut source).unwrap();
+    // Pending events are removed as well.
+    expect_mock_events(&mut poll, &mut events, &[]);
+
+    let err = mock::set_readiness(registry, ID1, Readiness::READABLE).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    let err = registry.deregister(&mut source).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    let err = registry
+        .reregister(&mut source, ID1, Interest::READABLE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    // Can be registered again.
+    registry
+        .register(&mut source, ID2, Interest::READABLE)
+        .unwrap();
+    mock::set_readiness(registry, ID2, Readiness::READABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[(ID2, Readiness::READABLE)]);
+}
+
+#[test]
+fn drop_source() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source = MockSource::new();
+    poll.registry()
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap();
+    mock::set_readiness(poll.registry(), ID1, Readiness::READABLE).unwrap();
+    drop(source);
+
+    expect_mock_events(&mut poll, &mut events, &[]);
+    let err = mock::set_readiness(poll.registry(), ID1, Readiness::READABLE).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn register_twice() {
+    let poll1 = Poll::new().unwrap();
+    let poll2 = Poll::new().unwrap();
+
+    let mut source = MockSource::new();
+    poll1
+        .registry()
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap();
+    let err = poll1
+        .registry()
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    let err = poll2
+        .registry()
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+}
+
+#[test]
+fn registry_try_clone() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let registry = poll.registry().try_clone().unwrap();
+    let mut source = MockSource::new();
+    registry
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap();
+    mock::set_readiness(poll.registry(), ID1, Readiness::READABLE).unwrap();
+    expect_mock_events(&mut poll, &mut events, &[(ID1, Readiness::READABLE)]);
+
+    let handle = std::thread::spawn(move || {
+        mock::set_readiness(&registry, ID1, Readiness::READ_CLOSED).unwrap();
+        drop(source);
+    });
+    handle.join().unwrap();
+    expect_mock_events(&mut poll, &mut events, &[]);
+}
+
+#[test]
+fn wake_up_poll() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let registry = poll.registry().try_clone().unwrap();
+    let mut source = MockSource::new();
+    registry
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap();
+
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        mock::set_readiness(&registry, ID1, Readiness::READABLE).unwrap();
+        source
+    });
+
+    // Setting readiness from another thread should wake up the poll.
+    poll_retry(&mut poll, &mut events, None);
+    let got: Vec<Token> = events.iter().map(|event| event.token()).collect();
+    assert_eq!(got, vec![ID1]);
+    drop(handle.join().unwrap());
+}
+
+#[test]
+fn with_os_events() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let waker = Waker::new(poll.registry(), ID1).unwrap();
+    let mut source = MockSource::new();
+    poll.registry()
+        .register(&mut source, ID2, Interest::READABLE)
+        .unwrap();
+
+    waker.wake().unwrap();
+    mock::set_readiness(poll.registry(), ID2, Readiness::READABLE).unwrap();
+
+    // The mock events are returned first, followed by the waker event, which
+    // may need another poll.
+    poll_retry(&mut poll, &mut events, Some(Duration::from_secs(1)));
+    let mut tokens: Vec<Token> = events.iter().map(|event| event.token()).collect();
+    assert_eq!(tokens[0], ID2);
+    if tokens.len() == 1 {
+        poll_retry(&mut poll, &mut events, Some(Duration::from_secs(1)));
+        tokens.extend(events.iter().map(|event| event.token()));
+    }
+    assert_eq!(tokens, vec![ID2, ID1]);
+    assert!(events.iter().last().unwrap().is_readable());
+}
+
+#[test]
+fn event_debug() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut source = MockSource::new();
+    poll.registry()
+        .register(&mut source, ID1, Interest::READABLE)
+        .unwrap();
+    mock::set_readiness(
+        poll.registry(),
+        ID1,
+        Readiness::READABLE | Readiness::READ_CLOSED,
+    )
+    .unwrap();
+    poll_retry(&mut poll, &mut events, Some(Duration::from_millis(0)));
+    let event = events.iter().next().unwrap();
+    let debug = format!("{:#?}", event);
+    assert!(debug.contains("mock_event"), "{}", debug);
+    assert!(debug.contains("READABLE"), "{}", debug);
+    assert!(debug.contains("READ_CLOSED"), "{}", debug);
+
+    assert_eq!(
+        format!("{:?}", Readiness::READABLE | Readiness::WRITE_CLOSED),
+        "READABLE | WRITE_CLOSED"
+    );
+    assert_eq!(format!("{:?}", Readiness::EMPTY), "EMPTY");
+}
+
+/// Poll without blocking, expecting exactly the events `expected`, in order.
+fn expect_mock_events(poll: &mut Poll, events: &mut Events, expected: &[(Token, Readiness)]) {
+    poll_retry(poll, events, Some(Duration::from_millis(0)));
+    let got: Vec<(Token, Readiness)> = events
+        .iter()
+        .map(|event| (event.token(), readiness(event)))
+        .collect();
+    assert_eq!(got, expected);
+}
+
+fn readiness(event: &Event) -> Readiness {
+    let flags = [
+        (event.is_readable(), Readiness::READABLE),
+        (event.is_writable(), Readiness::WRITABLE),
+        (event.is_error(), Readiness::ERROR),
+        (event.is_read_closed(), Readiness::READ_CLOSED),
+        (event.is_write_closed(), Readiness::WRITE_CLOSED),
+        (event.is_priority(), Readiness::PRIORITY),
+    ];
+    flags
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(Readiness::EMPTY, |readiness, (_, flag)| readiness | *flag)
+}
@@ -6,7 +6,7 @@ use mio::{event, Events, Interest, Poll, Registry, Token};
 use std::net;
 use std::sync::{Arc, Barrier};
 use std::thread::{self, sleep};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{fmt, io};
 
 mod util;
@@ -91,6 +91,51 @@ fn zero_duration_polls_events() {
     drop(listener);
 }
 
+#[test]
+fn poll_never_returns_before_timeout() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let timeouts = [
+        Duration::from_nanos(1),
+        Duration::from_micros(100),
+        Duration::from_micros(999),
+        Duration::from_micros(1_001),
+        Duration::from_micros(1_900),
+        Duration::from_micros(10_500),
+        Duration::from_millis(20),
+    ];
+    for timeout in timeouts.iter().copied() {
+        let start = Instant::now();
+        poll.poll(&mut events, Some(timeout)).unwrap();
+        let elapsed = start.elapsed();
+        assert!(events.is_empty());
+        assert!(
+            elapsed >= timeout,
+            "poll returned after {:?}, before the timeout of {:?}",
+            elapsed,
+            timeout
+        );
+    }
+}
+
+#[test]
+fn poll_sub_millisecond_timeout() {
+    let (mut poll, mut events) = init_with_poll();
+
+    // A sub-millisecond timeout should not be rounded down to zero, or be
+    // rounded up to a much larger timeout.
+    let timeout = Duration::from_micros(100);
+    let start = Instant::now();
+    poll.poll(&mut events, Some(timeout)).unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= timeout, "poll returned after {:?}", elapsed);
+    assert!(
+        elapsed < Duration::from_millis(100),
+        "poll returned after {:?}",
+        elapsed
+    );
+}
+
 #[test]
 fn poll_closes_fd() {
     init();
@@ -593,6 +638,118 @@ fn poll_erroneous_registration() {
     assert_error(registry.deregister(&mut source), "deregister");
 }
 
+#[test]
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    not(any(feature = "io-uring", feature = "poll-fallback"))
+))]
+fn inspect() {
+    use mio::unix::SourceFd;
+    use mio::Waker;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixDatagram;
+
+    init();
+    let poll = Poll::new().unwrap();
+    let registry = poll.registry();
+
+    let (socket1, socket2) = UnixDatagram::pair().unwrap();
+    let fd1 = socket1.as_raw_fd();
+    let fd2 = socket2.as_raw_fd();
+    registry
+        .register(&mut SourceFd(&fd1), ID1, Interest::READABLE)
+        .unwrap();
+    registry
+        .register(
+            &mut SourceFd(&fd2),
+            ID2,
+            Interest::WRITABLE | Interest::LEVEL | Interest::ONESHOT,
+        )
+        .unwrap();
+    let _waker = Waker::new(registry, ID3).unwrap();
+
+    let mut registrations = inspect_registrations(registry);
+    registrations.sort_by_key(|registration| registration.token());
+    assert_eq!(registrations.len(), 3);
+    assert_eq!(registrations[0].fd(), fd1);
+    assert_eq!(registrations[0].token(), ID1);
+    assert_eq!(registrations[0].interests(), Some(Interest::READABLE));
+    assert_ne!(registrations[0].events() & libc::EPOLLET as u32, 0);
+    assert_eq!(registrations[1].fd(), fd2);
+    assert_eq!(registrations[1].token(), ID2);
+    assert_eq!(
+        registrations[1].interests(),
+        Some(Interest::WRITABLE | Interest::LEVEL | Interest::ONESHOT)
+    );
+    assert_eq!(registrations[2].token(), ID3);
+
+    registry.deregister(&mut SourceFd(&fd1)).unwrap();
+    let registrations = inspect_registrations(registry);
+    assert_eq!(registrations.len(), 2);
+    assert!(registrations.iter().all(|r| r.fd() != fd1));
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    not(any(feature = "io-uring", feature = "poll-fallback"))
+))]
+fn inspect_duplicated_fd() {
+    use mio::unix::SourceFd;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    use std::os::unix::net::UnixDatagram;
+
+    init();
+    let poll = Poll::new().unwrap();
+
+    let (socket, _) = UnixDatagram::pair().unwrap();
+    let fd = socket.as_raw_fd();
+    poll.registry()
+        .register(&mut SourceFd(&fd), ID1, Interest::READABLE)
+        .unwrap();
+
+    // Closing the file descriptor while a duplicate is open doesn't remove
+    // the registration.
+    let dup = unsafe { UnixDatagram::from_raw_fd(libc::dup(fd)) };
+    drop(socket);
+    let registrations = inspect_registrations(poll.registry());
+    assert_eq!(registrations.len(), 1);
+    assert_eq!(registrations[0].fd(), fd);
+    assert_eq!(registrations[0].token(), ID1);
+
+    // Once all duplicates are closed it is removed.
+    drop(dup);
+    assert!(inspect_registrations(poll.registry()).is_empty());
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    any(feature = "io-uring", feature = "poll-fallback")
+))]
+fn inspect_not_supported() {
+    init();
+    let poll = Poll::new().unwrap();
+    let err = poll.registry().inspect().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+/// Returns `Registry::inspect`, without the internal registration of the
+/// `mock` feature.
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    not(any(feature = "io-uring", feature = "poll-fallback"))
+))]
+fn inspect_registrations(registry: &Registry) -> Vec<mio::EpollRegistration> {
+    let registrations = registry.inspect().unwrap();
+    #[cfg(feature = "mock")]
+    let registrations = registrations
+        .into_iter()
+        .filter(|r| r.token() != mio::mock::WAKE_TOKEN)
+        .collect();
+    registrations
+}
+
 /// Assert that `result` is an error and the formatted error (via
 /// `fmt::Display`) equals `expected_msg`.
 pub fn assert_error<T, E: fmt::Display>(result: Result<T, E>, expected_msg: &str) {
@@ -0,0 +1,287 @@
+#![cfg(all(unix, feature = "os-poll", feature = "os-util"))]
+
+use mio::unix::pipe::Receiver;
+use mio::unix::Process;
+use mio::{Events, Interest, Poll, Token};
+use std::io::{self, Read, Write};
+use std::process::Command;
+use std::thread::sleep;
+use std::time::Duration;
+
+mod util;
+use util::{
+    assert_send, assert_socket_non_blocking, assert_sync, assert_would_block, expect_events,
+    expect_no_events, init_with_poll, poll_retry, ExpectEvent,
+};
+
+const ID1: Token = Token(0);
+const ID2: Token = Token(1);
+const ID3: Token = Token(2);
+const ID4: Token = Token(3);
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Process>();
+    assert_sync::<Process>();
+}
+
+#[test]
+fn process_exit() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let child = Command::new("/bin/true").spawn().unwrap();
+    let mut process = Process::new(child).unwrap();
+    poll.registry()
+        .register(&mut process, ID1, Interest::READABLE)
+        .unwrap();
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    let status = process.try_wait().unwrap().unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn process_already_exited() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut child = Command::new("/bin/true").spawn().unwrap();
+    let pid = child.id();
+    // Reap the process before creating `Process`.
+    assert!(child.wait().unwrap().success());
+    let mut process = Process::new(child).unwrap();
+    assert_eq!(process.id(), pid);
+    poll.registry()
+        .register(&mut process, ID1, Interest::READABLE)
+        .unwrap();
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    assert!(process.try_wait().unwrap().unwrap().success());
+}
+
+#[test]
+fn process_kill() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let child = Command::new("/bin/sleep").arg("60").spawn().unwrap();
+    let mut process = Process::new(child).unwrap();
+    poll.registry()
+        .register(&mut process, ID1, Interest::READABLE)
+        .unwrap();
+
+    assert!(process.try_wait().unwrap().is_none());
+
+    process.child_mut().kill().unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    let status = process.try_wait().unwrap().unwrap();
+    assert!(!status.success());
+}
+
+#[test]
+fn process_multiple() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let child = Command::new("/bin/sleep").arg("60").spawn().unwrap();
+    let mut process1 = Process::new(child).unwrap();
+    let child = Command::new("/bin/sleep").arg("0.1").spawn().unwrap();
+    let mut process2 = Process::new(child).unwrap();
+    poll.registry()
+        .register(&mut process1, ID1, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut process2, ID2, Interest::READABLE)
+        .unwrap();
+
+    // The fallback implementation can wake up any process.
+    let mut status = None;
+    while status.is_none() {
+        poll_retry(&mut poll, &mut events, None);
+        assert!(process1.try_wait().unwrap().is_none());
+        status = process2.try_wait().unwrap();
+    }
+    assert!(status.unwrap().success());
+
+    process1.child_mut().kill().unwrap();
+    let mut status = None;
+    while status.is_none() {
+        poll_retry(&mut poll, &mut events, None);
+        status = process1.try_wait().unwrap();
+    }
+    assert!(!status.unwrap().success());
+}
+
+#[test]
+fn process_deregister() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let child = Command::new("/bin/true").spawn().unwrap();
+    let mut process = Process::new(child).unwrap();
+    poll.registry()
+        .register(&mut process, ID1, Interest::READABLE)
+        .unwrap();
+    poll.registry().deregister(&mut process).unwrap();
+
+    sleep(Duration::from_millis(100));
+    expect_no_events(&mut poll, &mut events);
+
+    let mut child = process.into_child();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn spawn_cat() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut process = Process::spawn(&mut Command::new("/bin/cat")).unwrap();
+    let mut stdin = process.take_stdin().unwrap();
+    let mut stdout = process.take_stdout().unwrap();
+    let mut stderr = process.take_stderr().unwrap();
+    assert!(process.take_stdin().is_none());
+    assert!(process.take_stdout().is_none());
+    assert!(process.take_stderr().is_none());
+    assert_socket_non_blocking(&stdin);
+    assert_socket_non_blocking(&stdout);
+    assert_socket_non_blocking(&stderr);
+
+    poll.registry()
+        .register(&mut process, ID1, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut stdin, ID2, Interest::WRITABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut stdout, ID3, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut stderr, ID4, Interest::READABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID2, Interest::WRITABLE)],
+    );
+
+    let mut buf = [0; 20];
+    assert_would_block(stdout.read(&mut buf));
+    assert!(process.try_wait().unwrap().is_none());
+
+    // `cat` should echo everything back in order.
+    let mut expected = Vec::new();
+    for i in 0..10 {
+        let msg = format!("message {}\n", i);
+        assert_eq!(stdin.write(msg.as_bytes()).unwrap(), msg.len());
+        expected.extend_from_slice(msg.as_bytes());
+    }
+
+    // Closing stdin makes `cat` exit, closing its stdout and stderr.
+    drop(stdin);
+    let outputs = read_to_end(
+        &mut poll,
+        &mut events,
+        &mut [(ID3, &mut stdout), (ID4, &mut stderr)],
+    );
+    assert_eq!(outputs[0], expected);
+    assert!(outputs[1].is_empty());
+
+    let mut status = None;
+    while status.is_none() {
+        status = process.try_wait().unwrap();
+        if status.is_none() {
+            poll_retry(&mut poll, &mut events, None);
+        }
+    }
+    assert!(status.unwrap().success());
+}
+
+#[test]
+fn spawn_sh() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut command = Command::new("/bin/sh");
+    command
+        .arg("-c")
+        .arg("echo out1; echo err1 >&2; sleep 0.1; echo out2; echo err2 >&2; exit 3");
+    let mut process = Process::spawn(&mut command).unwrap();
+    // Closing stdin right away shouldn't affect the script.
+    drop(process.take_stdin().unwrap());
+    let mut stdout = process.take_stdout().unwrap();
+    let mut stderr = process.take_stderr().unwrap();
+
+    poll.registry()
+        .register(&mut process, ID1, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut stdout, ID3, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut stderr, ID4, Interest::READABLE)
+        .unwrap();
+
+    let outputs = read_to_end(
+        &mut poll,
+        &mut events,
+        &mut [(ID3, &mut stdout), (ID4, &mut stderr)],
+    );
+    assert_eq!(outputs[0], b"out1\nout2\n");
+    assert_eq!(outputs[1], b"err1\nerr2\n");
+
+    let mut status = None;
+    while status.is_none() {
+        status = process.try_wait().unwrap();
+        if status.is_none() {
+            poll_retry(&mut poll, &mut events, None);
+        }
+    }
+    assert_eq!(status.unwrap().code(), Some(3));
+}
+
+/// Read from all `receivers` until the end of the file, expecting an event
+/// with `is_read_closed` set for each.
+fn read_to_end(
+    poll: &mut Poll,
+    events: &mut Events,
+    receivers: &mut [(Token, &mut Receiver)],
+) -> Vec<Vec<u8>> {
+    let mut outputs = vec![Vec::new(); receivers.len()];
+    let mut done = vec![false; receivers.len()];
+    let mut read_closed = vec![false; receivers.len()];
+    let mut buf = [0; 64];
+    loop {
+        for (i, (_, receiver)) in receivers.iter_mut().enumerate() {
+            while !done[i] {
+                match receiver.read(&mut buf) {
+                    Ok(0) => done[i] = true,
+                    Ok(n) => outputs[i].extend_from_slice(&buf[..n]),
+                    Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        assert!(!read_closed[i], "got `WouldBlock` after read closed event");
+                        break;
+                    }
+                    Err(err) => panic!("unexpected error: {}", err),
+                }
+            }
+        }
+
+        if done.iter().all(|done| *done) && read_closed.iter().all(|closed| *closed) {
+            return outputs;
+        }
+
+        poll_retry(poll, events, Some(Duration::from_secs(2)));
+        assert!(!events.is_empty(), "expected events");
+        for event in events.iter() {
+            if let Some(i) = receivers.iter().position(|(t, _)| *t == event.token()) {
+                read_closed[i] |= event.is_read_closed();
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+#![cfg(all(unix, feature = "mock"))]
+
+use mio::mock::{self, MockSource, Readiness};
+use mio::record::{self, Record, Recorder, Replay};
+use mio::{Events, Interest, Poll, Token};
+use std::io;
+use std::time::Duration;
+
+mod util;
+use util::{assert_send, assert_sync};
+
+const ID1: Token = Token(0);
+const ID2: Token = Token(1);
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Recorder<Vec<u8>>>();
+    assert_sync::<Recorder<Vec<u8>>>();
+    assert_send::<Replay<&[u8]>>();
+    assert_sync::<Replay<&[u8]>>();
+}
+
+#[test]
+fn record_and_replay() {
+    let mut events = Events::with_capacity(16);
+    let (log, recorded) = record(&mut events);
+
+    let mut replay = Replay::new(&log[..]).unwrap();
+    let mut source1 = MockSource::new();
+    let mut source2 = MockSource::new();
+    replay
+        .register(&mut source1, ID1, Interest::READABLE)
+        .unwrap();
+    replay
+        .register(&mut source2, ID2, Interest::READABLE | Interest::WRITABLE)
+        .unwrap();
+    for expected in recorded.iter() {
+        replay.poll(&mut events, None).unwrap();
+        assert_eq!(&collect(&events), expected);
+    }
+    replay
+        .reregister(&mut source1, ID1, Interest::WRITABLE)
+        .unwrap();
+    replay.deregister(&mut source2, ID2).unwrap();
+
+    let err = replay.poll(&mut events, None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn records() {
+    let mut events = Events::with_capacity(16);
+    let (log, recorded) = record(&mut events);
+
+    let records = record::records(&log[..])
+        .unwrap()
+        .collect::<io::Result<Vec<Record>>>()
+        .unwrap();
+    assert_eq!(records.len(), 7);
+    match records[0] {
+        Record::Register {
+            token, interests, ..
+        } => {
+            assert_eq!(token, ID1);
+            assert_eq!(interests, Interest::READABLE);
+        }
+        ref record => panic!("unexpected record: {:?}", record),
+    }
+    let mut polls = records.iter().filter_map(|record| match record {
+        Record::Poll { events, .. } => Some(events),
+        _ => None,
+    });
+    for expected in recorded.iter() {
+        assert_eq!(polls.next().unwrap(), expected);
+    }
+    assert!(polls.next().is_none());
+    match records[6] {
+        Record::Deregister { token, .. } => assert_eq!(token, ID2),
+        ref record => panic!("unexpected record: {:?}", record),
+    }
+
+    // Timestamps are monotonic.
+    let elapsed = records
+        .iter()
+        .map(|record| match *record {
+            Record::Poll { elapsed, .. }
+            | Record::Register { elapsed, .. }
+            | Record::Reregister { elapsed, .. }
+            | Record::Deregister { elapsed, .. } => elapsed,
+        })
+        .collect::<Vec<Duration>>();
+    assert!(elapsed.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn replay_diverged() {
+    let mut events = Events::with_capacity(16);
+    let (log, _) = record(&mut events);
+
+    // Different token.
+    let mut replay = Replay::new(&log[..]).unwrap();
+    let err = replay
+        .register(&mut MockSource::new(), ID2, Interest::READABLE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Different interests.
+    let mut replay = Replay::new(&log[..]).unwrap();
+    let err = replay
+        .register(&mut MockSource::new(), ID1, Interest::WRITABLE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Polling before registering.
+    let mut replay = Replay::new(&log[..]).unwrap();
+    let err = replay.poll(&mut events, None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn replay_more_events_than_capacity() {
+    let mut events = Events::with_capacity(16);
+    let (log, _) = record(&mut events);
+
+    let mut replay = Replay::new(&log[..]).unwrap();
+    replay
+        .register(&mut MockSource::new(), ID1, Interest::READABLE)
+        .unwrap();
+    replay
+        .register(
+            &mut MockSource::new(),
+            ID2,
+            Interest::READABLE | Interest::WRITABLE,
+        )
+        .unwrap();
+    // The first poll returned two events.
+    let mut events = Events::with_capacity(1);
+    let err = replay.poll(&mut events, None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn invalid_log() {
+    let err = Replay::new(&b"not a log"[..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let err = Replay::new(&b"mio"[..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // Unknown record kind.
+    let mut log = b"mio-rec\x01".to_vec();
+    log.extend_from_slice(&[9; 9]);
+    let mut records = record::records(&log[..]).unwrap();
+    let err = records.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Truncated record.
+    let mut log = b"mio-rec\x01".to_vec();
+    log.extend_from_slice(&[1, 0, 0]);
+    let mut records = record::records(&log[..]).unwrap();
+    let err = records.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+/// Records registrations and three polls with mock events, returns the log
+/// and the events returned by each poll.
+fn record(events: &mut Events) -> (Vec<u8>, Vec<Vec<(Token, Readiness)>>) {
+    let mut recorder = Recorder::new(Poll::new().unwrap(), Vec::new()).unwrap();
+    let registry = recorder.registry().try_clone().unwrap();
+
+    let mut source1 = MockSource::new();
+    let mut source2 = MockSource::new();
+    recorder
+        .register(&mut source1, ID1, Interest::READABLE)
+        .unwrap();
+    recorder
+        .register(&mut source2, ID2, Interest::READABLE | Interest::WRITABLE)
+        .unwrap();
+
+    let mut recorded = Vec::new();
+    mock::set_readiness(&registry, ID1, Readiness::READABLE).unwrap();
+    mock::set_readiness(&registry, ID2, Readiness::WRITABLE | Readiness::READ_CLOSED).unwrap();
+    recorder
+        .poll(events, Some(Duration::from_millis(0)))
+        .unwrap();
+    recorded.push(collect(events));
+
+    recorder
+        .poll(events, Some(Duration::from_millis(0)))
+        .unwrap();
+    recorded.push(collect(events));
+
+    mock::set_readiness(&registry, ID2, Readiness::READABLE | Readiness::ERROR).unwrap();
+    recorder
+        .poll(events, Some(Duration::from_millis(0)))
+        .unwrap();
+    recorded.push(collect(events));
+
+    recorder
+        .reregister(&mut source1, ID1, Interest::WRITABLE)
+        .unwrap();
+    recorder.deregister(&mut source2, ID2).unwrap();
+
+    assert_eq!(
+        recorded,
+        vec![
+            vec![
+                (ID1, Readiness::READABLE),
+                (ID2, Readiness::WRITABLE | Readiness::READ_CLOSED),
+            ],
+            vec![],
+            vec![(ID2, Readiness::READABLE | Readiness::ERROR)],
+        ]
+    );
+    let (_, log) = recorder.into_inner();
+    (log, recorded)
+}
+
+fn collect(events: &Events) -> Vec<(Token, Readiness)> {
+    events
+        .iter()
+        .map(|event| (event.token(), Readiness::from(event)))
+        .collect()
+}
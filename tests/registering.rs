@@ -1,16 +1,23 @@
 #![cfg(all(feature = "os-poll", feature = "tcp", feature = "udp"))]
 
 use log::{debug, info, trace};
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 use mio::net::UdpSocket;
+#[cfg(unix)]
+use mio::net::UnixStream;
 use mio::net::{TcpListener, TcpStream};
 use mio::{Events, Interest, Poll, Registry, Token};
+#[cfg(unix)]
+use std::io::Read;
 use std::io::{self, Write};
 use std::thread::sleep;
 use std::time::Duration;
 
+#[macro_use]
 mod util;
 use util::{any_local_address, assert_error, init};
+#[cfg(unix)]
+use util::{expect_events, expect_no_events, init_with_poll, ExpectEvent};
 
 const SERVER: Token = Token(0);
 const CLIENT: Token = Token(1);
@@ -142,7 +149,9 @@ pub fn reregister_different_interest_without_poll() {
 }
 
 #[test]
-#[cfg(debug_assertions)] // Check is only present when debug assertions are enabled.
+// Check is only present when debug assertions or the `registration-checks`
+// feature are enabled.
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 fn tcp_register_multiple_event_loops() {
     init();
 
@@ -190,7 +199,9 @@ fn tcp_register_multiple_event_loops() {
 }
 
 #[test]
-#[cfg(debug_assertions)] // Check is only present when debug assertions are enabled.
+// Check is only present when debug assertions or the `registration-checks`
+// feature are enabled.
+#[cfg(any(debug_assertions, feature = "registration-checks"))]
 fn udp_register_multiple_event_loops() {
     init();
 
@@ -240,3 +251,211 @@ fn registering_after_deregistering() {
         .unwrap();
     assert!(events.is_empty());
 }
+
+#[test]
+#[cfg(unix)]
+fn level_triggered() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut s1, mut s2) = UnixStream::pair().unwrap();
+    poll.registry()
+        .register(&mut s1, Token(0), Interest::READABLE | Interest::LEVEL)
+        .unwrap();
+
+    checked_write!(s2.write(b"Hello world!"));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(Token(0), Interest::READABLE)],
+    );
+
+    // Only read part of the data, we should still get another event.
+    let mut buf = [0; 5];
+    expect_read!(s1.read(&mut buf), b"Hello");
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(Token(0), Interest::READABLE)],
+    );
+
+    // Once all data is read there should be no more events.
+    let mut buf = [0; 16];
+    expect_read!(s1.read(&mut buf), b" world!");
+    expect_no_events(&mut poll, &mut events);
+}
+
+#[test]
+#[cfg(unix)]
+fn edge_triggered_by_default() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut s1, mut s2) = UnixStream::pair().unwrap();
+    poll.registry()
+        .register(&mut s1, Token(0), Interest::READABLE)
+        .unwrap();
+
+    checked_write!(s2.write(b"Hello world!"));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(Token(0), Interest::READABLE)],
+    );
+
+    // Not draining the socket means we won't get another event.
+    let mut buf = [0; 5];
+    expect_read!(s1.read(&mut buf), b"Hello");
+    expect_no_events(&mut poll, &mut events);
+}
+
+#[test]
+#[cfg(unix)]
+fn oneshot() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut s1, mut s2) = UnixStream::pair().unwrap();
+    poll.registry()
+        .register(&mut s1, Token(0), Interest::READABLE | Interest::ONESHOT)
+        .unwrap();
+
+    checked_write!(s2.write(b"Hello"));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(Token(0), Interest::READABLE)],
+    );
+
+    // The source is disarmed, so even new data doesn't trigger an event.
+    checked_write!(s2.write(b" world!"));
+    expect_no_events(&mut poll, &mut events);
+
+    // Until it's rearmed.
+    poll.registry()
+        .reregister(&mut s1, Token(1), Interest::READABLE | Interest::ONESHOT)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(Token(1), Interest::READABLE)],
+    );
+    expect_no_events(&mut poll, &mut events);
+
+    // After firing again it's disarmed again, and every reregister rearms it.
+    checked_write!(s2.write(b" Bye!"));
+    expect_no_events(&mut poll, &mut events);
+    poll.registry()
+        .reregister(&mut s1, Token(2), Interest::READABLE | Interest::ONESHOT)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(Token(2), Interest::READABLE)],
+    );
+    expect_no_events(&mut poll, &mut events);
+
+    let mut buf = [0; 32];
+    expect_read!(s1.read(&mut buf), b"Hello world! Bye!");
+
+    poll.registry().deregister(&mut s1).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn register_many_before_poll() {
+    // More registrations than fit in io_uring's submission queue.
+    const N: usize = 600;
+
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut streams = Vec::with_capacity(N);
+    for i in 0..N / 2 {
+        let (mut s1, mut s2) = UnixStream::pair().unwrap();
+        poll.registry()
+            .register(&mut s1, Token(2 * i), Interest::WRITABLE)
+            .unwrap();
+        poll.registry()
+            .register(&mut s2, Token(2 * i + 1), Interest::WRITABLE)
+            .unwrap();
+        streams.push((s1, s2));
+    }
+
+    // All streams are writable, but the events are spread over multiple calls
+    // to `poll`.
+    let mut seen = vec![false; N];
+    let mut remaining = N;
+    for _ in 0..N {
+        if remaining == 0 {
+            break;
+        }
+        poll.poll(&mut events, Some(Duration::from_millis(500)))
+            .unwrap();
+        for event in events.iter() {
+            assert!(event.is_writable());
+            let seen = &mut seen[event.token().0];
+            if !*seen {
+                *seen = true;
+                remaining -= 1;
+            }
+        }
+    }
+    assert_eq!(remaining, 0, "missing events");
+}
+
+#[test]
+#[cfg(unix)]
+fn registration_mode_without_readiness() {
+    let (poll, _) = init_with_poll();
+
+    let (mut s1, _s2) = UnixStream::pair().unwrap();
+    for &interests in &[
+        Interest::LEVEL,
+        Interest::ONESHOT,
+        Interest::LEVEL | Interest::ONESHOT,
+    ] {
+        let err = poll
+            .registry()
+            .register(&mut s1, Token(0), interests)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    poll.registry()
+        .register(&mut s1, Token(0), Interest::READABLE)
+        .unwrap();
+    let err = poll
+        .registry()
+        .reregister(&mut s1, Token(0), Interest::ONESHOT)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    poll.registry().deregister(&mut s1).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn reserved_token() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut s1, _s2) = UnixStream::pair().unwrap();
+    let err = poll
+        .registry()
+        .register(&mut s1, Token(usize::max_value()), Interest::WRITABLE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    poll.registry()
+        .register(&mut s1, Token(0), Interest::WRITABLE)
+        .unwrap();
+    let err = poll
+        .registry()
+        .reregister(&mut s1, Token(usize::max_value()), Interest::READABLE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    // The failed reregistration leaves the registration unchanged, also with
+    // a sub-millisecond timeout, which uses the selector's timer on Linux.
+    poll.poll(&mut events, Some(Duration::from_micros(100)))
+        .unwrap();
+    assert!(events
+        .iter()
+        .any(|event| event.token() == Token(0) && event.is_writable()));
+    poll.registry().deregister(&mut s1).unwrap();
+}
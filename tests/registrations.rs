@@ -0,0 +1,157 @@
+#![cfg(all(
+    unix,
+    feature = "os-poll",
+    feature = "os-util",
+    feature = "registration-checks"
+))]
+
+use mio::unix::pipe;
+use mio::{Interest, Poll, Token};
+
+mod util;
+use util::{assert_error, init};
+
+const RECEIVER: Token = Token(0);
+const SENDER: Token = Token(1);
+
+#[test]
+fn registrations() {
+    init();
+
+    let poll = Poll::new().unwrap();
+    let registry = poll.registry().try_clone().unwrap();
+    assert!(poll.registry().registrations().is_empty());
+
+    let (mut sender1, mut receiver1) = pipe::new().unwrap();
+    let (sender2, mut receiver2) = pipe::new().unwrap();
+    poll.registry()
+        .register(&mut sender1, SENDER, Interest::WRITABLE)
+        .unwrap();
+    // Registrations made using a clone are included.
+    registry
+        .register(&mut receiver1, RECEIVER, Interest::READABLE)
+        .unwrap();
+    registry
+        .register(&mut receiver2, Token(2), Interest::READABLE)
+        .unwrap();
+    assert_eq!(
+        poll.registry().registrations(),
+        vec![
+            (RECEIVER, Interest::READABLE),
+            (SENDER, Interest::WRITABLE),
+            (Token(2), Interest::READABLE),
+        ]
+    );
+
+    poll.registry()
+        .reregister(&mut sender1, Token(3), Interest::WRITABLE)
+        .unwrap();
+    poll.registry().deregister(&mut receiver1).unwrap();
+    assert_eq!(
+        registry.registrations(),
+        vec![
+            (Token(2), Interest::READABLE),
+            (Token(3), Interest::WRITABLE)
+        ]
+    );
+
+    // Dropped sources are removed.
+    drop(receiver2);
+    drop(sender2);
+    assert_eq!(
+        poll.registry().registrations(),
+        vec![(Token(3), Interest::WRITABLE)]
+    );
+
+    // Failed registrations are not tracked, nor do they change the existing
+    // registration.
+    let poll2 = Poll::new().unwrap();
+    let res = poll2
+        .registry()
+        .register(&mut sender1, SENDER, Interest::WRITABLE);
+    assert_error(res, "I/O source already registered with a `Registry`");
+    assert!(poll2.registry().registrations().is_empty());
+    poll.registry()
+        .reregister(&mut sender1, SENDER, Interest::WRITABLE)
+        .unwrap();
+    assert_eq!(
+        poll.registry().registrations(),
+        vec![(SENDER, Interest::WRITABLE)]
+    );
+}
+
+#[test]
+fn register_twice() {
+    init();
+
+    let poll = Poll::new().unwrap();
+    let (_, mut receiver) = pipe::new().unwrap();
+    poll.registry()
+        .register(&mut receiver, RECEIVER, Interest::READABLE)
+        .unwrap();
+
+    let res = poll
+        .registry()
+        .register(&mut receiver, SENDER, Interest::READABLE);
+    assert_error(res, "I/O source already registered with a `Registry`");
+    assert_eq!(
+        poll.registry().registrations(),
+        vec![(RECEIVER, Interest::READABLE)]
+    );
+}
+
+#[test]
+fn deregister_from_different_registry() {
+    init();
+
+    let poll1 = Poll::new().unwrap();
+    let poll2 = Poll::new().unwrap();
+    let (_, mut receiver) = pipe::new().unwrap();
+    poll1
+        .registry()
+        .register(&mut receiver, RECEIVER, Interest::READABLE)
+        .unwrap();
+
+    let res = poll2.registry().deregister(&mut receiver);
+    assert_error(res, "I/O source not registered with `Registry`");
+    let res = poll2
+        .registry()
+        .reregister(&mut receiver, RECEIVER, Interest::READABLE);
+    assert_error(
+        res,
+        "I/O source already registered with a different `Registry`",
+    );
+
+    // Still registered with the first registry.
+    poll1.registry().deregister(&mut receiver).unwrap();
+    assert!(poll1.registry().registrations().is_empty());
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn mock_sources() {
+    use mio::mock::MockSource;
+
+    init();
+
+    let poll = Poll::new().unwrap();
+    let mut source = MockSource::new();
+    let (_, mut receiver) = pipe::new().unwrap();
+    poll.registry()
+        .register(&mut source, SENDER, Interest::WRITABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut receiver, RECEIVER, Interest::READABLE)
+        .unwrap();
+    // The pipe used internally to wake up the poll isn't included.
+    assert_eq!(
+        poll.registry().registrations(),
+        vec![(RECEIVER, Interest::READABLE), (SENDER, Interest::WRITABLE)]
+    );
+
+    drop(source);
+    assert_eq!(
+        poll.registry().registrations(),
+        vec![(RECEIVER, Interest::READABLE)]
+    );
+}
@@ -0,0 +1,105 @@
+#![cfg(all(unix, feature = "os-poll", feature = "os-util"))]
+
+use mio::unix::Signals;
+use mio::{Interest, Token};
+
+mod util;
+use util::{
+    assert_send, assert_socket_close_on_exec, assert_socket_non_blocking, assert_sync,
+    assert_would_block, expect_events, expect_no_events, init_with_poll, ExpectEvent,
+};
+
+const ID: Token = Token(0);
+
+// NOTE: tests run in parallel, so each test uses different signals.
+
+fn raise(signal: libc::c_int) {
+    assert_eq!(unsafe { libc::raise(signal) }, 0);
+}
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Signals>();
+    assert_sync::<Signals>();
+}
+
+#[test]
+fn signals_single() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut signals = Signals::new(&[libc::SIGUSR1]).unwrap();
+    assert_socket_non_blocking(&signals);
+    assert_socket_close_on_exec(&signals);
+    poll.registry()
+        .register(&mut signals, ID, Interest::READABLE)
+        .unwrap();
+
+    assert_would_block(signals.receive());
+    expect_no_events(&mut poll, &mut events);
+
+    raise(libc::SIGUSR1);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    assert_eq!(signals.receive().unwrap(), libc::SIGUSR1);
+    assert_would_block(signals.receive());
+
+    // Again, to check the signal handling is still in place.
+    raise(libc::SIGUSR1);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    assert_eq!(signals.receive().unwrap(), libc::SIGUSR1);
+    assert_would_block(signals.receive());
+}
+
+#[test]
+fn signals_pending() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut signals = Signals::new(&[libc::SIGUSR2, libc::SIGWINCH]).unwrap();
+    poll.registry()
+        .register(&mut signals, ID, Interest::READABLE)
+        .unwrap();
+
+    assert_eq!(signals.pending().count(), 0);
+
+    raise(libc::SIGUSR2);
+    raise(libc::SIGWINCH);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    let mut pending = signals.pending().collect::<Result<Vec<_>, _>>().unwrap();
+    pending.sort();
+    let mut expected = vec![libc::SIGUSR2, libc::SIGWINCH];
+    expected.sort();
+    assert_eq!(pending, expected);
+    assert_would_block(signals.receive());
+}
+
+#[test]
+fn signals_shared() {
+    // Dropping one `Signals` shouldn't affect another handling the same
+    // signal.
+    let first = Signals::new(&[libc::SIGURG]).unwrap();
+    let second = Signals::new(&[libc::SIGURG]).unwrap();
+    drop(first);
+
+    raise(libc::SIGURG);
+    assert_eq!(second.receive().unwrap(), libc::SIGURG);
+    assert_would_block(second.receive());
+}
+
+#[test]
+fn signals_invalid() {
+    let err = Signals::new(&[0]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = Signals::new(&[libc::SIGUSR1, -1]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
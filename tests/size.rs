@@ -1,6 +1,13 @@
 #[test]
 #[cfg(unix)]
 #[cfg(not(debug_assertions))]
+// The io_uring and poll(2) selectors need to keep track of registrations in
+// the types, and the `registration-checks` feature keeps the selector id.
+#[cfg(not(any(
+    feature = "poll-fallback",
+    feature = "registration-checks",
+    all(feature = "io-uring", target_os = "linux")
+)))]
 fn assert_size() {
     use mio::net::*;
     use std::mem::size_of;
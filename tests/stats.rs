@@ -0,0 +1,153 @@
+#![cfg(all(feature = "os-poll", feature = "stats"))]
+
+use mio::stats::{Hook, HISTOGRAM_BUCKETS};
+use mio::{Events, Poll, Token, Waker};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+
+mod util;
+use util::{assert_send, assert_sync, init_with_poll};
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Poll>();
+    assert_sync::<Poll>();
+}
+
+#[test]
+fn no_polls() {
+    let (poll, _) = init_with_poll();
+
+    let stats = poll.stats();
+    assert_eq!(stats.polls(), 0);
+    assert_eq!(stats.events(), 0);
+    assert_eq!(stats.histogram(), &[0; HISTOGRAM_BUCKETS]);
+    assert_eq!(stats.blocked(), Duration::from_secs(0));
+    assert_eq!(stats.returned(), Duration::from_secs(0));
+    assert_eq!(stats.wakeups(), 0);
+    assert_eq!(stats.timeouts(), 0);
+}
+
+#[test]
+fn counters() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let wakers = (0..3)
+        .map(|n| Waker::new(poll.registry(), Token(n)).unwrap())
+        .collect::<Vec<Waker>>();
+    for waker in wakers.iter() {
+        waker.wake().unwrap();
+    }
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert_eq!(events.iter().count(), 3);
+
+    sleep(Duration::from_millis(10));
+
+    // Waking twice results in a single event.
+    wakers[0].wake().unwrap();
+    wakers[0].wake().unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert_eq!(events.iter().count(), 1);
+
+    poll.poll(&mut events, Some(Duration::from_millis(10)))
+        .unwrap();
+    assert!(events.is_empty());
+
+    let stats = poll.stats();
+    assert_eq!(stats.polls(), 3);
+    assert_eq!(stats.events(), 4);
+    let mut histogram = [0; HISTOGRAM_BUCKETS];
+    histogram[0] = 1; // No events.
+    histogram[1] = 1; // 1 event.
+    histogram[2] = 1; // 2 or 3 events.
+    assert_eq!(stats.histogram(), &histogram);
+    assert!(stats.blocked() >= Duration::from_millis(10));
+    assert!(stats.returned() >= Duration::from_millis(10));
+    assert_eq!(stats.wakeups(), 5);
+    assert_eq!(stats.timeouts(), 1);
+}
+
+#[test]
+fn wakeups_using_cloned_registry() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let registry = poll.registry().try_clone().unwrap();
+    let waker = Waker::new(&registry, Token(0)).unwrap();
+    waker.wake().unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert_eq!(poll.stats().wakeups(), 1);
+
+    // Wakers of another poll instance aren't counted.
+    let poll2 = Poll::new().unwrap();
+    let waker = Waker::new(poll2.registry(), Token(0)).unwrap();
+    waker.wake().unwrap();
+    assert_eq!(poll.stats().wakeups(), 1);
+    assert_eq!(poll2.stats().wakeups(), 1);
+}
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    Before(Option<Duration>),
+    After(bool, usize),
+}
+
+struct TestHook(Arc<Mutex<Vec<Call>>>);
+
+impl Hook for TestHook {
+    fn before_select(&mut self, timeout: Option<Duration>) {
+        self.0.lock().unwrap().push(Call::Before(timeout));
+    }
+
+    fn after_select(&mut self, result: &io::Result<()>, events: &Events) {
+        let call = Call::After(result.is_ok(), events.iter().count());
+        self.0.lock().unwrap().push(call);
+    }
+}
+
+#[test]
+fn hook() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    poll.set_hook(Box::new(TestHook(calls.clone())));
+
+    let waker = Waker::new(poll.registry(), Token(0)).unwrap();
+    waker.wake().unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    poll.poll(&mut events, Some(Duration::from_millis(1)))
+        .unwrap();
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            Call::Before(Some(Duration::from_secs(1))),
+            Call::After(true, 1),
+            Call::Before(Some(Duration::from_millis(1))),
+            Call::After(true, 0),
+        ]
+    );
+
+    assert!(poll.take_hook().is_some());
+    assert!(poll.take_hook().is_none());
+    poll.poll(&mut events, Some(Duration::from_millis(1)))
+        .unwrap();
+    assert_eq!(calls.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn default_hook_methods() {
+    struct NoopHook;
+
+    impl Hook for NoopHook {}
+
+    let (mut poll, mut events) = init_with_poll();
+    poll.set_hook(Box::new(NoopHook));
+    poll.poll(&mut events, Some(Duration::from_millis(1)))
+        .unwrap();
+    assert_eq!(poll.stats().polls(), 1);
+}
@@ -1,5 +1,7 @@
 #![cfg(all(feature = "os-poll", feature = "tcp"))]
 
+#[cfg(not(target_os = "openbsd"))]
+use mio::net::TcpKeepalive;
 use mio::net::TcpListener;
 use mio::{Interest, Token};
 use std::io::{self, Read};
@@ -8,6 +10,8 @@ use std::net::{self, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::sync::{Arc, Barrier};
 use std::thread;
+#[cfg(not(target_os = "openbsd"))]
+use std::time::Duration;
 
 mod util;
 use util::{
@@ -114,6 +118,41 @@ fn get_ttl_without_previous_set() {
     assert!(listener.take_error().unwrap().is_none());
 }
 
+#[test]
+fn keepalive_inherited() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    listener.set_keepalive(true).unwrap();
+    assert!(listener.keepalive().unwrap());
+    #[cfg(not(target_os = "openbsd"))]
+    let keepalive = {
+        let keepalive = TcpKeepalive::new()
+            .with_time(Duration::from_secs(300))
+            .with_interval(Duration::from_secs(30))
+            .with_retries(3);
+        listener.set_keepalive_params(keepalive).unwrap();
+        assert_eq!(listener.keepalive_params().unwrap(), keepalive);
+        keepalive
+    };
+
+    poll.registry()
+        .register(&mut listener, ID1, Interest::READABLE)
+        .unwrap();
+    let _stream = net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+
+    // Accepted streams inherit the settings.
+    let (stream, _) = listener.accept().unwrap();
+    assert!(stream.keepalive().unwrap());
+    #[cfg(not(target_os = "openbsd"))]
+    assert_eq!(stream.keepalive_params().unwrap(), keepalive);
+}
+
 #[cfg(unix)]
 #[test]
 fn raw_fd() {
@@ -267,6 +306,137 @@ fn tcp_listener_two_streams() {
     thread_handle2.join().expect("unable to join thread");
 }
 
+#[test]
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    feature = "os-util",
+    not(feature = "poll-fallback")
+))]
+fn exclusive_wake_up() {
+    use mio::unix::SourceFd;
+    use mio::{Events, Poll};
+    use std::time::Duration;
+
+    const N: usize = 4;
+
+    init();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let address = listener.local_addr().unwrap();
+    let fd = listener.as_raw_fd();
+
+    let barrier = Arc::new(Barrier::new(N + 1));
+    let handles: Vec<_> = (0..N)
+        .map(|_| {
+            let mut poll = Poll::new().unwrap();
+            poll.registry()
+                .register(
+                    &mut SourceFd(&fd),
+                    ID1,
+                    Interest::READABLE | Interest::EXCLUSIVE,
+                )
+                .unwrap();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let mut events = Events::with_capacity(8);
+                barrier.wait();
+                poll.poll(&mut events, Some(Duration::from_millis(500)))
+                    .unwrap();
+                !events.is_empty()
+            })
+        })
+        .collect();
+
+    // Give all threads some time to block in `poll`.
+    barrier.wait();
+    thread::sleep(Duration::from_millis(100));
+    let _stream = net::TcpStream::connect(address).unwrap();
+
+    let woken = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .filter(|woken| *woken)
+        .count();
+    assert!(woken >= 1, "no thread was woken up");
+    assert!(woken < N, "all threads were woken up");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn exclusive_invalid_combinations() {
+    let (poll, _) = init_with_poll();
+    let mut listener1 = TcpListener::from_std(net::TcpListener::bind(any_local_address()).unwrap());
+    let mut listener2 = TcpListener::from_std(net::TcpListener::bind(any_local_address()).unwrap());
+
+    let err = poll
+        .registry()
+        .register(
+            &mut listener1,
+            ID1,
+            Interest::READABLE | Interest::EXCLUSIVE | Interest::ONESHOT,
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let err = poll
+        .registry()
+        .register(
+            &mut listener2,
+            ID2,
+            Interest::READABLE | Interest::EXCLUSIVE | Interest::PRIORITY,
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let err = poll
+        .registry()
+        .register(&mut listener2, ID2, Interest::EXCLUSIVE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    not(feature = "poll-fallback")
+))]
+fn exclusive_reregister() {
+    let (poll, _) = init_with_poll();
+    let mut listener = TcpListener::from_std(net::TcpListener::bind(any_local_address()).unwrap());
+
+    poll.registry()
+        .register(&mut listener, ID1, Interest::READABLE | Interest::EXCLUSIVE)
+        .unwrap();
+    let err = poll
+        .registry()
+        .reregister(&mut listener, ID2, Interest::READABLE | Interest::EXCLUSIVE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    // Deregistering and registering again is allowed.
+    poll.registry().deregister(&mut listener).unwrap();
+    poll.registry()
+        .register(&mut listener, ID2, Interest::READABLE | Interest::EXCLUSIVE)
+        .unwrap();
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    feature = "poll-fallback"
+))]
+fn exclusive_not_supported() {
+    let (poll, _) = init_with_poll();
+    let mut listener = TcpListener::from_std(net::TcpListener::bind(any_local_address()).unwrap());
+
+    let err = poll
+        .registry()
+        .register(&mut listener, ID1, Interest::READABLE | Interest::EXCLUSIVE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
 /// Start `n_connections` connections to `address`. If a `barrier` is provided
 /// it will wait on it after each connection is made before it is dropped.
 fn start_connections(
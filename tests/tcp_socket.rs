@@ -0,0 +1,227 @@
+#![cfg(all(feature = "os-poll", feature = "tcp"))]
+
+use mio::net::TcpSocket;
+use mio::{Interest, Token};
+use std::io::{Read, Write};
+use std::net::{self, SocketAddr};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+mod util;
+use util::{
+    any_local_address, any_local_ipv6_address, assert_send, assert_socket_close_on_exec,
+    assert_socket_non_blocking, assert_sync, expect_events, init, init_with_poll, ExpectEvent,
+};
+
+const ID1: Token = Token(0);
+const ID2: Token = Token(1);
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<TcpSocket>();
+    assert_sync::<TcpSocket>();
+}
+
+#[test]
+fn set_reuseaddr() {
+    init();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    assert_socket_non_blocking(&socket);
+    assert_socket_close_on_exec(&socket);
+
+    socket.set_reuseaddr(true).unwrap();
+    assert!(socket.reuseaddr().unwrap());
+    socket.set_reuseaddr(false).unwrap();
+    assert!(!socket.reuseaddr().unwrap());
+}
+
+#[test]
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+fn set_reuseport() {
+    init();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_reuseport(true).unwrap();
+    assert!(socket.reuseport().unwrap());
+
+    // Two listeners can share an address with `SO_REUSEPORT`.
+    socket.bind(any_local_address()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    let _listener1 = socket.listen(8).unwrap();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_reuseport(true).unwrap();
+    socket.bind(addr).unwrap();
+    let _listener2 = socket.listen(8).unwrap();
+}
+
+#[test]
+fn set_only_v6() {
+    init();
+
+    let socket = TcpSocket::new_v6().unwrap();
+    socket.set_only_v6(true).unwrap();
+    assert!(socket.only_v6().unwrap());
+    socket.set_only_v6(false).unwrap();
+    assert!(!socket.only_v6().unwrap());
+}
+
+#[test]
+fn set_buffer_sizes() {
+    init();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_send_buffer_size(16 * 1024).unwrap();
+    // The OS may adjust the value, e.g. Linux doubles it.
+    assert!(socket.send_buffer_size().unwrap() >= 16 * 1024);
+    socket.set_recv_buffer_size(16 * 1024).unwrap();
+    assert!(socket.recv_buffer_size().unwrap() >= 16 * 1024);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn set_bind_address_no_port() {
+    init();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_bind_address_no_port(true).unwrap();
+    assert!(socket.bind_address_no_port().unwrap());
+
+    // The port is only picked when connecting.
+    socket.bind(any_local_address()).unwrap();
+    assert_eq!(socket.local_addr().unwrap().port(), 0);
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let stream = socket.connect(listener.local_addr().unwrap()).unwrap();
+    let (_, peer_addr) = listener.accept().unwrap();
+    assert_ne!(peer_addr.port(), 0);
+    drop(stream);
+}
+
+#[test]
+fn connect() {
+    smoke_test_connect(any_local_address(), TcpSocket::new_v4);
+}
+
+#[test]
+fn connect_ipv6() {
+    smoke_test_connect(any_local_ipv6_address(), TcpSocket::new_v6);
+}
+
+fn smoke_test_connect<F>(addr: SocketAddr, new_socket: F)
+where
+    F: FnOnce() -> std::io::Result<TcpSocket>,
+{
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(addr).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut stream = new_socket().unwrap().connect(addr).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+
+    let (mut peer, _) = listener.accept().unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    stream.write_all(b"hello").unwrap();
+    let mut buf = [0; 5];
+    peer.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn connect_from_bind_address() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(any_local_address()).unwrap();
+    let local_addr = socket.local_addr().unwrap();
+    assert_ne!(local_addr.port(), 0);
+
+    let mut stream = socket.connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+
+    assert_eq!(stream.local_addr().unwrap(), local_addr);
+    let (_, peer_addr) = listener.accept().unwrap();
+    assert_eq!(peer_addr, local_addr);
+}
+
+#[test]
+fn listen() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let socket = TcpSocket::new_for_addr(any_local_address()).unwrap();
+    socket.set_reuseaddr(true).unwrap();
+    socket.bind(any_local_address()).unwrap();
+    let mut listener = socket.listen(1).unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_ne!(addr.port(), 0);
+
+    poll.registry()
+        .register(&mut listener, ID2, Interest::READABLE)
+        .unwrap();
+    let _stream = net::TcpStream::connect(addr).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID2, Interest::READABLE)],
+    );
+    let (stream, peer_addr) = listener.accept().unwrap();
+    assert_eq!(stream.local_addr().unwrap(), addr);
+    assert_eq!(peer_addr.ip(), addr.ip());
+}
+
+#[test]
+#[cfg(unix)]
+fn listen_without_bind() {
+    init();
+
+    // Listening without binding an address picks a random port. Windows
+    // returns `WSAEINVAL` instead.
+    let socket = TcpSocket::new_v4().unwrap();
+    let listener = socket.listen(8).unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_ne!(addr.port(), 0);
+    let _stream = net::TcpStream::connect(("127.0.0.1", addr.port())).unwrap();
+}
+
+#[test]
+fn bind_address_in_use() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let socket = TcpSocket::new_v4().unwrap();
+    let err = socket.bind(listener.local_addr().unwrap()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+}
+
+#[test]
+#[cfg(unix)]
+fn raw_fd() {
+    init();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    let fd = socket.as_raw_fd();
+    assert_eq!(socket.into_raw_fd(), fd);
+
+    // Still open after `into_raw_fd`.
+    let socket = unsafe { TcpSocket::from_raw_fd(fd) };
+    socket.set_reuseaddr(true).unwrap();
+    assert!(socket.reuseaddr().unwrap());
+}
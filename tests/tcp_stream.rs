@@ -1,5 +1,15 @@
 #![cfg(all(feature = "os-poll", feature = "tcp"))]
 
+#[cfg(not(target_os = "openbsd"))]
+use mio::net::TcpKeepalive;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use mio::net::TcpState;
 use mio::net::TcpStream;
 use mio::{Interest, Token};
 use std::io::{self, IoSlice, IoSliceMut, Read, Write};
@@ -8,6 +18,7 @@ use std::net::{self, Shutdown, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::sync::{mpsc::channel, Arc, Barrier};
 use std::thread;
+use std::time::Duration;
 
 #[macro_use]
 mod util;
@@ -257,6 +268,251 @@ fn get_nodelay_without_previous_set() {
     thread_handle.join().expect("unable to join thread");
 }
 
+#[test]
+fn set_get_keepalive() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let barrier = Arc::new(Barrier::new(2));
+    let (thread_handle, address) = start_listener(1, Some(barrier.clone()), false);
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .expect("unable to register TCP stream");
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+
+    assert!(!stream.keepalive().unwrap());
+    stream.set_keepalive(true).unwrap();
+    assert!(stream.keepalive().unwrap());
+    stream.set_keepalive(false).unwrap();
+    assert!(!stream.keepalive().unwrap());
+
+    #[cfg(not(target_os = "openbsd"))]
+    {
+        let keepalive = TcpKeepalive::new()
+            .with_time(Duration::from_secs(120))
+            .with_interval(Duration::from_secs(15))
+            .with_retries(4);
+        stream.set_keepalive_params(keepalive).unwrap();
+        assert!(stream.keepalive().unwrap());
+        assert_eq!(stream.keepalive_params().unwrap(), keepalive);
+
+        // Options that aren't set are left unchanged.
+        let keepalive = TcpKeepalive::new().with_retries(6);
+        stream.set_keepalive_params(keepalive).unwrap();
+        let params = stream.keepalive_params().unwrap();
+        assert_eq!(params.time(), Some(Duration::from_secs(120)));
+        assert_eq!(params.interval(), Some(Duration::from_secs(15)));
+        assert_eq!(params.retries(), Some(6));
+
+        // Durations shorter than a second are rounded up.
+        let keepalive = TcpKeepalive::new()
+            .with_time(Duration::from_millis(500))
+            .with_interval(Duration::from_millis(1));
+        stream.set_keepalive_params(keepalive).unwrap();
+        let params = stream.keepalive_params().unwrap();
+        assert_eq!(params.time(), Some(Duration::from_secs(1)));
+        assert_eq!(params.interval(), Some(Duration::from_secs(1)));
+    }
+    assert!(stream.take_error().unwrap().is_none());
+
+    barrier.wait();
+    thread_handle.join().expect("unable to join thread");
+}
+
+#[test]
+fn set_get_linger() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .expect("unable to register TCP stream");
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    let (mut peer, _) = listener.accept().unwrap();
+
+    assert_eq!(stream.linger().unwrap(), None);
+    stream.set_linger(Some(Duration::from_secs(5))).unwrap();
+    assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(5)));
+
+    // Linger times shorter than a second are rounded up, rather than down to
+    // zero which would reset the connection.
+    stream.set_linger(Some(Duration::from_millis(500))).unwrap();
+    assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(1)));
+
+    // Dropping the stream with a linger time of zero resets the connection.
+    stream.set_linger(Some(Duration::from_secs(0))).unwrap();
+    assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(0)));
+    drop(stream);
+    let mut buf = [0; 8];
+    let err = peer.read(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn set_get_linux_options() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let barrier = Arc::new(Barrier::new(2));
+    let (thread_handle, address) = start_listener(1, Some(barrier.clone()), false);
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .expect("unable to register TCP stream");
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+
+    stream
+        .set_user_timeout(Duration::from_millis(1500))
+        .unwrap();
+    assert_eq!(stream.user_timeout().unwrap(), Duration::from_millis(1500));
+    stream.set_user_timeout(Duration::from_micros(500)).unwrap();
+    assert_eq!(stream.user_timeout().unwrap(), Duration::from_millis(1));
+    stream.set_notsent_lowat(16 * 1024).unwrap();
+    assert_eq!(stream.notsent_lowat().unwrap(), 16 * 1024);
+    stream.set_quickack(true).unwrap();
+    assert!(stream.quickack().unwrap());
+    stream.set_cork(true).unwrap();
+    assert!(stream.cork().unwrap());
+    stream.set_cork(false).unwrap();
+    assert!(!stream.cork().unwrap());
+    assert!(stream.take_error().unwrap().is_none());
+
+    barrier.wait();
+    thread_handle.join().expect("unable to join thread");
+}
+
+#[test]
+#[cfg(unix)]
+fn set_recv_lowat() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::READABLE | Interest::WRITABLE)
+        .expect("unable to register TCP stream");
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    let (mut peer, _) = listener.accept().unwrap();
+
+    stream.set_recv_lowat(DATA1_LEN as u32 + 1).unwrap();
+    assert_eq!(stream.recv_lowat().unwrap(), DATA1_LEN as u32 + 1);
+
+    // Not readable until enough data is received.
+    peer.write_all(DATA1).unwrap();
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    expect_no_events(&mut poll, &mut events);
+    peer.write_all(DATA2).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+
+    let mut buf = [0; DATA1_LEN + DATA2_LEN];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf[..DATA1_LEN], DATA1);
+    assert_eq!(&buf[DATA1_LEN..], DATA2);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn tcp_info() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::READABLE | Interest::WRITABLE)
+        .expect("unable to register TCP stream");
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    let (mut peer, _) = listener.accept().unwrap();
+
+    stream.write_all(DATA1).unwrap();
+    let mut buf = [0; DATA1_LEN];
+    peer.read_exact(&mut buf).unwrap();
+
+    let info = stream.tcp_info().unwrap();
+    assert_eq!(info.state(), TcpState::Established);
+    assert!(info.snd_mss() > 0);
+    assert!(info.snd_cwnd() >= info.snd_mss());
+    assert!(info.rto() > Duration::from_millis(0));
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        assert_eq!(info.unacked(), Some(0));
+        assert_eq!(info.lost(), Some(0));
+    }
+
+    // After the peer shuts down the connection is half closed.
+    drop(peer);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    assert_eq!(stream.tcp_info().unwrap().state(), TcpState::CloseWait);
+}
+
+#[test]
+fn queue_len() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::READABLE | Interest::WRITABLE)
+        .expect("unable to register TCP stream");
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    let (mut peer, _) = listener.accept().unwrap();
+
+    assert_eq!(stream.recv_queue_len().unwrap(), 0);
+    assert_send_queue_len!(stream, 0);
+
+    peer.write_all(DATA1).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    assert_eq!(stream.recv_queue_len().unwrap(), DATA1_LEN);
+
+    let mut buf = [0; DATA1_LEN];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(stream.recv_queue_len().unwrap(), 0);
+}
+
 #[test]
 fn shutdown_read() {
     let (mut poll, mut events) = init_with_poll();
@@ -636,6 +892,40 @@ fn tcp_shutdown_client_both_close_event() {
     handle.join().expect("failed to join thread");
 }
 
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn tcp_stream_priority_event() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let stream = net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    stream.set_nonblocking(true).unwrap();
+    let mut stream = TcpStream::from_std(stream);
+    let (peer, _) = listener.accept().unwrap();
+
+    poll.registry()
+        .register(&mut stream, ID1, Interest::PRIORITY)
+        .unwrap();
+    expect_no_events(&mut poll, &mut events);
+
+    // Send a single byte of out-of-band (urgent) data.
+    let n = unsafe {
+        libc::send(
+            peer.as_raw_fd(),
+            DATA1.as_ptr() as *const libc::c_void,
+            1,
+            libc::MSG_OOB,
+        )
+    };
+    assert_eq!(n, 1);
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Readiness::PRIORITY)],
+    );
+}
+
 /// Start a listener that accepts `n_connections` connections on the returned
 /// address. It echos back any data it reads from the connection before
 /// accepting another one.
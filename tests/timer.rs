@@ -0,0 +1,200 @@
+#![cfg(all(
+    feature = "os-poll",
+    feature = "os-util",
+    any(target_os = "android", target_os = "linux")
+))]
+
+use mio::unix::{Clock, Timer};
+use mio::{Interest, Token};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+mod util;
+use util::{
+    assert_send, assert_socket_close_on_exec, assert_socket_non_blocking, assert_sync,
+    assert_would_block, expect_events, expect_no_events, init_with_poll, ExpectEvent,
+};
+
+const ID: Token = Token(0);
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Timer>();
+    assert_sync::<Timer>();
+}
+
+#[test]
+fn timer_timeout() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut timer = Timer::new(Clock::Monotonic).unwrap();
+    assert_socket_non_blocking(&timer);
+    assert_socket_close_on_exec(&timer);
+    poll.registry()
+        .register(&mut timer, ID, Interest::READABLE)
+        .unwrap();
+
+    // Not armed.
+    assert!(timer.remaining().unwrap().is_none());
+    assert_would_block(timer.read());
+    expect_no_events(&mut poll, &mut events);
+
+    let start = Instant::now();
+    timer.set_timeout(Duration::from_millis(20)).unwrap();
+    assert!(timer.remaining().unwrap().is_some());
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(timer.read().unwrap(), 1);
+    assert_would_block(timer.read());
+
+    // One-shot, so no more events.
+    assert!(timer.remaining().unwrap().is_none());
+    expect_no_events(&mut poll, &mut events);
+}
+
+#[test]
+fn timer_zero_timeout() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut timer = Timer::new(Clock::Monotonic).unwrap();
+    poll.registry()
+        .register(&mut timer, ID, Interest::READABLE)
+        .unwrap();
+
+    timer.set_timeout(Duration::from_secs(0)).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    assert_eq!(timer.read().unwrap(), 1);
+}
+
+#[test]
+fn timer_interval() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut timer = Timer::new(Clock::Monotonic).unwrap();
+    poll.registry()
+        .register(&mut timer, ID, Interest::READABLE)
+        .unwrap();
+
+    timer.set_interval(Duration::from_millis(10)).unwrap();
+    for _ in 0..3 {
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(ID, Interest::READABLE)],
+        );
+        assert!(timer.read().unwrap() >= 1);
+    }
+
+    // Not reading the timer accumulates the expirations.
+    sleep(Duration::from_millis(50));
+    assert!(timer.read().unwrap() >= 3);
+
+    timer.disarm().unwrap();
+    assert!(timer.remaining().unwrap().is_none());
+    let _ = timer.read();
+    // Readiness from before the timer was disarmed may still be reported, but
+    // it shouldn't expire again.
+    poll.poll(&mut events, Some(Duration::from_millis(0)))
+        .unwrap();
+    sleep(Duration::from_millis(20));
+    expect_no_events(&mut poll, &mut events);
+}
+
+#[test]
+fn timer_zero_interval() {
+    let timer = Timer::new(Clock::Monotonic).unwrap();
+    let err = timer.set_interval(Duration::from_secs(0)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn timer_deadline() {
+    let (mut poll, mut events) = init_with_poll();
+
+    for clock in [Clock::Monotonic, Clock::Boottime].iter().copied() {
+        let mut timer = Timer::new(clock).unwrap();
+        poll.registry()
+            .register(&mut timer, ID, Interest::READABLE)
+            .unwrap();
+
+        // Deadline in the past.
+        let now = clock.now().unwrap();
+        timer.set_deadline(now / 2, None).unwrap();
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(ID, Interest::READABLE)],
+        );
+        assert_eq!(timer.read().unwrap(), 1);
+
+        let deadline = clock.now().unwrap() + Duration::from_millis(20);
+        timer
+            .set_deadline(deadline, Some(Duration::from_millis(10)))
+            .unwrap();
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(ID, Interest::READABLE)],
+        );
+        assert!(clock.now().unwrap() >= deadline);
+        assert!(timer.read().unwrap() >= 1);
+
+        // Periodic after the deadline.
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(ID, Interest::READABLE)],
+        );
+        assert!(timer.read().unwrap() >= 1);
+        timer.disarm().unwrap();
+
+        poll.registry().deregister(&mut timer).unwrap();
+    }
+}
+
+#[test]
+fn timer_reset() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut timer = Timer::new(Clock::Boottime).unwrap();
+    poll.registry()
+        .register(&mut timer, ID, Interest::READABLE)
+        .unwrap();
+
+    // Setting the timer again replaces the previous setting.
+    timer.set_timeout(Duration::from_millis(10)).unwrap();
+    timer.set_timeout(Duration::from_secs(60)).unwrap();
+    sleep(Duration::from_millis(20));
+    expect_no_events(&mut poll, &mut events);
+    assert_would_block(timer.read());
+}
+
+#[test]
+fn timer_from_raw_fd() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let timer = Timer::new(Clock::Monotonic).unwrap();
+    let fd = timer.into_raw_fd();
+    let mut timer = unsafe { Timer::from_raw_fd(fd) };
+    assert_eq!(timer.as_raw_fd(), fd);
+
+    poll.registry()
+        .register(&mut timer, ID, Interest::READABLE)
+        .unwrap();
+    timer.set_timeout(Duration::from_millis(1)).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID, Interest::READABLE)],
+    );
+    assert_eq!(timer.read().unwrap(), 1);
+}
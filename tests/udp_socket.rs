@@ -135,6 +135,41 @@ fn set_get_ttl() {
     assert!(socket1.take_error().unwrap().is_none());
 }
 
+#[test]
+fn queue_len() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let socket2 = UdpSocket::bind(any_local_address()).unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+
+    assert_eq!(socket1.recv_queue_len().unwrap(), 0);
+    assert_send_queue_len!(socket2, 0);
+
+    let address = socket1.local_addr().unwrap();
+    checked_write!(socket2.send_to(DATA1, address));
+    checked_write!(socket2.send_to(DATA2, address));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+
+    // Linux only returns the size of the next datagram.
+    let len = socket1.recv_queue_len().unwrap();
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(len, DATA1.len());
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    assert!(len >= DATA1.len());
+
+    let mut buf = [0; 20];
+    expect_read!(socket1.recv_from(&mut buf), DATA1, __anywhere);
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(socket1.recv_queue_len().unwrap(), DATA2.len());
+}
+
 #[test]
 fn get_ttl_without_previous_set() {
     let socket1 = UdpSocket::bind(any_local_address()).unwrap();
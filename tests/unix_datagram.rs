@@ -165,6 +165,30 @@ fn unix_datagram_pair() {
     assert!(datagram2.take_error().unwrap().is_none());
 }
 
+#[test]
+fn unix_datagram_queue_len() {
+    init();
+
+    let (datagram1, datagram2) = UnixDatagram::pair().unwrap();
+    assert_eq!(datagram2.recv_queue_len().unwrap(), 0);
+    assert_send_queue_len!(datagram1, 0);
+
+    checked_write!(datagram1.send(DATA1));
+    checked_write!(datagram1.send(DATA2));
+    // Linux only returns the size of the next datagram.
+    let len = datagram2.recv_queue_len().unwrap();
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(len, DATA1.len());
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    assert!(len >= DATA1.len() + DATA2.len());
+
+    let mut buf = [0; DEFAULT_BUF_SIZE];
+    expect_read!(datagram2.recv(&mut buf), DATA1);
+    expect_read!(datagram2.recv(&mut buf), DATA2);
+    assert_eq!(datagram2.recv_queue_len().unwrap(), 0);
+    assert_send_queue_len!(datagram1, 0);
+}
+
 #[test]
 fn unix_datagram_shutdown() {
     let (mut poll, mut events) = init_with_poll();
@@ -0,0 +1,279 @@
+#![cfg(all(unix, feature = "os-poll", feature = "os-util"))]
+
+use mio::unix::pipe::{self, Receiver, Sender};
+use mio::{Events, Interest, Poll, Token};
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+mod util;
+use util::{
+    assert_send, assert_socket_close_on_exec, assert_socket_non_blocking, assert_sync,
+    assert_would_block, expect_events, expect_no_events, init_with_poll, ExpectEvent,
+};
+
+const RECEIVER: Token = Token(0);
+const SENDER: Token = Token(1);
+
+const DATA1: &[u8; 11] = b"Hello world";
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Sender>();
+    assert_sync::<Sender>();
+    assert_send::<Receiver>();
+    assert_sync::<Receiver>();
+}
+
+#[test]
+fn smoke() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut sender, mut receiver) = pipe::new().unwrap();
+    assert_socket_non_blocking(&sender);
+    assert_socket_close_on_exec(&sender);
+    assert_socket_non_blocking(&receiver);
+    assert_socket_close_on_exec(&receiver);
+
+    poll.registry()
+        .register(&mut receiver, RECEIVER, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut sender, SENDER, Interest::WRITABLE)
+        .unwrap();
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SENDER, Interest::WRITABLE)],
+    );
+
+    let mut buf = [0; 20];
+    assert_would_block(receiver.read(&mut buf));
+
+    let n = sender.write(DATA1).unwrap();
+    assert_eq!(n, DATA1.len());
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(RECEIVER, Interest::READABLE)],
+    );
+
+    let n = receiver.read(&mut buf).unwrap();
+    assert_eq!(n, DATA1.len());
+    assert_eq!(&buf[..n], &DATA1[..]);
+    assert_would_block(receiver.read(&mut buf));
+}
+
+#[test]
+fn event_when_sender_is_dropped() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let (sender, mut receiver) = pipe::new().unwrap();
+    poll.registry()
+        .register(&mut receiver, RECEIVER, Interest::READABLE)
+        .unwrap();
+
+    let barrier = std::sync::Arc::new(std::sync::Barrier::new(2));
+    let thread_barrier = barrier.clone();
+    let handle = std::thread::spawn(move || {
+        thread_barrier.wait();
+        drop(sender);
+    });
+
+    expect_no_events(&mut poll, &mut events);
+    barrier.wait();
+    handle.join().unwrap();
+
+    expect_one_closed_event(&mut poll, &mut events, RECEIVER, true);
+
+    // Reading now returns the end of the file.
+    let mut buf = [0; 20];
+    assert_eq!(receiver.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn event_when_receiver_is_dropped() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let (mut sender, receiver) = pipe::new().unwrap();
+    poll.registry()
+        .register(&mut sender, SENDER, Interest::WRITABLE)
+        .unwrap();
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SENDER, Interest::WRITABLE)],
+    );
+
+    drop(receiver);
+
+    expect_one_closed_event(&mut poll, &mut events, SENDER, false);
+}
+
+#[test]
+fn writable_after_full() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut sender, mut receiver) = pipe::new().unwrap();
+    poll.registry()
+        .register(&mut sender, SENDER, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SENDER, Interest::WRITABLE)],
+    );
+
+    // Fill the pipe.
+    let buf = [0; 4096];
+    loop {
+        match sender.write(&buf) {
+            Ok(_) => continue,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+    expect_no_events(&mut poll, &mut events);
+
+    // Empty the pipe.
+    let mut buf = [0; 4096];
+    loop {
+        match receiver.read(&mut buf) {
+            Ok(_) => continue,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SENDER, Interest::WRITABLE)],
+    );
+}
+
+#[test]
+fn set_nonblocking() {
+    let (sender, receiver) = pipe::new().unwrap();
+
+    sender.set_nonblocking(false).unwrap();
+    receiver.set_nonblocking(false).unwrap();
+    assert_blocking(&sender);
+    assert_blocking(&receiver);
+
+    sender.set_nonblocking(true).unwrap();
+    receiver.set_nonblocking(true).unwrap();
+    assert_socket_non_blocking(&sender);
+    assert_socket_non_blocking(&receiver);
+}
+
+#[test]
+fn from_child_process_io() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut child = Command::new("/bin/cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut sender = Sender::from(child.stdin.take().unwrap());
+    let mut receiver = Receiver::from(child.stdout.take().unwrap());
+    let stderr = Receiver::from(child.stderr.take().unwrap());
+    assert_socket_non_blocking(&sender);
+    assert_socket_non_blocking(&receiver);
+    assert_socket_non_blocking(&stderr);
+
+    poll.registry()
+        .register(&mut receiver, RECEIVER, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut sender, SENDER, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SENDER, Interest::WRITABLE)],
+    );
+
+    let mut buf = [0; 20];
+    assert_would_block(receiver.read(&mut buf));
+    assert_would_block((&stderr).read(&mut buf));
+
+    assert_eq!(sender.write(DATA1).unwrap(), DATA1.len());
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(RECEIVER, Interest::READABLE)],
+    );
+    let mut n = 0;
+    while n < DATA1.len() {
+        match receiver.read(&mut buf[n..]) {
+            Ok(read) => n += read,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                expect_events(
+                    &mut poll,
+                    &mut events,
+                    vec![ExpectEvent::new(RECEIVER, Interest::READABLE)],
+                );
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+    assert_eq!(&buf[..n], &DATA1[..]);
+
+    // Closing stdin makes `cat` exit, closing its stdout.
+    drop(sender);
+    expect_one_closed_event(&mut poll, &mut events, RECEIVER, true);
+    assert_eq!(receiver.read(&mut buf).unwrap(), 0);
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn from_raw_fd() {
+    let (sender, receiver) = pipe::new().unwrap();
+    let fd = sender.into_raw_fd();
+    let mut sender = unsafe { Sender::from_raw_fd(fd) };
+    assert_eq!(sender.as_raw_fd(), fd);
+    let fd = receiver.into_raw_fd();
+    let mut receiver = unsafe { Receiver::from_raw_fd(fd) };
+    assert_eq!(receiver.as_raw_fd(), fd);
+
+    assert_eq!(sender.write(DATA1).unwrap(), DATA1.len());
+    let mut buf = [0; 20];
+    assert_eq!(receiver.read(&mut buf).unwrap(), DATA1.len());
+    assert_eq!(&buf[..DATA1.len()], &DATA1[..]);
+}
+
+/// Expect a single event for `token`, with the other side closed.
+fn expect_one_closed_event(poll: &mut Poll, events: &mut Events, token: Token, read: bool) {
+    poll.poll(events, Some(Duration::from_secs(1))).unwrap();
+    let mut iter = events.iter();
+    let event = iter.next().unwrap();
+    assert_eq!(event.token(), token, "invalid token, event: {:#?}", event);
+    if read {
+        assert!(
+            event.is_read_closed(),
+            "expected closed or error, event: {:#?}",
+            event
+        );
+    } else {
+        assert!(
+            event.is_write_closed(),
+            "expected closed or error, event: {:#?}",
+            event
+        );
+    }
+    assert!(iter.next().is_none());
+}
+
+fn assert_blocking<F: AsRawFd>(fd: &F) {
+    let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFL) };
+    assert!(flags != -1);
+    assert_eq!(flags & libc::O_NONBLOCK, 0);
+}
@@ -0,0 +1,221 @@
+#![cfg(all(unix, feature = "os-poll", feature = "os-util"))]
+
+use mio::unix::pty::{self, Master, Slave, WindowSize};
+use mio::{Events, Interest, Poll, Token};
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+use std::process::Command;
+use std::time::Duration;
+
+mod util;
+use util::{
+    assert_send, assert_socket_close_on_exec, assert_socket_non_blocking, assert_sync,
+    assert_would_block, expect_events, init_with_poll, poll_retry, ExpectEvent,
+};
+
+const MASTER: Token = Token(0);
+const PROCESS: Token = Token(1);
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<Master>();
+    assert_sync::<Master>();
+    assert_send::<Slave>();
+    assert_sync::<Slave>();
+}
+
+#[test]
+fn smoke() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut master, mut slave) = pty::new().unwrap();
+    assert_socket_non_blocking(&master);
+    assert_socket_close_on_exec(&master);
+    assert_socket_close_on_exec(&slave);
+
+    poll.registry()
+        .register(&mut master, MASTER, Interest::READABLE)
+        .unwrap();
+
+    let mut buf = [0; 20];
+    assert_would_block(master.read(&mut buf));
+
+    // Output of the program inside the pseudo-terminal.
+    slave.write_all(b"Hello world").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(MASTER, Interest::READABLE)],
+    );
+    let n = master.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello world");
+    assert_would_block(master.read(&mut buf));
+
+    // Input for the program, in canonical mode a line at a time.
+    master.write_all(b"input\n").unwrap();
+    let n = slave.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"input\n");
+}
+
+#[test]
+fn window_size() {
+    let (master, slave) = pty::new().unwrap();
+
+    let size = WindowSize {
+        rows: 24,
+        columns: 80,
+        width: 0,
+        height: 0,
+    };
+    master.set_window_size(size).unwrap();
+    assert_eq!(master.window_size().unwrap(), size);
+
+    // Should also be visible on the slave side.
+    let mut sys_size = unsafe { std::mem::zeroed::<libc::winsize>() };
+    assert_ne!(
+        unsafe { libc::ioctl(slave.as_raw_fd(), libc::TIOCGWINSZ as _, &mut sys_size) },
+        -1
+    );
+    assert_eq!(sys_size.ws_row, 24);
+    assert_eq!(sys_size.ws_col, 80);
+
+    let size = WindowSize {
+        rows: 50,
+        columns: 132,
+        width: 1320,
+        height: 1000,
+    };
+    master.set_window_size(size).unwrap();
+    assert_eq!(master.window_size().unwrap(), size);
+}
+
+#[test]
+fn spawn_sh() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let (mut master, slave) = pty::new().unwrap();
+    master
+        .set_window_size(WindowSize {
+            rows: 30,
+            columns: 100,
+            ..WindowSize::default()
+        })
+        .unwrap();
+    poll.registry()
+        .register(&mut master, MASTER, Interest::READABLE)
+        .unwrap();
+
+    // Disable the prompt to simplify checking the output.
+    let mut command = Command::new("/bin/sh");
+    command.env("PS1", "");
+    let mut process = slave.spawn(&mut command).unwrap();
+    drop(slave);
+    poll.registry()
+        .register(&mut process, PROCESS, Interest::READABLE)
+        .unwrap();
+
+    master
+        .write_all(b"test -t 0 && echo is_a_tty; stty size; echo $((40 + 2)); exit 7\n")
+        .unwrap();
+
+    let output = read_until_closed(&mut poll, &mut events, &mut master);
+    let output = String::from_utf8_lossy(&output);
+    // The output includes the echoed input, so only match complete lines.
+    let lines: Vec<&str> = output.lines().map(|line| line.trim_end()).collect();
+    assert!(lines.contains(&"is_a_tty"), "output: {:?}", output);
+    assert!(lines.contains(&"30 100"), "output: {:?}", output);
+    assert!(lines.contains(&"42"), "output: {:?}", output);
+
+    let mut status = None;
+    while status.is_none() {
+        status = process.try_wait().unwrap();
+        if status.is_none() {
+            poll_retry(&mut poll, &mut events, None);
+        }
+    }
+    assert_eq!(status.unwrap().code(), Some(7));
+}
+
+#[test]
+fn spawn_same_command_twice() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut command = Command::new("/bin/sh");
+    command.arg("-c").arg("test -t 0 && echo is_a_tty");
+    for _ in 0..2 {
+        let (mut master, slave) = pty::new().unwrap();
+        poll.registry()
+            .register(&mut master, MASTER, Interest::READABLE)
+            .unwrap();
+
+        let mut process = slave.spawn(&mut command).unwrap();
+        drop(slave);
+        poll.registry()
+            .register(&mut process, PROCESS, Interest::READABLE)
+            .unwrap();
+
+        let output = read_until_closed(&mut poll, &mut events, &mut master);
+        let output = String::from_utf8_lossy(&output);
+        assert_eq!(output.trim_end(), "is_a_tty");
+
+        let mut status = None;
+        while status.is_none() {
+            status = process.try_wait().unwrap();
+            if status.is_none() {
+                poll_retry(&mut poll, &mut events, None);
+            }
+        }
+        assert!(status.unwrap().success());
+        poll.registry().deregister(&mut master).unwrap();
+        poll.registry().deregister(&mut process).unwrap();
+    }
+}
+
+#[test]
+fn from_raw_fd() {
+    let (master, slave) = pty::new().unwrap();
+    let fd = master.into_raw_fd();
+    let mut master = unsafe { Master::from_raw_fd(fd) };
+    assert_eq!(master.as_raw_fd(), fd);
+    let fd = slave.into_raw_fd();
+    let mut slave = unsafe { Slave::from_raw_fd(fd) };
+    assert_eq!(slave.as_raw_fd(), fd);
+
+    slave.write_all(b"Hello world").unwrap();
+    let mut buf = [0; 20];
+    let n = master.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello world");
+}
+
+/// Read from `master` until the slave side is closed, expecting an event with
+/// `is_read_closed` set.
+fn read_until_closed(poll: &mut Poll, events: &mut Events, master: &mut Master) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut buf = [0; 256];
+    let mut read_closed = false;
+    loop {
+        match master.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => output.extend_from_slice(&buf[..n]),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                poll_retry(poll, events, Some(Duration::from_secs(2)));
+                read_closed |= events
+                    .iter()
+                    .any(|event| event.token() == MASTER && event.is_read_closed());
+            }
+            // Linux returns `EIO` once the slave side is closed.
+            Err(ref err) if err.raw_os_error() == Some(libc::EIO) => break,
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+
+    if !read_closed {
+        // Haven't polled since the slave side was closed.
+        poll_retry(poll, events, Some(Duration::from_secs(1)));
+        read_closed = events
+            .iter()
+            .any(|event| event.token() == MASTER && event.is_read_closed());
+    }
+    assert!(read_closed, "expected read closed event");
+    output
+}
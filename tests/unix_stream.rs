@@ -124,6 +124,26 @@ fn unix_stream_pair() {
     assert_would_block(s2.read(&mut buf));
 }
 
+#[test]
+fn unix_stream_queue_len() {
+    init();
+
+    let (mut s1, mut s2) = UnixStream::pair().unwrap();
+    assert_eq!(s2.recv_queue_len().unwrap(), 0);
+    assert_send_queue_len!(s1, 0);
+
+    checked_write!(s1.write(&DATA1));
+    assert_eq!(s2.recv_queue_len().unwrap(), DATA1_LEN);
+    // Includes the kernel's overhead.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert!(s1.send_queue_len().unwrap() >= DATA1_LEN);
+
+    let mut buf = [0; DEFAULT_BUF_SIZE];
+    expect_read!(s2.read(&mut buf), DATA1);
+    assert_eq!(s2.recv_queue_len().unwrap(), 0);
+    assert_send_queue_len!(s1, 0);
+}
+
 #[test]
 fn unix_stream_peer_addr() {
     init();
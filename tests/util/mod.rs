@@ -127,6 +127,9 @@ impl From<Interest> for Readiness {
         if interests.is_lio() {
             readiness.0 |= LIO;
         }
+        if interests.is_priority() {
+            readiness.0 |= PRIORITY;
+        }
         readiness
     }
 }
@@ -136,8 +139,7 @@ pub fn expect_events(poll: &mut Poll, events: &mut Events, mut expected: Vec<Exp
     // poll returns the first event only in a single call. To be a bit more
     // lenient we'll poll a couple of times.
     for _ in 0..3 {
-        poll.poll(events, Some(Duration::from_millis(500)))
-            .expect("unable to poll");
+        poll_retry(poll, events, Some(Duration::from_millis(500)));
 
         for event in events.iter() {
             let index = expected.iter().position(|expected| expected.matches(event));
@@ -163,8 +165,7 @@ pub fn expect_events(poll: &mut Poll, events: &mut Events, mut expected: Vec<Exp
 }
 
 pub fn expect_no_events(poll: &mut Poll, events: &mut Events) {
-    poll.poll(events, Some(Duration::from_millis(50)))
-        .expect("unable to poll");
+    poll_retry(poll, events, Some(Duration::from_millis(50)));
     if !events.is_empty() {
         for event in events.iter() {
             error!("unexpected event: {:?}", event);
@@ -173,6 +174,18 @@ pub fn expect_no_events(poll: &mut Poll, events: &mut Events) {
     }
 }
 
+/// Poll, retrying if the call is interrupted by a signal, e.g. `SIGCHLD` when
+/// using `unix::Process`.
+pub fn poll_retry(poll: &mut Poll, events: &mut Events, timeout: Option<Duration>) {
+    loop {
+        match poll.poll(events, timeout) {
+            Ok(()) => return,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => panic!("unable to poll: {}", err),
+        }
+    }
+}
+
 /// Assert that `result` is an error and the formatted error (via
 /// `fmt::Display`) equals `expected_msg`.
 pub fn assert_error<T, E: fmt::Display>(result: Result<T, E>, expected_msg: &str) {
@@ -311,3 +324,21 @@ macro_rules! expect_read {
         assert_eq!(address, source);
     }};
 }
+
+/// Asserts the length of the send queue of `$socket` on the platforms that
+/// support `send_queue_len`, does nothing on others.
+///
+/// Usage: `assert_send_queue_len!(stream, 0);`.
+macro_rules! assert_send_queue_len {
+    ($socket: expr, $expected: expr) => {
+        #[cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "netbsd"
+        ))]
+        assert_eq!($socket.send_queue_len().unwrap(), $expected);
+    };
+}
@@ -28,6 +28,15 @@ fn waker() {
     expect_waker_event(&mut poll, &mut events, token);
 }
 
+#[test]
+fn waker_reserved_token() {
+    init();
+
+    let poll = Poll::new().expect("unable to create new Poll instance");
+    let err = Waker::new(poll.registry(), Token(usize::max_value())).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
 #[test]
 fn waker_multiple_wakeups_same_thread() {
     init();